@@ -0,0 +1,191 @@
+// Static analysis of EVM bytecode.
+
+use std::collections::BTreeSet;
+
+use libsofl_core::engine::types::{opcode, Bytes};
+
+/// An instruction in the bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub pc: usize,
+    pub opcode: u8,
+    /// The immediate bytes of PUSH1 to PUSH32, which may be shorter than
+    /// expected if the code ends in the middle of them.
+    pub operand: Option<Bytes>,
+}
+
+impl Instruction {
+    /// The pc of the next instruction.
+    pub fn next_pc(&self) -> usize {
+        self.pc + 1 + self.operand.as_ref().map_or(0, |o| o.len())
+    }
+}
+
+/// The number of immediate bytes following the opcode.
+fn immediate_size(op: u8) -> usize {
+    if (opcode::PUSH1..=opcode::PUSH32).contains(&op) {
+        (op - opcode::PUSH1 + 1) as usize
+    } else {
+        0
+    }
+}
+
+/// Disassemble the bytecode into instructions.
+/// The immediate bytes of PUSH instructions are their operands,
+/// instead of being decoded as opcodes.
+pub fn disassemble(code: &Bytes) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = code[pc];
+        let size = immediate_size(op);
+        let operand = (size > 0).then(|| {
+            let end = (pc + 1 + size).min(code.len());
+            code.slice(pc + 1..end)
+        });
+        let instruction = Instruction {
+            pc,
+            opcode: op,
+            operand,
+        };
+        pc = instruction.next_pc();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+/// The valid jump destinations, i.e., pcs of `JUMPDEST` instructions,
+/// excluding `0x5b` bytes in the operands of PUSH instructions.
+pub fn jump_dests(code: &Bytes) -> BTreeSet<usize> {
+    disassemble(code)
+        .into_iter()
+        .filter(|i| i.opcode == opcode::JUMPDEST)
+        .map(|i| i.pc)
+        .collect()
+}
+
+/// How control leaves a basic block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Terminator {
+    /// `JUMP`.
+    Jump,
+    /// `JUMPI`, which falls through to the next block if not taken.
+    ConditionalJump,
+    /// `STOP`, `RETURN`, `REVERT`, `INVALID`, or `SELFDESTRUCT`,
+    /// with the opcode.
+    Halt(u8),
+    /// The next instruction is a `JUMPDEST` or the code ends.
+    FallThrough,
+}
+
+/// A sequence of instructions that is only entered at the first one
+/// and only left at the last one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    pub start_pc: usize,
+    /// The pc of the last instruction.
+    pub end_pc: usize,
+    pub terminator: Terminator,
+}
+
+/// Partition the bytecode into basic blocks,
+/// which start at `JUMPDEST`s and end at jumps or halting instructions.
+pub fn basic_blocks(code: &Bytes) -> Vec<BasicBlock> {
+    let instructions = disassemble(code);
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    for (i, instruction) in instructions.iter().enumerate() {
+        let terminator = match instruction.opcode {
+            opcode::JUMP => Some(Terminator::Jump),
+            opcode::JUMPI => Some(Terminator::ConditionalJump),
+            op @ (opcode::STOP
+            | opcode::RETURN
+            | opcode::REVERT
+            | opcode::INVALID
+            | opcode::SELFDESTRUCT) => Some(Terminator::Halt(op)),
+            _ => {
+                let next = instructions.get(i + 1);
+                next.map_or(true, |n| n.opcode == opcode::JUMPDEST)
+                    .then_some(Terminator::FallThrough)
+            }
+        };
+        if let Some(terminator) = terminator {
+            blocks.push(BasicBlock {
+                start_pc: instructions[start].pc,
+                end_pc: instruction.pc,
+                terminator,
+            });
+            start = i + 1;
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::engine::types::{opcode, Bytes};
+
+    use super::{
+        basic_blocks, disassemble, jump_dests, BasicBlock, Terminator,
+    };
+
+    #[test]
+    fn test_disassemble_push_operands() {
+        let code = Bytes::from(vec![
+            0x60, 0x5b, // PUSH1 0x5b
+            0x5b, // JUMPDEST
+            0x61, 0x00, 0x5b, // PUSH2 0x005b
+            0x5f, // PUSH0
+            0x56, // JUMP
+            0x62, 0x01, // truncated PUSH3
+        ]);
+        let instructions = disassemble(&code);
+        let summary: Vec<_> = instructions
+            .iter()
+            .map(|i| (i.pc, i.opcode, i.operand.as_ref().map(|o| o.to_vec())))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0, opcode::PUSH1, Some(vec![0x5b])),
+                (2, opcode::JUMPDEST, None),
+                (3, opcode::PUSH2, Some(vec![0x00, 0x5b])),
+                (6, opcode::PUSH0, None),
+                (7, opcode::JUMP, None),
+                (8, opcode::PUSH3, Some(vec![0x01])),
+            ]
+        );
+        assert_eq!(jump_dests(&code).into_iter().collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_basic_blocks_with_branch() {
+        // if (calldatasize() == 0) { stop } else { revert }
+        let code = Bytes::from(vec![
+            0x36, // 0: CALLDATASIZE
+            0x15, // 1: ISZERO
+            0x60, 0x08, // 2: PUSH1 0x08
+            0x57, // 4: JUMPI
+            0x60, 0x5b, // 5: PUSH1 0x5b, not a jump destination
+            0xfd, // 7: REVERT
+            0x5b, // 8: JUMPDEST
+            0x60, 0x00, // 9: PUSH1 0
+            0x5b, // 11: JUMPDEST
+            0x00, // 12: STOP
+        ]);
+        let block = |start_pc, end_pc, terminator| BasicBlock {
+            start_pc,
+            end_pc,
+            terminator,
+        };
+        assert_eq!(
+            basic_blocks(&code),
+            vec![
+                block(0, 4, Terminator::ConditionalJump),
+                block(5, 7, Terminator::Halt(opcode::REVERT)),
+                block(8, 9, Terminator::FallThrough),
+                block(11, 12, Terminator::Halt(opcode::STOP)),
+            ]
+        );
+    }
+}
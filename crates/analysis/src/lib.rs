@@ -1 +1,3 @@
+pub mod bytecode;
+pub mod reentrancy;
 pub mod taint;
@@ -0,0 +1,138 @@
+use libsofl_core::{
+    blockchain::{
+        provider::{BcProvider, BcStateProvider},
+        transaction::Tx,
+    },
+    engine::{
+        memory::MemoryBcState,
+        state::BcState,
+        transition::TransitionSpec,
+        types::{BcStateRef, ExecutionResult, TxHash},
+    },
+    error::SoflError,
+};
+
+use crate::{default_policy, policies};
+
+use super::{
+    policy::TaintPolicy, sink::results::TaintResults,
+    source::tx_input::TxInputSource, TaintAnalyzer,
+};
+
+/// The taint analysis of a historical transaction.
+#[derive(Debug, Clone)]
+pub struct TaintReport {
+    pub tx: TxHash,
+    /// The result of the replayed transaction.
+    pub result: ExecutionResult,
+    /// Where tainted data reaches a sink.
+    pub results: TaintResults,
+}
+
+/// Replay the transaction on the state forked right before it,
+/// with taint introduced by `sources`, propagated by `policy`,
+/// and recorded in `sinks`.
+/// Memory taint is tracked at the granularity of 32-byte words.
+pub fn analyze_tx<T, S, P, Src, Pol>(
+    provider: &P,
+    tx_hash: TxHash,
+    policy: Pol,
+    sources: Src,
+    mut sinks: TaintResults,
+) -> Result<TaintReport, SoflError>
+where
+    T: Tx,
+    S: BcStateRef,
+    S::Error: std::fmt::Debug,
+    P: BcProvider<T> + BcStateProvider<S>,
+    Src: for<'a> TaintPolicy<&'a mut MemoryBcState<S>>,
+    Pol: for<'a> TaintPolicy<&'a mut MemoryBcState<S>>,
+{
+    let tx = provider.tx(tx_hash.into())?;
+    let position = tx.position().ok_or_else(|| {
+        SoflError::NotFound(format!("transaction with hash {}", tx_hash))
+    })?;
+    let mut state = provider.bc_state_at(position)?;
+    let spec = TransitionSpec::from_tx_hash(provider, tx_hash)?;
+    let mut analyzer =
+        TaintAnalyzer::new(policies!(sources, policy, &mut sinks), 32);
+    let result = state
+        .transit(spec, &mut analyzer)?
+        .pop()
+        .expect("one transaction is executed");
+    Ok(TaintReport {
+        tx: tx_hash,
+        result,
+        results: sinks,
+    })
+}
+
+/// Same as `analyze_tx`, with the transaction input as the source
+/// and the default propagation policy.
+pub fn analyze_tx_input<T, S, P>(
+    provider: &P,
+    tx_hash: TxHash,
+) -> Result<TaintReport, SoflError>
+where
+    T: Tx,
+    S: BcStateRef,
+    S::Error: std::fmt::Debug,
+    P: BcProvider<T> + BcStateProvider<S>,
+{
+    analyze_tx(
+        provider,
+        tx_hash,
+        default_policy!(),
+        TxInputSource::default(),
+        TaintResults::default(),
+    )
+}
+
+#[cfg(test)]
+mod tests_with_dep {
+    use libsofl_core::{
+        blockchain::{provider::BcProvider, transaction::Tx},
+        conversion::ConvertTo,
+        engine::types::Address,
+    };
+    use libsofl_reth::config::RethConfig;
+    use libsofl_utils::config::Config;
+
+    use crate::taint::sink::results::{TaintedOp, TaintedOpKind};
+
+    use super::analyze_tx_input;
+
+    #[test]
+    fn test_analyze_usdt_transfer() {
+        let bp = RethConfig::must_load().bc_provider().unwrap();
+        let usdt: Address = "0xdAC17F958D2ee523a2206206994597C13D831ec7".cvt();
+        // transfer(address,uint256)
+        let selector = [0xa9, 0x05, 0x9c, 0xbb];
+        // the first direct USDT transfer since block 17000000
+        let tx = (17000000u64..17000100)
+            .flat_map(|bn| bp.txs_in_block(bn.into()).unwrap())
+            .find(|tx| {
+                tx.to() == Some(usdt) && tx.input().starts_with(&selector)
+            })
+            .expect("no USDT transfer");
+
+        let report = analyze_tx_input(&bp, tx.hash()).unwrap();
+        assert!(report.result.is_success());
+        // the balance of the recipient, keyed by the hash of the recipient
+        // in the calldata, is written with the amount in the calldata
+        let hit = report
+            .results
+            .hits
+            .iter()
+            .find(|hit| hit.sink.kind == TaintedOpKind::StorageWrite)
+            .expect("no tainted storage write");
+        assert_eq!(hit.sink.address, usdt);
+        assert_eq!(hit.sources, vec!["tx input".to_string()]);
+        let hashed = |op: &TaintedOp| op.kind == TaintedOpKind::Hash;
+        assert!(report
+            .results
+            .hits
+            .iter()
+            .any(|hit| hit.path.iter().any(hashed)));
+    }
+}
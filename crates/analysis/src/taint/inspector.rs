@@ -1,7 +1,7 @@
 use libsofl_core::engine::{
     inspector::EvmInspector,
     state::BcState,
-    types::{opcode, Inspector},
+    types::{opcode, Inspector, TxEnv},
 };
 
 use crate::taint::{
@@ -35,7 +35,8 @@ impl<S: BcState, P: TaintPolicy<S>> Inspector<S> for TaintAnalyzer<S, P> {
                 self.child_calls.last_mut().unwrap().replace(child_call);
                 self.child_calls.last_mut().unwrap().as_mut()
             }
-            _ => None,
+            // the most recent child call, e.g., for RETURNDATACOPY
+            _ => self.child_calls.last_mut().unwrap().as_mut(),
         };
         let mut current_taint_tracker = TaintTracker {
             stack: taint_stack,
@@ -232,4 +233,12 @@ impl<S: BcState, P: TaintPolicy<S>> Inspector<S> for TaintAnalyzer<S, P> {
     }
 }
 
-impl<S: BcState, P: TaintPolicy<S>> EvmInspector<S> for TaintAnalyzer<S, P> {}
+impl<S: BcState, P: TaintPolicy<S>> EvmInspector<S> for TaintAnalyzer<S, P> {
+    fn transaction(&mut self, tx: &TxEnv, _state: &S) -> bool {
+        if self.reset_storage {
+            self.storages.clear();
+        }
+        self.policy.transaction(tx);
+        true
+    }
+}
@@ -29,6 +29,17 @@ impl TaintableMemory {
         }
     }
 
+    /// Taint all words overlapping the bytes starting from the given offset,
+    /// i.e., the range is rounded outward to the word granularity.
+    pub fn taint_covering(&mut self, offset: usize, size: usize) {
+        if size == 0 {
+            return;
+        }
+        let start = offset / self.word_size * self.word_size;
+        let end = (offset + size).div_ceil(self.word_size) * self.word_size;
+        self.taint(start, end - start);
+    }
+
     /// Copy a slice of the taintable memory.
     pub fn slice(&self, offset: usize, size: usize) -> TaintableMemory {
         let start = offset / self.word_size;
@@ -62,6 +73,25 @@ impl TaintableMemory {
         }
     }
 
+    /// Copy the taint of a number of bytes from `src` to `dest`,
+    /// overwriting the taint of the destination, e.g., for `MCOPY`.
+    /// The ranges may overlap.
+    pub fn copy(&mut self, dest: usize, src: usize, size: usize) {
+        let start = dest / self.word_size;
+        let end = (dest + size) / self.word_size;
+        // read the source before the destination is cleaned
+        let tainted: Vec<bool> = (start..end)
+            .map(|i| {
+                let offset = (i * self.word_size + src).saturating_sub(dest);
+                self.is_tainted(offset, self.word_size)
+            })
+            .collect();
+        self.clean(dest, size);
+        for (i, tainted) in (start..end).zip(tainted) {
+            self.memory[i] = tainted;
+        }
+    }
+
     /// Check if a number of bytes starting from the given offset is tainted.
     pub fn is_tainted(&self, offset: usize, size: usize) -> bool {
         let start = offset / self.word_size;
@@ -7,6 +7,7 @@ pub mod stack;
 #[macro_use]
 pub mod policy;
 pub mod call;
+pub mod driver;
 pub mod propagation;
 pub mod sink;
 pub mod source;
@@ -17,7 +18,7 @@ use std::collections::HashMap;
 use libsofl_core::{
     engine::{
         state::BcState,
-        types::{Address, EvmContext, Interpreter},
+        types::{Address, EvmContext, Interpreter, U256},
     },
     error::SoflError,
 };
@@ -69,6 +70,10 @@ pub struct TaintAnalyzer<S: BcState, P: TaintPolicy<S>> {
     policy: P,
     storages: HashMap<Address, TaintableStorage>,
 
+    /// Whether storage taint is reset at the beginning of each transaction.
+    /// If false, storage taint is carried over to the following transactions.
+    reset_storage: bool,
+
     // nested taintable objects (akin to call stack)
     stacks: Vec<TaintableStack>,
     memories: Vec<TaintableMemory>,
@@ -81,12 +86,12 @@ pub struct TaintAnalyzer<S: BcState, P: TaintPolicy<S>> {
 }
 
 impl<S: BcState, P: TaintPolicy<S>> TaintAnalyzer<S, P> {
-    #[allow(unused)]
-    fn new(policy: P, memory_word_size: usize) -> Self {
+    pub fn new(policy: P, memory_word_size: usize) -> Self {
         Self {
             memory_word_size,
             policy,
             storages: HashMap::new(),
+            reset_storage: false,
             stacks: Vec::new(),
             memories: Vec::new(),
             calls: Vec::new(),
@@ -97,4 +102,21 @@ impl<S: BcState, P: TaintPolicy<S>> TaintAnalyzer<S, P> {
     }
 }
 
-impl<S: BcState, P: TaintPolicy<S>> TaintAnalyzer<S, P> {}
+impl<S: BcState, P: TaintPolicy<S>> TaintAnalyzer<S, P> {
+    /// Reset storage taint at the beginning of each transaction.
+    /// By default, storage taint is carried across transactions, so that
+    /// a sequence of transactions executed with the same analyzer is analyzed
+    /// incrementally, i.e., attacker-controlled state set in an earlier
+    /// transaction taints the reads in later transactions.
+    pub fn with_storage_reset(mut self) -> Self {
+        self.reset_storage = true;
+        self
+    }
+
+    /// Whether the storage slot of the contract is tainted.
+    pub fn is_storage_tainted(&self, address: Address, slot: U256) -> bool {
+        self.storages
+            .get(&address)
+            .is_some_and(|storage| storage.is_tainted(slot))
+    }
+}
@@ -1,11 +1,14 @@
 use libsofl_core::engine::{
-    state::BcState, types::EvmContext, types::Interpreter,
+    state::BcState, types::EvmContext, types::Interpreter, types::TxEnv,
 };
 
 use crate::taint::TaintTracker;
 
 #[auto_impl::auto_impl(&mut, Box)]
 pub trait TaintPolicy<S: BcState> {
+    /// Called before each transaction is analyzed.
+    fn transaction(&mut self, _tx: &TxEnv) {}
+
     /// Propagate taint before the execution of an instruction.
     /// The returned vector contains the stack taint effects of the instruction.
     /// The stack taint effects specifies which stack elements are tainted after the execution of the instruction.
@@ -50,7 +53,10 @@ mod tests {
         let policy = policies![MathPolicy {}, EnvPolicy {}];
         let mut analyzer = super::super::TaintAnalyzer::new(policy, 32);
         let mut state = MemoryBcState::fresh();
-        let spec = TransitionSpecBuilder::default().bypass_check().build();
+        let spec = TransitionSpecBuilder::default()
+            .bypass_check()
+            .build()
+            .unwrap();
         state.transit(spec, &mut analyzer).unwrap();
     }
 }
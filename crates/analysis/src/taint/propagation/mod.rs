@@ -6,7 +6,7 @@ pub mod nested_call;
 
 use libsofl_core::engine::{
     state::BcState,
-    types::{EvmContext, Interpreter},
+    types::{EvmContext, Interpreter, TxEnv},
 };
 
 #[macro_export]
@@ -62,6 +62,12 @@ impl<S: BcState> TaintPolicy<S> for () {
 impl<S: BcState, P1: TaintPolicy<S>, P2: TaintPolicy<S>> TaintPolicy<S>
     for (P1, P2)
 {
+    #[inline]
+    fn transaction(&mut self, tx: &TxEnv) {
+        self.0.transaction(tx);
+        self.1.transaction(tx);
+    }
+
     /// Propagate taint before the execution of an instruction.
     /// First, propagate taint according to the first policy.
     /// Then, propagate taint according to the second policy.
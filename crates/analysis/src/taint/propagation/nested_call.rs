@@ -22,19 +22,15 @@ impl<S: BcState> TaintPolicy<S> for NestedCallPolicy {
             }
             opcode::RETURNDATACOPY => {
                 stack_borrow!(interp, dest, offset, len);
-                if taint_tracker.stack.any_tainted(3) {
-                    taint_tracker.memory.taint(dest.cvt(), len.cvt());
-                } else {
-                    let len = len.cvt();
-                    let tainted = taint_tracker
-                        .child_call
-                        .as_ref()
-                        .expect("invalid bytecode: child_call is None")
-                        .return_data
-                        .is_tainted(offset.cvt(), len);
-                    if tainted {
-                        taint_tracker.memory.taint(dest.cvt(), len);
-                    }
+                let dest = dest.cvt();
+                let len = len.cvt();
+                // the return data is empty if there is no child call yet
+                let tainted = taint_tracker.stack.any_tainted(3)
+                    || taint_tracker.child_call.as_ref().is_some_and(|c| {
+                        c.return_data.is_tainted(offset.cvt(), len)
+                    });
+                if tainted {
+                    taint_tracker.memory.taint_covering(dest, len);
                 }
                 vec![]
             }
@@ -203,3 +199,101 @@ impl<S: BcState> TaintPolicy<S> for NestedCallPolicy {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            memory::MemoryBcState,
+            state::BcState,
+            types::{Address, SpecId, U256},
+        },
+        solidity::{caller::HighLevelCaller, scripting::compile_yul},
+    };
+
+    use crate::{
+        policies,
+        taint::{
+            propagation::{call::CallPolicy, execution::ExecutionPolicy},
+            source::calldata::CalldataSource,
+            TaintAnalyzer,
+        },
+    };
+
+    use super::NestedCallPolicy;
+
+    #[test]
+    fn test_return_data_copy() {
+        let mut state = MemoryBcState::fresh();
+        let mut analyzer = TaintAnalyzer::new(
+            policies!(
+                CalldataSource::default(),
+                ExecutionPolicy::default(),
+                CallPolicy::default(),
+                NestedCallPolicy::default()
+            ),
+            32,
+        );
+        let compile = |code: &str| {
+            let (_, code) = compile_yul("0.8.12", code).unwrap().remove(0);
+            code
+        };
+        // echoes the calldata
+        let echo = compile(
+            r#"
+        object "Echo" {
+            code {
+                mstore(0, calldataload(0))
+                return(0, 0x20)
+            }
+        }
+        "#,
+        );
+        let constant = compile(
+            r#"
+        object "Constant" {
+            code {
+                mstore(0, 42)
+                return(0, 0x20)
+            }
+        }
+        "#,
+        );
+        let caller = compile(
+            r#"
+        object "Caller" {
+            code {
+                // nothing is returned before any call
+                returndatacopy(0, 0, 0)
+                calldatacopy(0, 0, 0x20)
+                pop(call(gas(), 0x1234, 0, 0, 0x20, 0, 0))
+                returndatacopy(0x40, 0, 0x20)
+                sstore(0, mload(0x40))
+                pop(call(gas(), 0x5678, 0, 0, 0x20, 0, 0))
+                returndatacopy(0x80, 0, 0x20)
+                sstore(1, mload(0x80))
+                stop()
+            }
+        }
+        "#,
+        );
+        let contract = Address::ZERO;
+        state.replace_account_code(contract, caller.cvt()).unwrap();
+        state
+            .replace_account_code(0x1234.cvt(), echo.cvt())
+            .unwrap();
+        state
+            .replace_account_code(0x5678.cvt(), constant.cvt())
+            .unwrap();
+        let calldata: Vec<u8> = vec![0xab; 0x20];
+        HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .call(&mut state, contract, calldata.cvt(), None, &mut analyzer)
+            .unwrap();
+
+        assert!(analyzer.is_storage_tainted(contract, U256::ZERO));
+        assert!(!analyzer.is_storage_tainted(contract, U256::from(1)));
+    }
+}
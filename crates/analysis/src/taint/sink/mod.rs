@@ -1 +1,4 @@
+pub mod overflow;
+pub mod results;
+pub mod storage_write;
 pub mod tx_output;
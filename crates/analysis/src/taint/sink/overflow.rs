@@ -0,0 +1,131 @@
+use libsofl_core::engine::{
+    state::BcState,
+    types::{opcode, Address, EvmContext, Interpreter},
+};
+
+use crate::taint::{policy::TaintPolicy, TaintTracker};
+
+/// An arithmetic operation on tainted operands whose result wraps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverflowSite {
+    /// ADD, MUL, or SUB.
+    pub op: u8,
+    /// The storage context where the operation is executed.
+    pub address: Address,
+    pub pc: usize,
+}
+
+/// OverflowSink records ADD, MUL, and SUB with a tainted operand whose
+/// result wraps around 2^256, i.e., a potential integer overflow (or
+/// underflow) controlled by the attacker.
+///
+/// Since Solidity 0.8, checked arithmetic reverts on overflow, and wrapping
+/// is only expected in `unchecked` blocks or hand-written assembly, e.g.,
+/// overflow checks themselves. The sink is thus opt-in, and is intended
+/// for code compiled by older compilers or `unchecked` code.
+/// It should be composed after the source and propagation policies.
+#[derive(Debug, Clone, Default)]
+pub struct OverflowSink {
+    pub overflows: Vec<OverflowSite>,
+}
+
+impl<S: BcState> TaintPolicy<S> for OverflowSink {
+    fn before_step(
+        &mut self,
+        taint_tracker: &mut TaintTracker,
+        interp: &mut Interpreter,
+        _data: &mut EvmContext<S>,
+    ) -> Vec<Option<bool>> {
+        let op = interp.current_opcode();
+        if !matches!(op, opcode::ADD | opcode::MUL | opcode::SUB)
+            || !taint_tracker.stack.any_tainted(2)
+        {
+            return vec![];
+        }
+        stack_borrow!(interp, a, b);
+        // the result wraps iff it differs from the exact result
+        let (_, wrapped) = match op {
+            opcode::ADD => a.overflowing_add(*b),
+            opcode::MUL => a.overflowing_mul(*b),
+            _ => a.overflowing_sub(*b),
+        };
+        if wrapped {
+            self.overflows.push(OverflowSite {
+                op,
+                address: interp.contract().address,
+                pc: interp.program_counter(),
+            });
+        }
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            memory::MemoryBcState,
+            state::BcState,
+            types::{opcode, Address, U256},
+        },
+        solidity::{caller::HighLevelCaller, scripting::compile_yul},
+    };
+
+    use crate::{
+        policies,
+        taint::{
+            propagation::{execution::ExecutionPolicy, math::MathPolicy},
+            source::calldata::CalldataSource,
+            TaintAnalyzer,
+        },
+    };
+
+    use super::OverflowSink;
+
+    #[test]
+    fn test_tainted_mul_overflow() {
+        let mut state = MemoryBcState::fresh();
+        let mut sink = OverflowSink::default();
+        let mut analyzer = TaintAnalyzer::new(
+            policies!(
+                CalldataSource::default(),
+                ExecutionPolicy::default(),
+                MathPolicy::default(),
+                &mut sink
+            ),
+            32,
+        );
+        let (_, code) = compile_yul(
+            "0.8.12",
+            r#"
+        object "A" {
+            code {
+                let amount := calldataload(0)
+                // no overflow
+                sstore(0, add(amount, 1))
+                // overflows with a large amount
+                sstore(1, mul(amount, 3))
+                // wraps, but on untainted operands
+                sstore(2, sub(callvalue(), 1))
+                stop()
+            }
+        }
+        "#,
+        )
+        .unwrap()
+        .remove(0);
+        let contract = Address::ZERO;
+        state.replace_account_code(contract, code.cvt()).unwrap();
+        let amount = U256::MAX / U256::from(2);
+        let calldata: Vec<u8> = amount.to_be_bytes::<32>().to_vec();
+        HighLevelCaller::default()
+            .bypass_check()
+            .call(&mut state, contract, calldata.cvt(), None, &mut analyzer)
+            .unwrap();
+
+        assert_eq!(sink.overflows.len(), 1);
+        assert_eq!(sink.overflows[0].op, opcode::MUL);
+        assert_eq!(sink.overflows[0].address, contract);
+    }
+}
@@ -0,0 +1,387 @@
+use std::{collections::HashMap, fmt::Display};
+
+use libsofl_core::{
+    conversion::ConvertTo,
+    engine::{
+        state::BcState,
+        types::{opcode, Address, TxEnv, U256},
+    },
+};
+
+use crate::taint::policy::TaintPolicy;
+
+/// The kind of a key operation that consumes tainted data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaintedOpKind {
+    /// KECCAK256 over tainted memory.
+    Hash,
+    /// SSTORE with a tainted key or value.
+    StorageWrite,
+    /// CALL-like or CREATE-like with a tainted target, value, or input.
+    Call,
+    /// RETURN or REVERT of the transaction with tainted data.
+    Output,
+}
+
+impl Display for TaintedOpKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TaintedOpKind::Hash => "hash",
+            TaintedOpKind::StorageWrite => "storage write",
+            TaintedOpKind::Call => "call",
+            TaintedOpKind::Output => "output",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A key operation on tainted data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaintedOp {
+    pub kind: TaintedOpKind,
+    /// The storage context where the operation is executed.
+    pub address: Address,
+    pub pc: usize,
+}
+
+impl Display for TaintedOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}:{}", self.kind, self.address, self.pc)
+    }
+}
+
+/// A sink reached by tainted data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SinkHit {
+    /// The index of the transaction in the analyzed sequence.
+    pub tx: usize,
+    pub sink: TaintedOp,
+    /// Labels of the taint sources.
+    pub sources: Vec<String>,
+    /// Key operations on tainted data in the same storage context before the sink, in order.
+    pub path: Vec<TaintedOp>,
+}
+
+/// TaintResults is a policy that records where tainted data reaches a sink
+/// (storage write, call, and transaction output), together with the key operations along the way.
+/// It does not propagate taint itself and should be composed after the source and propagation policies.
+///
+/// Taint is not distinguished by source, so every hit is attributed to all `source_labels`.
+/// When a sequence of transactions is analyzed without resetting storage taint,
+/// tainted storage written by an earlier transaction and read by the current one
+/// is reported as an additional cross-transaction source.
+#[derive(Debug, Clone)]
+pub struct TaintResults {
+    pub source_labels: Vec<String>,
+    pub hits: Vec<SinkHit>,
+
+    // index of the current transaction, None before the first transaction
+    tx: Option<usize>,
+    // tainted key operations observed so far in the current transaction
+    ops: Vec<TaintedOp>,
+    // the transaction that last wrote tainted data to each storage slot
+    storage_origins: HashMap<(Address, U256), usize>,
+    // cross-transaction sources read in the current transaction
+    cross_sources: Vec<String>,
+}
+
+impl Default for TaintResults {
+    fn default() -> Self {
+        Self::new(vec!["tx input".to_string()])
+    }
+}
+
+impl TaintResults {
+    pub fn new(source_labels: Vec<String>) -> Self {
+        Self {
+            source_labels,
+            hits: Vec::new(),
+            tx: None,
+            ops: Vec::new(),
+            storage_origins: HashMap::new(),
+            cross_sources: Vec::new(),
+        }
+    }
+
+    /// A human-readable summary of the sink hits.
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+
+    fn current_tx(&self) -> usize {
+        self.tx.unwrap_or_default()
+    }
+
+    fn record(&mut self, kind: TaintedOpKind, address: Address, pc: usize) {
+        let op = TaintedOp { kind, address, pc };
+        if kind != TaintedOpKind::Hash {
+            let path = self
+                .ops
+                .iter()
+                .filter(|o| o.address == address)
+                .cloned()
+                .collect();
+            let mut sources = self.source_labels.clone();
+            sources.extend(self.cross_sources.iter().cloned());
+            self.hits.push(SinkHit {
+                tx: self.current_tx(),
+                sink: op.clone(),
+                sources,
+                path,
+            });
+        }
+        self.ops.push(op);
+    }
+}
+
+impl Display for TaintResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.hits.is_empty() {
+            return writeln!(f, "no tainted sink");
+        }
+        for (i, hit) in self.hits.iter().enumerate() {
+            writeln!(f, "#{} tx {}: {}", i, hit.tx, hit.sink)?;
+            writeln!(f, "  sources: {}", hit.sources.join(", "))?;
+            let path = hit
+                .path
+                .iter()
+                .chain(std::iter::once(&hit.sink))
+                .map(|op| op.to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            writeln!(f, "  path: {}", path)?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: BcState> TaintPolicy<S> for TaintResults {
+    fn transaction(&mut self, _tx: &TxEnv) {
+        self.tx = Some(self.tx.map_or(0, |i| i + 1));
+        self.ops.clear();
+        self.cross_sources.clear();
+    }
+
+    fn before_step(
+        &mut self,
+        taint_tracker: &mut crate::taint::TaintTracker,
+        interp: &mut libsofl_core::engine::types::Interpreter,
+        data: &mut libsofl_core::engine::types::EvmContext<S>,
+    ) -> Vec<Option<bool>> {
+        let address = interp.contract().address;
+        let pc = interp.program_counter();
+        let stack = &taint_tracker.stack;
+        let memory = &taint_tracker.memory;
+        let kind = match interp.current_opcode() {
+            opcode::KECCAK256 => {
+                stack_borrow!(interp, offset, len);
+                let tainted = stack.any_tainted(2)
+                    || memory.is_tainted(offset.cvt(), len.cvt());
+                tainted.then_some(TaintedOpKind::Hash)
+            }
+            opcode::SLOAD => {
+                stack_borrow!(interp, key);
+                let origin = self.storage_origins.get(&(address, *key));
+                match origin {
+                    Some(tx)
+                        if *tx != self.current_tx()
+                            && taint_tracker.storage.is_tainted(*key) =>
+                    {
+                        let label = format!(
+                            "storage {}[{:#x}] written in tx {}",
+                            address, key, tx
+                        );
+                        if !self.cross_sources.contains(&label) {
+                            self.cross_sources.push(label);
+                        }
+                    }
+                    _ => {}
+                }
+                None
+            }
+            opcode::SSTORE => {
+                let tainted = stack.any_tainted(2);
+                if tainted {
+                    stack_borrow!(interp, key, _value);
+                    let tx = self.current_tx();
+                    self.storage_origins.insert((address, *key), tx);
+                }
+                tainted.then_some(TaintedOpKind::StorageWrite)
+            }
+            opcode::CALL | opcode::CALLCODE => {
+                stack_borrow!(interp, _gas, _to, _value, offset, len);
+                let tainted = stack.is_tainted(1)
+                    || stack.is_tainted(2)
+                    || memory.is_tainted(offset.cvt(), len.cvt());
+                tainted.then_some(TaintedOpKind::Call)
+            }
+            opcode::DELEGATECALL | opcode::STATICCALL => {
+                stack_borrow!(interp, _gas, _to, offset, len);
+                let tainted = stack.is_tainted(1)
+                    || memory.is_tainted(offset.cvt(), len.cvt());
+                tainted.then_some(TaintedOpKind::Call)
+            }
+            opcode::CREATE | opcode::CREATE2 => {
+                stack_borrow!(interp, _value, offset, len);
+                let tainted = stack.is_tainted(0)
+                    || memory.is_tainted(offset.cvt(), len.cvt());
+                tainted.then_some(TaintedOpKind::Call)
+            }
+            opcode::RETURN | opcode::REVERT
+                if data.journaled_state.depth() == 1 =>
+            {
+                stack_borrow!(interp, offset, len);
+                memory
+                    .is_tainted(offset.cvt(), len.cvt())
+                    .then_some(TaintedOpKind::Output)
+            }
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            self.record(kind, address, pc);
+        }
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{memory::MemoryBcState, state::BcState, types::Address},
+        solidity::{caller::HighLevelCaller, scripting::compile_yul},
+    };
+
+    use crate::{
+        policies,
+        taint::{
+            propagation::{execution::ExecutionPolicy, math::MathPolicy},
+            source::tx_input::TxInputSource,
+            TaintAnalyzer,
+        },
+    };
+
+    use super::{TaintResults, TaintedOpKind};
+
+    #[test]
+    fn test_summary_of_hashed_storage_write() {
+        let mut state = MemoryBcState::fresh();
+        let mut results = TaintResults::default();
+        let mut analyzer = TaintAnalyzer::new(
+            policies!(
+                TxInputSource::default(),
+                ExecutionPolicy::default(),
+                MathPolicy::default(),
+                &mut results
+            ),
+            32,
+        );
+        let (_, code) = compile_yul(
+            "0.8.12",
+            r#"
+        object "A" {
+            code {
+                mstore(0, calldataload(0))
+                let slot := keccak256(0, 0x20)
+                sstore(slot, 1)
+                stop()
+            }
+        }
+        "#,
+        )
+        .unwrap()
+        .remove(0);
+        let contract = Address::ZERO;
+        state.replace_account_code(contract, code.cvt()).unwrap();
+        let mut calldata: Vec<u8> = Vec::new();
+        calldata.resize(0x20, 0);
+        HighLevelCaller::default()
+            .bypass_check()
+            .call(&mut state, contract, calldata.cvt(), None, &mut analyzer)
+            .unwrap();
+
+        assert_eq!(results.hits.len(), 1);
+        let hit = &results.hits[0];
+        assert_eq!(hit.sink.kind, TaintedOpKind::StorageWrite);
+        assert_eq!(hit.path.len(), 1);
+        assert_eq!(hit.path[0].kind, TaintedOpKind::Hash);
+
+        let summary = results.summary();
+        assert!(summary.contains("sources: tx input"));
+        assert!(summary.contains("hash at"));
+        assert!(summary.contains("-> storage write at"));
+    }
+
+    fn run_two_txs(reset_storage: bool) -> TaintResults {
+        let mut state = MemoryBcState::fresh();
+        let mut results = TaintResults::default();
+        let mut analyzer = TaintAnalyzer::new(
+            policies!(
+                TxInputSource::default(),
+                ExecutionPolicy::default(),
+                &mut results
+            ),
+            32,
+        );
+        if reset_storage {
+            analyzer = analyzer.with_storage_reset();
+        }
+        let (_, code) = compile_yul(
+            "0.8.12",
+            r#"
+        object "A" {
+            code {
+                switch calldataload(0)
+                case 1 {
+                    sstore(0, calldataload(0x20))
+                    stop()
+                }
+                default {
+                    mstore(0, sload(0))
+                    return(0, 0x20)
+                }
+            }
+        }
+        "#,
+        )
+        .unwrap()
+        .remove(0);
+        let contract = Address::ZERO;
+        state.replace_account_code(contract, code.cvt()).unwrap();
+        let caller = HighLevelCaller::default().bypass_check();
+
+        // tx 0 writes attacker-controlled data to storage
+        let mut calldata: Vec<u8> = vec![0; 0x40];
+        calldata[0x1f] = 1;
+        calldata[0x3f] = 0x42;
+        caller
+            .call(&mut state, contract, calldata.cvt(), None, &mut analyzer)
+            .unwrap();
+        // tx 1 returns the storage
+        let calldata: Vec<u8> = vec![0; 0x20];
+        caller
+            .call(&mut state, contract, calldata.cvt(), None, &mut analyzer)
+            .unwrap();
+        results
+    }
+
+    #[test]
+    fn test_cross_tx_storage_taint() {
+        let results = run_two_txs(false);
+        assert_eq!(results.hits.len(), 2);
+        assert_eq!(results.hits[0].tx, 0);
+        assert_eq!(results.hits[0].sink.kind, TaintedOpKind::StorageWrite);
+        let hit = &results.hits[1];
+        assert_eq!(hit.tx, 1);
+        assert_eq!(hit.sink.kind, TaintedOpKind::Output);
+        assert!(hit.sources.iter().any(|s| s.contains("written in tx 0")));
+        assert!(results.summary().contains("written in tx 0"));
+    }
+
+    #[test]
+    fn test_storage_taint_reset_between_txs() {
+        let results = run_two_txs(true);
+        assert_eq!(results.hits.len(), 1);
+        assert_eq!(results.hits[0].sink.kind, TaintedOpKind::StorageWrite);
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use libsofl_core::engine::{
+    state::BcState,
+    types::{keccak256, opcode, Address, EvmContext, Interpreter, U256},
+};
+
+use crate::taint::{policy::TaintPolicy, TaintTracker};
+
+/// The EIP-1967 implementation slot of proxies,
+/// i.e., `keccak256("eip1967.proxy.implementation") - 1`.
+pub fn eip1967_implementation_slot() -> U256 {
+    let hash = keccak256(b"eip1967.proxy.implementation");
+    U256::from_be_bytes(hash.0) - U256::from(1)
+}
+
+/// A predicate over the storage context and the slot of an SSTORE.
+pub type SlotFilter = Arc<dyn Fn(Address, U256) -> bool + Send + Sync>;
+
+/// A tainted value written to a watched storage slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageWriteHit {
+    /// The storage context where the slot is written.
+    pub address: Address,
+    pub slot: U256,
+    pub pc: usize,
+}
+
+/// StorageWriteSink records SSTOREs that write tainted values to sensitive
+/// slots selected by the filter, e.g., the owner or the implementation slot
+/// of a proxy, whose tainted writes indicate a hijack.
+/// Like `TaintResults`, it should be composed after the source and
+/// propagation policies.
+#[derive(Clone)]
+pub struct StorageWriteSink {
+    pub hits: Vec<StorageWriteHit>,
+
+    filter: SlotFilter,
+}
+
+impl std::fmt::Debug for StorageWriteSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StorageWriteSink")
+            .field("hits", &self.hits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StorageWriteSink {
+    pub fn new<F>(filter: F) -> Self
+    where
+        F: Fn(Address, U256) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            hits: Vec::new(),
+            filter: Arc::new(filter),
+        }
+    }
+
+    /// Watch the EIP-1967 implementation slot of all contracts.
+    pub fn eip1967_implementation() -> Self {
+        let slot = eip1967_implementation_slot();
+        Self::new(move |_, s| s == slot)
+    }
+}
+
+impl<S: BcState> TaintPolicy<S> for StorageWriteSink {
+    fn before_step(
+        &mut self,
+        taint_tracker: &mut TaintTracker,
+        interp: &mut Interpreter,
+        _data: &mut EvmContext<S>,
+    ) -> Vec<Option<bool>> {
+        if interp.current_opcode() != opcode::SSTORE {
+            return vec![];
+        }
+        stack_borrow!(interp, key, _value);
+        let address = interp.contract().address;
+        // the value is the second operand
+        if taint_tracker.stack.is_tainted(1) && (self.filter)(address, *key) {
+            self.hits.push(StorageWriteHit {
+                address,
+                slot: *key,
+                pc: interp.program_counter(),
+            });
+        }
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{memory::MemoryBcState, state::BcState, types::Address},
+        solidity::{caller::HighLevelCaller, scripting::compile_yul},
+    };
+
+    use crate::{
+        policies,
+        taint::{
+            propagation::execution::ExecutionPolicy,
+            source::calldata::CalldataSource, TaintAnalyzer,
+        },
+    };
+
+    use super::{eip1967_implementation_slot, StorageWriteSink};
+
+    #[test]
+    fn test_tainted_implementation_write() {
+        let slot = eip1967_implementation_slot();
+        assert_eq!(
+            format!("{:#x}", slot),
+            "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc"
+        );
+
+        let mut state = MemoryBcState::fresh();
+        let mut sink = StorageWriteSink::eip1967_implementation();
+        let mut analyzer = TaintAnalyzer::new(
+            policies!(
+                CalldataSource::default(),
+                ExecutionPolicy::default(),
+                &mut sink
+            ),
+            32,
+        );
+        let (_, code) = compile_yul(
+            "0.8.12",
+            r#"
+        object "A" {
+            code {
+                let slot := 0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc
+                // an unwatched slot
+                sstore(0, calldataload(0))
+                // an untainted value
+                sstore(slot, caller())
+                sstore(slot, calldataload(0))
+                stop()
+            }
+        }
+        "#,
+        )
+        .unwrap()
+        .remove(0);
+        let contract = Address::ZERO;
+        state.replace_account_code(contract, code.cvt()).unwrap();
+        let calldata: Vec<u8> = vec![0xab; 0x20];
+        HighLevelCaller::default()
+            .bypass_check()
+            .call(&mut state, contract, calldata.cvt(), None, &mut analyzer)
+            .unwrap();
+
+        assert_eq!(sink.hits.len(), 1);
+        assert_eq!(sink.hits[0].address, contract);
+        assert_eq!(sink.hits[0].slot, slot);
+    }
+}
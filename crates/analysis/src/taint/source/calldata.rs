@@ -0,0 +1,99 @@
+use libsofl_core::{
+    conversion::ConvertTo,
+    engine::{
+        state::BcState,
+        types::{opcode, EvmContext, Interpreter},
+    },
+};
+
+use crate::taint::{policy::TaintPolicy, TaintTracker};
+
+/// CalldataSource treats the calldata of the transaction as attacker-controlled.
+/// Words loaded by CALLDATALOAD are tainted on the stack, and memory written by
+/// CALLDATACOPY is tainted, rounded outward to the memory granularity so that
+/// partially copied words are tainted as well.
+///
+/// Only the outermost call reads the transaction calldata.
+/// The calldata of internal calls is tracked by `CallPolicy`.
+#[derive(Debug, Clone, Default)]
+pub struct CalldataSource {}
+
+impl<S: BcState> TaintPolicy<S> for CalldataSource {
+    #[inline]
+    fn before_step(
+        &mut self,
+        taint_tracker: &mut TaintTracker,
+        interp: &mut Interpreter,
+        data: &mut EvmContext<S>,
+    ) -> Vec<Option<bool>> {
+        if data.journaled_state.depth() > 1 {
+            return vec![];
+        }
+        match interp.current_opcode() {
+            opcode::CALLDATALOAD => vec![Some(true)],
+            opcode::CALLDATACOPY => {
+                stack_borrow!(interp, dest, _offset, len);
+                taint_tracker.memory.taint_covering(dest.cvt(), len.cvt());
+                vec![]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            memory::MemoryBcState,
+            state::BcState,
+            types::{Address, U256},
+        },
+        solidity::{caller::HighLevelCaller, scripting::compile_yul},
+    };
+
+    use crate::{
+        policies,
+        taint::{propagation::execution::ExecutionPolicy, TaintAnalyzer},
+    };
+
+    use super::CalldataSource;
+
+    #[test]
+    fn test_calldata_flows_into_storage() {
+        let mut state = MemoryBcState::fresh();
+        let mut analyzer = TaintAnalyzer::new(
+            policies!(CalldataSource::default(), ExecutionPolicy::default()),
+            32,
+        );
+        let (_, code) = compile_yul(
+            "0.8.12",
+            r#"
+        object "A" {
+            code {
+                sstore(0, calldataload(0))
+                // copy the selector only, which is less than a word
+                calldatacopy(0, 0, 4)
+                sstore(1, mload(0))
+                sstore(2, 7)
+                stop()
+            }
+        }
+        "#,
+        )
+        .unwrap()
+        .remove(0);
+        let contract = Address::ZERO;
+        state.replace_account_code(contract, code.cvt()).unwrap();
+        let calldata: Vec<u8> = vec![0xab; 0x20];
+        HighLevelCaller::default()
+            .bypass_check()
+            .call(&mut state, contract, calldata.cvt(), None, &mut analyzer)
+            .unwrap();
+
+        assert!(analyzer.is_storage_tainted(contract, U256::ZERO));
+        assert!(analyzer.is_storage_tainted(contract, U256::from(1)));
+        assert!(!analyzer.is_storage_tainted(contract, U256::from(2)));
+    }
+}
@@ -1 +1,2 @@
+pub mod calldata;
 pub mod tx_input;
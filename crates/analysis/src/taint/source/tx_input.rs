@@ -1,10 +1,11 @@
-use libsofl_core::{
-    conversion::ConvertTo,
-    engine::{state::BcState, types::opcode},
-};
+use libsofl_core::engine::{state::BcState, types::opcode};
 
 use crate::taint::policy::TaintPolicy;
 
+use super::calldata::CalldataSource;
+
+/// TxInputSource treats the transaction input, i.e., the calldata
+/// (see `CalldataSource`) and its size, as attacker-controlled.
 #[derive(Debug, Clone, Default)]
 pub struct TxInputSource {}
 
@@ -21,17 +22,8 @@ impl<S: BcState> TaintPolicy<S> for TxInputSource {
             return vec![];
         }
         match interp.current_opcode() {
-            opcode::CALLDATALOAD | opcode::CALLDATASIZE => {
-                vec![Some(true)]
-            }
-            opcode::CALLDATACOPY => {
-                stack_borrow!(interp, dest, _offset, len);
-                let dest = dest.cvt();
-                let len = len.cvt();
-                taint_tracker.memory.taint(dest, len);
-                vec![]
-            }
-            _ => vec![],
+            opcode::CALLDATASIZE => vec![Some(true)],
+            _ => CalldataSource {}.before_step(taint_tracker, interp, data),
         }
     }
 }
@@ -0,0 +1,220 @@
+use std::{collections::VecDeque, marker::PhantomData, ops::Range};
+
+use alloy_dyn_abi::{DynSolValue, EventExt};
+use alloy_json_abi::Event;
+
+use crate::{
+    engine::types::{Address, BlockNumber, TxHash},
+    error::SoflError,
+};
+
+use super::{
+    provider::{log_matches, BcProvider},
+    transaction::Tx,
+    tx_position::TxPosition,
+};
+
+/// An event decoded from a log, along with the transaction that emits it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRecord {
+    pub position: TxPosition,
+    pub tx_hash: TxHash,
+    /// The index of the log among the logs of the transaction.
+    pub log_index: usize,
+    pub address: Address,
+    pub indexed: Vec<DynSolValue>,
+    pub body: Vec<DynSolValue>,
+}
+
+/// EventStream lazily decodes the events of the given ABI in a block range.
+/// Blocks are loaded one at a time,
+/// so the memory usage does not grow with the range.
+/// Logs that do not decode as the event (e.g., ERC721 `Transfer` when decoding
+/// ERC20 `Transfer`, which shares the same topic) are skipped.
+pub struct EventStream<'a, T: Tx, P: BcProvider<T>> {
+    provider: &'a P,
+    event: Event,
+    address: Option<Address>,
+    blocks: Range<BlockNumber>,
+    pending: VecDeque<EventRecord>,
+    _tx: PhantomData<T>,
+}
+
+impl<'a, T: Tx, P: BcProvider<T>> EventStream<'a, T, P> {
+    pub fn new(
+        provider: &'a P,
+        event: Event,
+        blocks: Range<BlockNumber>,
+    ) -> Self {
+        Self {
+            provider,
+            event,
+            address: None,
+            blocks,
+            pending: VecDeque::new(),
+            _tx: PhantomData,
+        }
+    }
+
+    /// Only decode the events emitted by the contract.
+    pub fn with_address(mut self, address: Address) -> Self {
+        self.address.replace(address);
+        self
+    }
+
+    fn load_block(&mut self, bn: BlockNumber) -> Result<(), SoflError> {
+        let topic0 = (!self.event.anonymous).then(|| self.event.selector());
+        // the log query rules out blocks without any matching log,
+        // so that transactions are only loaded for the remaining blocks
+        let logs = self.provider.get_logs(self.address, topic0, bn..bn + 1)?;
+        if logs.is_empty() {
+            return Ok(());
+        }
+
+        let txs = self.provider.txs_in_block(bn.into())?;
+        for (index, tx) in txs.into_iter().enumerate() {
+            let logs = tx.logs().unwrap_or_default();
+            for (log_index, log) in logs.into_iter().enumerate() {
+                if !log_matches(&log, self.address, topic0) {
+                    continue;
+                }
+                let Ok(decoded) =
+                    self.event.decode_log_parts(log.topics, &log.data, true)
+                else {
+                    continue;
+                };
+                self.pending.push_back(EventRecord {
+                    position: TxPosition::new(bn, index as u64),
+                    tx_hash: tx.hash(),
+                    log_index,
+                    address: log.address,
+                    indexed: decoded.indexed,
+                    body: decoded.body,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Tx, P: BcProvider<T>> Iterator for EventStream<'_, T, P> {
+    type Item = Result<EventRecord, SoflError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Some(Ok(record));
+            }
+            let bn = self.blocks.next()?;
+            if let Err(e) = self.load_block(bn) {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_dyn_abi::DynSolValue;
+    use alloy_json_abi::Event;
+    use mockall::predicate::eq;
+
+    use crate::{
+        blockchain::{
+            provider::MockBcProvider,
+            transaction::{Log, MockTx},
+            tx_position::TxPosition,
+        },
+        conversion::ConvertTo,
+        engine::types::{Address, BlockHashOrNumber, TxHash, B256, U256},
+    };
+
+    use super::EventStream;
+
+    fn transfer_log(token: Address, value: u64, erc721: bool) -> Log {
+        let event = Event::parse(
+            "event Transfer(address indexed, address indexed, uint256)",
+        )
+        .unwrap();
+        let from: Address = 0x1.cvt();
+        let to: Address = 0x2.cvt();
+        let mut topics =
+            vec![event.selector(), from.into_word(), to.into_word()];
+        let mut data = U256::from(value).to_be_bytes_vec();
+        if erc721 {
+            // the token id is indexed
+            topics.push(B256::from(U256::from(value).to_be_bytes::<32>()));
+            data.clear();
+        }
+        Log {
+            address: token,
+            topics,
+            data: data.into(),
+        }
+    }
+
+    fn mock_tx(hash: u8, logs: Vec<Log>) -> MockTx {
+        let mut tx = MockTx::new();
+        tx.expect_hash().return_const(TxHash::with_last_byte(hash));
+        tx.expect_logs().returning(move || Some(logs.clone()));
+        tx
+    }
+
+    #[test]
+    fn test_decode_erc20_transfers() {
+        let token: Address = 0x1000.cvt();
+        let nft: Address = 0x2000.cvt();
+        let mut bp = MockBcProvider::<MockTx>::new();
+        // block 101 has no transfer, so its transactions are never loaded
+        bp.expect_get_logs().returning(move |_, _, blocks| {
+            if blocks.start == 101 {
+                Ok(vec![])
+            } else {
+                Ok(vec![transfer_log(token, 1, false)])
+            }
+        });
+        bp.expect_txs_in_block()
+            .with(eq(BlockHashOrNumber::from(100)))
+            .returning(move |_| {
+                Ok(vec![
+                    mock_tx(1, vec![]),
+                    mock_tx(2, vec![transfer_log(token, 7, false)]),
+                ])
+            });
+        bp.expect_txs_in_block()
+            .with(eq(BlockHashOrNumber::from(102)))
+            .returning(move |_| {
+                Ok(vec![mock_tx(
+                    3,
+                    vec![
+                        transfer_log(nft, 8, true),
+                        transfer_log(token, 9, false),
+                    ],
+                )])
+            });
+
+        let event = "event Transfer(address indexed from, address indexed to, uint256 value)";
+        let event = Event::parse(event).unwrap();
+        let records: Vec<_> = EventStream::new(&bp, event, 100..103)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(records[0].position, TxPosition::new(100, 1));
+        assert_eq!(records[0].tx_hash, TxHash::with_last_byte(2));
+        assert_eq!(records[0].log_index, 0);
+        assert_eq!(records[0].address, token);
+        let (from, to): (Address, Address) = (0x1.cvt(), 0x2.cvt());
+        assert_eq!(
+            records[0].indexed,
+            vec![DynSolValue::Address(from), DynSolValue::Address(to)]
+        );
+        let value = |v: u64| vec![DynSolValue::Uint(U256::from(v), 256)];
+        assert_eq!(records[0].body, value(7));
+
+        // the ERC721 transfer is skipped
+        assert_eq!(records[1].position, TxPosition::new(102, 0));
+        assert_eq!(records[1].log_index, 1);
+        assert_eq!(records[1].body, value(9));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod events;
 pub mod provider;
 pub mod transaction;
 pub mod tx_position;
+pub mod verify;
@@ -1,17 +1,23 @@
+use std::ops::Range;
+
 use crate::engine::types::BcStateRef;
 use auto_impl::auto_impl;
 use mockall::automock;
 
 use crate::engine::memory::MemoryBcState;
+use crate::engine::types::AccountInfo;
+use crate::engine::types::Address;
 use crate::engine::types::BlockEnv;
 use crate::engine::types::BlockHash;
 use crate::engine::types::BlockHashOrNumber;
 use crate::engine::types::BlockNumber;
 use crate::engine::types::CfgEnv;
+use crate::engine::types::Hash;
 use crate::engine::types::TxEnv;
 use crate::engine::types::TxHashOrPosition;
 use crate::error::SoflError;
 
+use super::transaction::Log;
 use super::transaction::Tx;
 use super::tx_position::TxPosition;
 
@@ -28,6 +34,53 @@ pub trait BcProvider<T: Tx>: Send + Sync {
         block: BlockHashOrNumber,
     ) -> Result<Vec<T>, SoflError>;
 
+    /// Transactions in the block range sent to the contract, in order.
+    /// If `internal` is true, transactions that reach the contract via internal calls
+    /// are included as well, as far as the provider can detect them,
+    /// e.g., only those in which the contract emits a log, without tracing.
+    /// Internal calls to the contract that emit no log may be missed.
+    /// The default implementation scans every block and only detects direct calls.
+    fn txs_to_address(
+        &self,
+        range: Range<BlockNumber>,
+        contract: Address,
+        internal: bool,
+    ) -> Result<Vec<T>, SoflError> {
+        let _ = internal;
+        let mut txs = Vec::new();
+        for bn in range {
+            let block_txs = self.txs_in_block(bn.into())?;
+            txs.extend(
+                block_txs.into_iter().filter(|tx| tx.to() == Some(contract)),
+            );
+        }
+        Ok(txs)
+    }
+
+    /// Logs emitted in the block range, in order,
+    /// filtered by the emitting contract and the first topic.
+    /// `None` filters match any log.
+    /// The default implementation loads the logs of every transaction in the range.
+    fn get_logs(
+        &self,
+        address: Option<Address>,
+        topic0: Option<Hash>,
+        range: Range<BlockNumber>,
+    ) -> Result<Vec<Log>, SoflError> {
+        let mut logs = Vec::new();
+        for bn in range {
+            for tx in self.txs_in_block(bn.into())? {
+                logs.extend(
+                    tx.logs()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|log| log_matches(log, address, topic0)),
+                );
+            }
+        }
+        Ok(logs)
+    }
+
     // block info
     fn block_number_by_hash(
         &self,
@@ -56,6 +109,17 @@ pub trait BcProvider<T: Tx>: Send + Sync {
     ) -> Result<(), SoflError>;
 }
 
+/// Whether the log is emitted by the address with the first topic,
+/// where `None` matches any.
+pub fn log_matches(
+    log: &Log,
+    address: Option<Address>,
+    topic0: Option<Hash>,
+) -> bool {
+    address.map_or(true, |a| log.address == a)
+        && topic0.map_or(true, |t| log.topics.first() == Some(&t))
+}
+
 #[auto_impl(&, Box, Arc, Rc)]
 #[automock]
 pub trait BcStateProvider<S: BcStateRef>: Send + Sync {
@@ -63,6 +127,16 @@ pub trait BcStateProvider<S: BcStateRef>: Send + Sync {
         &self,
         pos: TxPosition,
     ) -> Result<MemoryBcState<S>, SoflError>;
+
+    /// Fetch the account info of many accounts at once
+    /// in the state of `bc_state_at(pos)`,
+    /// e.g., to prefetch all accounts touched by a known trace.
+    /// The results are in the same order as the addresses.
+    fn basic_batch(
+        &self,
+        pos: TxPosition,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, SoflError>;
 }
 
 #[cfg(test)]
@@ -1,7 +1,13 @@
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::Function;
+use alloy_rlp::{Encodable, Header, EMPTY_STRING_CODE};
 use mockall::automock;
 
 use crate::{
-    engine::types::{Address, Bytes, Hash, TxEnv, TxHash, U256},
+    engine::types::{
+        keccak256, Address, Bytes, CreateScheme, Hash, TransactTo, TxEnv,
+        TxHash, U256,
+    },
     error::SoflError,
 };
 
@@ -18,6 +24,16 @@ pub trait Tx: Send + Sync {
     /// Returns the recipient of the transaction.
     fn to(&self) -> Option<Address>;
 
+    /// Returns whether the transaction calls an account or creates a contract.
+    fn kind(&self) -> TxKind {
+        self.to().into()
+    }
+
+    /// Returns whether the transaction creates a contract.
+    fn is_create(&self) -> bool {
+        self.kind().is_create()
+    }
+
     /// Returns the value of the transaction.
     fn value(&self) -> U256;
 
@@ -45,12 +61,402 @@ pub trait Tx: Send + Sync {
 
     /// Returns the gas used by the transaction.
     /// None if the transaction is not executed.
+    fn gas_used(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the logs emitted by the transaction.
+    /// None if the transaction is not executed.
     fn logs(&self) -> Option<Vec<Log>>;
 }
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+/// The kind of a transaction, i.e., a message call or a contract creation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TxKind {
+    Call(Address),
+    Create,
+}
+
+impl TxKind {
+    pub fn is_create(&self) -> bool {
+        matches!(self, TxKind::Create)
+    }
+
+    /// Returns the callee, None for contract creation.
+    pub fn to(&self) -> Option<Address> {
+        match self {
+            TxKind::Call(to) => Some(*to),
+            TxKind::Create => None,
+        }
+    }
+}
+
+impl From<Option<Address>> for TxKind {
+    fn from(to: Option<Address>) -> Self {
+        match to {
+            Some(to) => TxKind::Call(to),
+            None => TxKind::Create,
+        }
+    }
+}
+
+/// A transaction without signature, e.g., synthesized for simulation,
+/// which is executed on behalf of the sender.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsignedTx {
+    pub sender: Address,
+    pub kind: TxKind,
+    /// The nonce of the sender, None to skip the nonce check.
+    pub nonce: Option<u64>,
+    pub gas_limit: u64,
+    pub gas_price: U256,
+    pub value: U256,
+    pub input: Bytes,
+    pub chain_id: Option<u64>,
+}
+
+impl UnsignedTx {
+    /// RLP-encode the sender and the fields as a list.
+    /// Optional fields are encoded as lists of zero or one element,
+    /// so that `None` differs from `Some(0)`.
+    pub fn rlp_encode(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.sender.encode(&mut payload);
+        match self.kind {
+            TxKind::Call(to) => to.encode(&mut payload),
+            TxKind::Create => payload.push(EMPTY_STRING_CODE),
+        }
+        let nonce: Vec<u64> = self.nonce.into_iter().collect();
+        alloy_rlp::encode_list::<u64, u64>(&nonce, &mut payload);
+        self.gas_limit.encode(&mut payload);
+        self.gas_price.encode(&mut payload);
+        self.value.encode(&mut payload);
+        self.input.encode(&mut payload);
+        let chain_id: Vec<u64> = self.chain_id.into_iter().collect();
+        alloy_rlp::encode_list::<u64, u64>(&chain_id, &mut payload);
+
+        let mut out = Vec::new();
+        Header {
+            list: true,
+            payload_length: payload.len(),
+        }
+        .encode(&mut out);
+        out.extend(payload);
+        out
+    }
+}
+
+impl Tx for UnsignedTx {
+    /// Returns the keccak256 hash of the RLP-encoded sender and fields,
+    /// which is deterministic but differs from the hash of any signed tx.
+    fn hash(&self) -> TxHash {
+        keccak256(self.rlp_encode())
+    }
+
+    fn sender(&self) -> Address {
+        self.sender
+    }
+
+    fn to(&self) -> Option<Address> {
+        self.kind.to()
+    }
+
+    fn kind(&self) -> TxKind {
+        self.kind
+    }
+
+    fn value(&self) -> U256 {
+        self.value
+    }
+
+    fn input(&self) -> Bytes {
+        self.input.clone()
+    }
+
+    fn fill_tx_env(&self, env: &mut TxEnv) -> Result<(), SoflError> {
+        env.caller = self.sender;
+        env.gas_limit = self.gas_limit;
+        env.gas_price = self.gas_price;
+        env.transact_to = match self.kind {
+            TxKind::Call(to) => TransactTo::Call(to),
+            TxKind::Create => TransactTo::Create(CreateScheme::Create),
+        };
+        env.value = self.value;
+        env.data = self.input.clone();
+        env.nonce = self.nonce;
+        env.chain_id = self.chain_id;
+        Ok(())
+    }
+
+    fn position(&self) -> Option<TxPosition> {
+        None
+    }
+
+    fn output(&self) -> Option<Bytes> {
+        None
+    }
+
+    fn success(&self) -> Option<bool> {
+        None
+    }
+
+    fn logs(&self) -> Option<Vec<Log>> {
+        None
+    }
+}
+
+/// The default gas limit of transactions built by `TxBuilder`,
+/// i.e., the block gas limit of mainnet.
+pub const DEFAULT_TX_GAS_LIMIT: u64 = 30_000_000;
+
+/// TxBuilder builds an `UnsignedTx`, e.g.,
+/// `TxBuilder::new().from(a).to(b).call(sig, args).build_unsigned()`.
+/// By default, the nonce and chain id are not checked and the gas is free,
+/// i.e., the gas price is zero.
+#[derive(Debug, Clone)]
+pub struct TxBuilder {
+    from: Address,
+    to: Option<Address>,
+    call: Option<(String, Vec<DynSolValue>)>,
+    input: Bytes,
+    value: U256,
+    gas_limit: u64,
+    gas_price: U256,
+    nonce: Option<u64>,
+    chain_id: Option<u64>,
+}
+
+impl Default for TxBuilder {
+    fn default() -> Self {
+        Self {
+            from: Address::ZERO,
+            to: None,
+            call: None,
+            input: Bytes::new(),
+            value: U256::ZERO,
+            gas_limit: DEFAULT_TX_GAS_LIMIT,
+            gas_price: U256::ZERO,
+            nonce: None,
+            chain_id: None,
+        }
+    }
+}
+
+impl TxBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from(mut self, from: Address) -> Self {
+        self.from = from;
+        self
+    }
+
+    /// The callee. The transaction creates a contract if not set.
+    pub fn to(mut self, to: Address) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// Call the function, e.g., `transfer(address,uint256)`, with the
+    /// arguments, which are ABI-encoded as the input when built.
+    pub fn call(mut self, sig: &str, args: &[DynSolValue]) -> Self {
+        self.call = Some((sig.to_string(), args.to_vec()));
+        self
+    }
+
+    /// The raw input, e.g., the init code of a contract creation.
+    pub fn input(mut self, input: Bytes) -> Self {
+        self.call = None;
+        self.input = input;
+        self
+    }
+
+    pub fn value(mut self, value: U256) -> Self {
+        self.value = value;
+        self
+    }
+
+    pub fn gas(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    pub fn gas_price(mut self, gas_price: U256) -> Self {
+        self.gas_price = gas_price;
+        self
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    pub fn build_unsigned(self) -> Result<UnsignedTx, SoflError> {
+        let input = match &self.call {
+            Some((sig, args)) => {
+                let func = Function::parse(sig)
+                    .map_err(|e| SoflError::Abi(format!("{:?}", e)))?;
+                func.abi_encode_input(args)
+                    .map_err(|e| SoflError::Abi(format!("{:?}", e)))?
+                    .into()
+            }
+            None => self.input,
+        };
+        Ok(UnsignedTx {
+            sender: self.from,
+            kind: self.to.into(),
+            nonce: self.nonce,
+            gas_limit: self.gas_limit,
+            gas_price: self.gas_price,
+            value: self.value,
+            input,
+            chain_id: self.chain_id,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct Log {
     pub address: Address,
     pub topics: Vec<Hash>,
     pub data: Bytes,
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy_dyn_abi::DynSolValue;
+
+    use crate::{
+        conversion::ConvertTo,
+        engine::{
+            inspector::no_inspector,
+            memory::MemoryBcState,
+            state::BcState,
+            transition::TransitionSpecBuilder,
+            types::{Address, Bytes, SpecId, U256},
+        },
+        solidity::{
+            caller::HighLevelCaller,
+            scripting::{deploy_contracts, SolScriptConfig},
+        },
+    };
+
+    use super::{Tx, TxBuilder, TxKind, UnsignedTx};
+
+    #[test]
+    fn test_unsigned_tx_hash() {
+        let to: Address = 0x1234.cvt();
+        let tx = UnsignedTx {
+            sender: 0xabc.cvt(),
+            kind: TxKind::Call(to),
+            nonce: Some(0),
+            gas_limit: 21000,
+            gas_price: U256::from(1),
+            value: U256::from(100),
+            input: Bytes::new(),
+            chain_id: Some(1),
+        };
+        assert_eq!(tx.hash(), tx.clone().hash());
+        let different = [
+            UnsignedTx {
+                sender: 0xabd.cvt(),
+                ..tx.clone()
+            },
+            UnsignedTx {
+                kind: TxKind::Create,
+                ..tx.clone()
+            },
+            UnsignedTx {
+                nonce: None,
+                ..tx.clone()
+            },
+            UnsignedTx {
+                value: U256::from(101),
+                ..tx.clone()
+            },
+            UnsignedTx {
+                input: Bytes::from(vec![0]),
+                ..tx.clone()
+            },
+        ];
+        for other in different {
+            assert_ne!(tx.hash(), other.hash());
+        }
+    }
+
+    #[test]
+    fn test_build_and_transit_transfer() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Token {
+                mapping(address => uint256) public balanceOf;
+                constructor() {
+                    balanceOf[msg.sender] = 1000;
+                }
+                function transfer(address to, uint256 amount) external {
+                    balanceOf[msg.sender] -= amount;
+                    balanceOf[to] += amount;
+                }
+            }
+        "#;
+        let token = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Token"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+
+        // the deployer holds the initial supply
+        let holder = Address::ZERO;
+        let receiver: Address = 0xbeef.cvt();
+        let args = [
+            DynSolValue::Address(receiver),
+            DynSolValue::Uint(U256::from(10), 256),
+        ];
+        let tx = TxBuilder::new()
+            .from(holder)
+            .to(token)
+            .call("transfer(address,uint256)", &args)
+            .gas(100_000)
+            .build_unsigned()
+            .unwrap();
+        assert_eq!(tx.kind(), TxKind::Call(token));
+        assert_eq!(tx.sender(), holder);
+
+        let spec = TransitionSpecBuilder::new()
+            .set_evm_version(SpecId::LATEST)
+            .append_tx(tx)
+            .build()
+            .unwrap();
+        let r = state.transit(spec, no_inspector()).unwrap().pop().unwrap();
+        assert!(r.is_success());
+        let balance = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .view(
+                &mut state,
+                token,
+                "balanceOf(address) returns (uint256)",
+                &[DynSolValue::Address(receiver)],
+                no_inspector(),
+            )
+            .unwrap();
+        assert_eq!(balance[0].as_uint().unwrap().0, U256::from(10));
+
+        // an invalid signature
+        assert!(TxBuilder::new()
+            .to(token)
+            .call("transfer(address", &[])
+            .build_unsigned()
+            .is_err());
+    }
+}
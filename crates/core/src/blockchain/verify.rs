@@ -0,0 +1,104 @@
+use crate::{
+    engine::{
+        inspector::no_inspector,
+        state::BcState,
+        transition::TransitionSpecBuilder,
+        types::{BcStateRef, BlockEnv, BlockNumber, CfgEnv, TxEnv, TxHash},
+    },
+    error::SoflError,
+};
+
+use super::{
+    provider::{BcProvider, BcStateProvider},
+    transaction::Tx,
+    tx_position::TxPosition,
+};
+
+/// A transaction whose replayed execution diverges from its receipt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxMismatch {
+    /// The index of the transaction in the block.
+    pub index: u64,
+    pub hash: TxHash,
+    /// The gas used recorded in the receipt.
+    pub expected_gas_used: Option<u64>,
+    pub gas_used: u64,
+    /// The status recorded in the receipt.
+    pub expected_success: Option<bool>,
+    pub success: bool,
+}
+
+/// The result of replaying a block against the receipts of the provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockVerification {
+    pub block: BlockNumber,
+    /// The number of replayed transactions.
+    pub txs: usize,
+    pub mismatches: Vec<TxMismatch>,
+}
+
+impl BlockVerification {
+    /// Whether every transaction matches its receipt.
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Replay all transactions of the block on the state before it,
+/// and compare the gas used and status of each transaction with
+/// those recorded by the provider, e.g., to check the engine against
+/// the execution of a node.
+/// Receipt fields unknown to the provider are not compared,
+/// and neither is the state root.
+pub fn verify_block<T, S, P>(
+    provider: &P,
+    block: BlockNumber,
+) -> Result<BlockVerification, SoflError>
+where
+    T: Tx,
+    S: BcStateRef,
+    S::Error: std::fmt::Debug,
+    P: BcProvider<T> + BcStateProvider<S>,
+{
+    let mut state = provider.bc_state_at(TxPosition::new(block, 0))?;
+    let txs = provider.txs_in_block(block.into())?;
+    let mut cfg = CfgEnv::default();
+    let mut block_env = BlockEnv::default();
+    provider.fill_cfg_env(&mut cfg, block.into())?;
+    provider.fill_block_env(&mut block_env, block.into())?;
+    let mut spec_builder = TransitionSpecBuilder::new()
+        .set_cfg(cfg)
+        .set_block(block_env);
+    for tx in &txs {
+        let mut tx_env = TxEnv::default();
+        tx.fill_tx_env(&mut tx_env)?;
+        spec_builder = spec_builder.append_tx_env(tx_env);
+    }
+    let results = state.transit(spec_builder.build()?, no_inspector())?;
+
+    let mismatches = txs
+        .iter()
+        .zip(results.iter())
+        .enumerate()
+        .filter_map(|(index, (tx, result))| {
+            let (gas_used, success) = (result.gas_used(), result.is_success());
+            let expected_gas_used = tx.gas_used();
+            let expected_success = tx.success();
+            let matched = expected_gas_used.map_or(true, |g| g == gas_used)
+                && expected_success.map_or(true, |s| s == success);
+            (!matched).then(|| TxMismatch {
+                index: index as u64,
+                hash: tx.hash(),
+                expected_gas_used,
+                gas_used,
+                expected_success,
+                success,
+            })
+        })
+        .collect();
+    Ok(BlockVerification {
+        block,
+        txs: txs.len(),
+        mismatches,
+    })
+}
@@ -3,7 +3,8 @@ use std::str::FromStr;
 use crate::{
     blockchain::tx_position::TxPosition,
     engine::types::{
-        Address, BlockHashOrNumber, Bytes, Hash, Signed, TxHashOrPosition, Uint,
+        Address, BlockHashOrNumber, Bytes, Hash, Signed, TxHashOrPosition,
+        Uint, I256, U256,
     },
 };
 
@@ -144,6 +145,35 @@ impl<const BITS: usize, const LIMBS: usize> ConvertTo<Uint<BITS, LIMBS>>
     }
 }
 
+///*** Convert between Uint and Signed */
+// The bits are reinterpreted as two's complement, i.e., values with the
+// sign bit set are negative, as how the EVM treats `int256`.
+impl<const BITS: usize, const LIMBS: usize> ConvertTo<Signed<BITS, LIMBS>>
+    for Uint<BITS, LIMBS>
+{
+    fn cvt(&self) -> Signed<BITS, LIMBS> {
+        Signed::<BITS, LIMBS>::from_raw(*self)
+    }
+}
+impl<const BITS: usize, const LIMBS: usize> ConvertTo<Uint<BITS, LIMBS>>
+    for Signed<BITS, LIMBS>
+{
+    fn cvt(&self) -> Uint<BITS, LIMBS> {
+        self.into_raw()
+    }
+}
+
+/// Interpret the word as an `int256`, e.g., a Chainlink answer,
+/// which is negative if the sign bit is set.
+pub fn as_i256(value: U256) -> I256 {
+    value.cvt()
+}
+
+/// The two's complement word of the `int256`.
+pub fn from_i256(value: I256) -> U256 {
+    value.cvt()
+}
+
 ///*** Convert to Bytes */
 impl ConvertTo<Bytes> for String {
     fn cvt(&self) -> Bytes {
@@ -261,9 +291,11 @@ impl<const BITS: usize, const LIMBS: usize> ConvertTo<u128>
 mod tests {
     use crate::{
         conversion::{ConvertFrom, ConvertTo},
-        engine::types::U256,
+        engine::types::{I256, U256},
     };
 
+    use super::{as_i256, from_i256};
+
     #[test]
     fn test_cvt() {
         let from = 0u128;
@@ -285,4 +317,36 @@ mod tests {
         let from = U256::from(0xff);
         assert_eq!(ConvertTo::<String>::cvt(&from), "255".to_string());
     }
+
+    #[test]
+    fn test_cvt_negative_answer() {
+        // -1234.5678 USD with 8 decimals, as returned by latestRoundData
+        let word: U256 =
+            "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffe341670920"
+                .cvt();
+        let answer = as_i256(word);
+        assert!(answer.is_negative());
+        assert_eq!(answer, I256::try_from(-123456780000i64).unwrap());
+        assert_eq!(as_i256(U256::MAX), I256::MINUS_ONE);
+        assert_eq!(as_i256(U256::from(42)), I256::try_from(42).unwrap());
+    }
+
+    #[test]
+    fn test_cvt_i256_round_trip() {
+        for value in [
+            I256::ZERO,
+            I256::ONE,
+            I256::MINUS_ONE,
+            I256::MAX,
+            I256::MIN,
+            I256::try_from(-100_000_000i64).unwrap(),
+        ] {
+            assert_eq!(as_i256(from_i256(value)), value);
+        }
+        // the sign bit alone is the minimum
+        let min = U256::from(1) << 255;
+        assert_eq!(as_i256(min), I256::MIN);
+        assert_eq!(from_i256(I256::MIN), min);
+        assert_eq!(from_i256(I256::MINUS_ONE), U256::MAX);
+    }
 }
@@ -0,0 +1,410 @@
+use std::marker::PhantomData;
+
+use crate::{
+    blockchain::{provider::BcProvider, transaction::Tx},
+    conversion::ConvertTo,
+    error::SoflError,
+};
+
+use super::types::{BlockEnv, BlockHashOrNumber, CfgEnv, U256};
+
+/// BlockContext decides the block environment that a transition executes in,
+/// e.g., to simulate a transaction as if it were executed in a future block.
+pub trait BlockContext {
+    /// Fill the chain cfg and the block env of the transition.
+    fn fill_env(
+        &self,
+        cfg: &mut CfgEnv,
+        block: &mut BlockEnv,
+    ) -> Result<(), SoflError>;
+}
+
+/// The chain cfg and the block env of a block on the chain of the provider.
+#[derive(Debug, Clone)]
+pub struct FromProvider<T, P> {
+    provider: P,
+    block: BlockHashOrNumber,
+    _tx: PhantomData<T>,
+}
+
+impl<T: Tx, P: BcProvider<T>> FromProvider<T, P> {
+    pub fn new(provider: P, block: impl Into<BlockHashOrNumber>) -> Self {
+        Self {
+            provider,
+            block: block.into(),
+            _tx: PhantomData,
+        }
+    }
+}
+
+impl<T: Tx, P: BcProvider<T>> BlockContext for FromProvider<T, P> {
+    fn fill_env(
+        &self,
+        cfg: &mut CfgEnv,
+        block: &mut BlockEnv,
+    ) -> Result<(), SoflError> {
+        self.provider.fill_cfg_env(cfg, self.block)?;
+        self.provider.fill_block_env(block, self.block)
+    }
+}
+
+/// A fixed block env. The chain cfg is left untouched.
+#[derive(Debug, Clone, Default)]
+pub struct Fixed(pub BlockEnv);
+
+impl BlockContext for Fixed {
+    fn fill_env(
+        &self,
+        _cfg: &mut CfgEnv,
+        block: &mut BlockEnv,
+    ) -> Result<(), SoflError> {
+        *block = self.0.clone();
+        Ok(())
+    }
+}
+
+/// The block env of another context, moved forward in block number and time.
+#[derive(Debug, Clone)]
+pub struct Offset<C> {
+    pub base: C,
+    pub blocks: u64,
+    pub seconds: u64,
+}
+
+impl<C: BlockContext> Offset<C> {
+    pub fn new(base: C, blocks: u64, seconds: u64) -> Self {
+        Self {
+            base,
+            blocks,
+            seconds,
+        }
+    }
+}
+
+impl<C: BlockContext> BlockContext for Offset<C> {
+    fn fill_env(
+        &self,
+        cfg: &mut CfgEnv,
+        block: &mut BlockEnv,
+    ) -> Result<(), SoflError> {
+        self.base.fill_env(cfg, block)?;
+        block.number += U256::from(self.blocks);
+        block.timestamp += U256::from(self.seconds);
+        Ok(())
+    }
+}
+
+/// The default interval between blocks in seconds, i.e., the slot time of
+/// the beacon chain.
+pub const DEFAULT_BLOCK_TIME: u64 = 12;
+
+/// The base fee of the next block by EIP-1559, which moves towards the
+/// gas target (half of the gas limit) by at most 1/8 per block.
+pub fn next_base_fee(base_fee: U256, gas_used: U256, gas_limit: U256) -> U256 {
+    const ELASTICITY_MULTIPLIER: u64 = 2;
+    const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+    let target = gas_limit / U256::from(ELASTICITY_MULTIPLIER);
+    if target.is_zero() || gas_used == target {
+        return base_fee;
+    }
+    let denominator = U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR);
+    if gas_used > target {
+        let delta = base_fee * (gas_used - target) / target / denominator;
+        base_fee + delta.max(U256::from(1))
+    } else {
+        let delta = base_fee * (target - gas_used) / target / denominator;
+        base_fee.saturating_sub(delta)
+    }
+}
+
+/// A block env moving forward in multi-block simulations,
+/// where the number and the timestamp move together by the block time.
+/// Either of them can be overridden by setting the field of `block`.
+#[derive(Debug, Clone)]
+pub struct BlockClock {
+    pub block: BlockEnv,
+    /// The interval between blocks in seconds.
+    pub block_time: u64,
+    /// Whether to update the base fee between blocks by EIP-1559.
+    pub fee_market: bool,
+    /// The gas used in the current block, recorded by `record_gas_used`.
+    pub gas_used: u64,
+}
+
+impl BlockClock {
+    pub fn new(block: BlockEnv) -> Self {
+        Self {
+            block,
+            block_time: DEFAULT_BLOCK_TIME,
+            fee_market: false,
+            gas_used: 0,
+        }
+    }
+
+    pub fn with_block_time(mut self, block_time: u64) -> Self {
+        self.block_time = block_time;
+        self
+    }
+
+    /// Recompute the base fee of each new block from the gas used in
+    /// the previous one, which should be recorded with `record_gas_used`.
+    pub fn with_fee_market(mut self) -> Self {
+        self.fee_market = true;
+        self
+    }
+
+    /// Record the gas used by transactions executed in the current block,
+    /// e.g., `ExecutionResult::gas_used` of each transition.
+    pub fn record_gas_used(&mut self, gas_used: u64) {
+        self.gas_used += gas_used;
+    }
+
+    /// Move forward by the blocks, each of which takes the block time.
+    /// The skipped blocks are empty, which lowers the base fee
+    /// if the fee market is simulated.
+    pub fn advance_block(&mut self, blocks: u64) {
+        self.next_blocks(blocks);
+        self.block.timestamp +=
+            U256::from(blocks) * U256::from(self.block_time);
+    }
+
+    /// Move forward in time, with a new block at every multiple of
+    /// the block time that is passed.
+    /// As in `advance_block`, the base fee is updated for each new block
+    /// if the fee market is simulated.
+    pub fn advance_time(&mut self, seconds: u64) {
+        let before = self.block.timestamp;
+        self.block.timestamp += U256::from(seconds);
+        if self.block_time > 0 {
+            let block_time = U256::from(self.block_time);
+            let blocks: u64 =
+                (self.block.timestamp / block_time - before / block_time).cvt();
+            if blocks > 0 {
+                self.next_blocks(blocks);
+            }
+        }
+    }
+
+    /// Move the block number forward, starting a new block with no gas used.
+    /// The timestamp is left untouched.
+    fn next_blocks(&mut self, blocks: u64) {
+        if self.fee_market {
+            for _ in 0..blocks {
+                self.block.basefee = next_base_fee(
+                    self.block.basefee,
+                    U256::from(std::mem::take(&mut self.gas_used)),
+                    self.block.gas_limit,
+                );
+            }
+        }
+        self.gas_used = 0;
+        self.block.number += U256::from(blocks);
+    }
+}
+
+impl BlockContext for BlockClock {
+    fn fill_env(
+        &self,
+        _cfg: &mut CfgEnv,
+        block: &mut BlockEnv,
+    ) -> Result<(), SoflError> {
+        *block = self.block.clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        engine::{
+            inspector::no_inspector,
+            memory::MemoryBcState,
+            types::{Address, BlockEnv, Bytes, CfgEnv, SpecId, U256},
+        },
+        error::SoflError,
+        solidity::{
+            caller::HighLevelCaller,
+            scripting::{deploy_contracts, SolScriptConfig},
+        },
+    };
+
+    use super::{next_base_fee, BlockClock, BlockContext, Fixed, Offset};
+
+    struct Missing;
+
+    impl BlockContext for Missing {
+        fn fill_env(
+            &self,
+            _cfg: &mut CfgEnv,
+            _block: &mut BlockEnv,
+        ) -> Result<(), SoflError> {
+            Err(SoflError::NotFound("block".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_missing_block_is_an_error() {
+        let mut state = MemoryBcState::fresh();
+        let err = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .at_block_context(Missing)
+            .call(
+                &mut state,
+                Address::ZERO,
+                Bytes::new(),
+                None,
+                no_inspector(),
+            )
+            .unwrap_err();
+        assert!(matches!(err, SoflError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_offset_time() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Vault {
+                function withdraw() public view returns (uint256) {
+                    require(block.timestamp >= 2000, "locked");
+                    return block.number;
+                }
+            }
+        "#;
+        let vault = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Vault"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let now = Fixed(BlockEnv {
+            number: U256::from(100),
+            timestamp: U256::from(1000),
+            ..Default::default()
+        });
+        let caller = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST);
+
+        let withdraw = "withdraw() returns (uint256)";
+        let err = caller
+            .clone()
+            .at_block_context(now.clone())
+            .view(&mut state, vault, withdraw, &[], no_inspector())
+            .unwrap_err();
+        assert!(matches!(err, SoflError::Exec(_)));
+
+        let ret = caller
+            .at_block_context(Offset::new(now, 100, 1200))
+            .view(&mut state, vault, withdraw, &[], no_inspector())
+            .unwrap();
+        assert_eq!(ret[0].as_uint().unwrap().0, U256::from(200));
+    }
+
+    #[test]
+    fn test_block_clock_unlocks() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Vault {
+                function withdraw() public view returns (uint256) {
+                    require(block.timestamp >= 1060, "locked");
+                    return block.number;
+                }
+            }
+        "#;
+        let vault = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Vault"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let mut clock = BlockClock::new(BlockEnv {
+            number: U256::from(100),
+            timestamp: U256::from(1008),
+            ..Default::default()
+        });
+        let caller = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST);
+        let withdraw = "withdraw() returns (uint256)";
+
+        clock.advance_block(4);
+        assert_eq!(clock.block.number, U256::from(104));
+        assert_eq!(clock.block.timestamp, U256::from(1056));
+        let err = caller
+            .clone()
+            .at_block_context(clock.clone())
+            .view(&mut state, vault, withdraw, &[], no_inspector())
+            .unwrap_err();
+        assert!(matches!(err, SoflError::Exec(_)));
+
+        // a new block every 12 seconds
+        clock.advance_time(5);
+        assert_eq!(clock.block.number, U256::from(104));
+        clock.advance_time(7);
+        assert_eq!(clock.block.number, U256::from(105));
+        let ret = caller
+            .at_block_context(clock.clone())
+            .view(&mut state, vault, withdraw, &[], no_inspector())
+            .unwrap();
+        assert_eq!(ret[0].as_uint().unwrap().0, U256::from(105));
+
+        let mut clock = clock.with_block_time(2);
+        clock.advance_block(3);
+        assert_eq!(clock.block.number, U256::from(108));
+        assert_eq!(clock.block.timestamp, U256::from(1074));
+    }
+
+    #[test]
+    fn test_base_fee_of_full_blocks() {
+        let gas_limit = 30_000_000u64;
+        let gwei = U256::from(1_000_000_000u64);
+        let mut clock = BlockClock::new(BlockEnv {
+            basefee: gwei,
+            gas_limit: U256::from(gas_limit),
+            ..Default::default()
+        })
+        .with_fee_market();
+
+        // 12.5% per full block
+        let mut expected = gwei;
+        for _ in 0..3 {
+            clock.record_gas_used(gas_limit);
+            clock.advance_block(1);
+            expected = expected * U256::from(9) / U256::from(8);
+            assert_eq!(clock.block.basefee, expected);
+        }
+        assert_eq!(clock.block.basefee, U256::from(1_423_828_125u64));
+
+        // an empty block lowers the base fee by 12.5%
+        clock.advance_block(1);
+        let decrease = expected / U256::from(8);
+        assert_eq!(clock.block.basefee, expected - decrease);
+
+        // a block is crossed every 12 seconds
+        clock.record_gas_used(gas_limit);
+        let before = clock.block.basefee;
+        clock.advance_time(5);
+        assert_eq!(clock.block.basefee, before);
+        clock.advance_time(7);
+        let full = before * U256::from(9) / U256::from(8);
+        assert_eq!(clock.block.basefee, full);
+        clock.advance_time(24);
+        let empty = full - full / U256::from(8);
+        assert_eq!(clock.block.basefee, empty - empty / U256::from(8));
+
+        // unchanged at the target
+        let target = U256::from(gas_limit / 2);
+        let gas_limit = U256::from(gas_limit);
+        assert_eq!(next_base_fee(gwei, target, gas_limit), gwei);
+        // at least 1 wei up
+        let one = U256::from(1);
+        assert_eq!(next_base_fee(one, target + one, gas_limit), U256::from(2));
+    }
+}
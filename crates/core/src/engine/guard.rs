@@ -0,0 +1,321 @@
+use std::ops::Range;
+
+use alloy_primitives::Log;
+use revm::interpreter::{CallOutcome, CreateOutcome, InstructionResult};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::SoflError;
+
+use super::{
+    inspector::EvmInspector,
+    state::BcState,
+    types::{
+        opcode, Address, CallInputs, CreateInputs, Database, EvmContext,
+        ExecutionResult, Inspector, Interpreter, TxEnv, U256,
+    },
+};
+
+/// The number of steps between two checks of the cancellation token.
+const CANCELLATION_CHECK_INTERVAL: u64 = 1024;
+
+/// TransitionGuard wraps the inspector of a transition
+/// and halts the execution once the step limit of the transition is exceeded
+/// or the transition is cancelled.
+///
+/// The steps are counted across all call frames and transactions of the transition.
+/// Once halted, every frame halts at its next step,
+/// so the execution unwinds without running any further opcode.
+///
+/// The guard also spoofs `tx.origin` by overriding the result of `ORIGIN`,
+/// since revm takes the origin from the sender of the transaction.
+pub(crate) struct TransitionGuard<I> {
+    inspector: I,
+    step_limit: Option<u64>,
+    cancellation_token: Option<CancellationToken>,
+    origin: Option<Address>,
+    steps: u64,
+    exceeded: bool,
+    interrupted: bool,
+    /// Whether the current step is `ORIGIN` to be overridden.
+    spoofing_origin: bool,
+}
+
+impl<I> TransitionGuard<I> {
+    pub(crate) fn new(
+        inspector: I,
+        step_limit: Option<u64>,
+        cancellation_token: Option<CancellationToken>,
+        origin: Option<Address>,
+    ) -> Self {
+        Self {
+            inspector,
+            step_limit,
+            cancellation_token,
+            origin,
+            steps: 0,
+            exceeded: false,
+            interrupted: false,
+            spoofing_origin: false,
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancellation_token
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+    }
+
+    /// Whether the transaction should not be executed,
+    /// i.e., the transition is cancelled before the transaction.
+    pub(crate) fn check_before_transaction(&mut self) -> Result<(), SoflError> {
+        if self.is_cancelled() {
+            self.interrupted = true;
+        }
+        self.check()
+    }
+
+    /// Whether the execution is halted by the guard.
+    pub(crate) fn check(&self) -> Result<(), SoflError> {
+        if self.interrupted {
+            return Err(SoflError::Interrupted);
+        }
+        match self.step_limit {
+            Some(limit) if self.exceeded => {
+                Err(SoflError::StepLimitExceeded(limit))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<DB: Database, I: Inspector<DB>> Inspector<DB> for TransitionGuard<I> {
+    #[inline]
+    fn initialize_interp(
+        &mut self,
+        interp: &mut Interpreter,
+        context: &mut EvmContext<DB>,
+    ) {
+        self.inspector.initialize_interp(interp, context);
+    }
+
+    #[inline]
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        self.steps += 1;
+        if self.step_limit.is_some_and(|limit| self.steps > limit) {
+            self.exceeded = true;
+        }
+        if self.steps % CANCELLATION_CHECK_INTERVAL == 0 && self.is_cancelled()
+        {
+            self.interrupted = true;
+        }
+        if self.exceeded || self.interrupted {
+            interp.instruction_result = InstructionResult::OutOfGas;
+            return;
+        }
+        self.spoofing_origin =
+            self.origin.is_some() && interp.current_opcode() == opcode::ORIGIN;
+        self.inspector.step(interp, context);
+    }
+
+    #[inline]
+    fn log(&mut self, context: &mut EvmContext<DB>, log: &Log) {
+        self.inspector.log(context, log);
+    }
+
+    #[inline]
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        context: &mut EvmContext<DB>,
+    ) {
+        if std::mem::take(&mut self.spoofing_origin)
+            && interp.instruction_result == InstructionResult::Continue
+        {
+            if let (Some(origin), Some(top)) =
+                (self.origin, interp.stack.data_mut().last_mut())
+            {
+                *top = U256::from_be_bytes(origin.into_word().0);
+            }
+        }
+        self.inspector.step_end(interp, context);
+    }
+
+    #[inline]
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+        return_memory_offset: Range<usize>,
+    ) -> Option<CallOutcome> {
+        self.inspector.call(context, inputs, return_memory_offset)
+    }
+
+    #[inline]
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.inspector.call_end(context, inputs, outcome)
+    }
+
+    #[inline]
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.inspector.create(context, inputs)
+    }
+
+    #[inline]
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.inspector.create_end(context, inputs, outcome)
+    }
+
+    #[inline]
+    fn selfdestruct(
+        &mut self,
+        contract: Address,
+        target: Address,
+        value: U256,
+    ) {
+        self.inspector.selfdestruct(contract, target, value);
+    }
+}
+
+impl<BS: BcState, I: EvmInspector<BS>> EvmInspector<BS> for TransitionGuard<I> {
+    fn transaction(&mut self, tx: &TxEnv, state: &BS) -> bool {
+        self.inspector.transaction(tx, state)
+    }
+
+    fn transaction_end(
+        &mut self,
+        tx: &TxEnv,
+        state: &BS,
+        result: &ExecutionResult,
+    ) {
+        self.inspector.transaction_end(tx, state, result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_util::sync::CancellationToken;
+
+    use crate::{
+        conversion::ConvertTo,
+        engine::{
+            inspector::{no_inspector, EvmInspector},
+            memory::MemoryBcState,
+            state::BcState,
+            transition::TransitionSpecBuilder,
+            types::{
+                Address, Bytecode, Database, EvmContext, Inspector,
+                Interpreter, TransactTo, TxEnv,
+            },
+        },
+        error::SoflError,
+    };
+
+    #[test]
+    fn test_step_limit_on_infinite_loop() {
+        let looper: Address = 0x1000.cvt();
+        let mut state = MemoryBcState::fresh();
+        // JUMPDEST; PUSH1 0; JUMP
+        let code = Bytecode::new_raw(vec![0x5b, 0x60, 0x00, 0x56].into());
+        state.replace_account_code(looper, code).unwrap();
+        let tx = TxEnv {
+            transact_to: TransactTo::Call(looper),
+            gas_limit: 30_000_000,
+            ..Default::default()
+        };
+        let spec = TransitionSpecBuilder::new()
+            .bypass_check()
+            .append_tx_env(tx.clone())
+            .set_step_limit(1000)
+            .build()
+            .unwrap();
+        let err = state.transit(spec.clone(), no_inspector()).unwrap_err();
+        assert!(matches!(err, SoflError::StepLimitExceeded(1000)));
+        let err = state.transit_without_inspector(spec).unwrap_err();
+        assert!(matches!(err, SoflError::StepLimitExceeded(1000)));
+
+        // the aborted transaction is not committed
+        let caller = state.basic(tx.caller).unwrap().unwrap_or_default();
+        assert_eq!(caller.nonce, 0);
+
+        // without the limit, the loop runs out of gas
+        let spec = TransitionSpecBuilder::new()
+            .bypass_check()
+            .append_tx_env(tx)
+            .build()
+            .unwrap();
+        let result = state.transit(spec, no_inspector()).unwrap().remove(0);
+        assert!(!result.is_success());
+        assert_eq!(result.gas_used(), 30_000_000);
+    }
+
+    /// Cancel the token after a number of steps.
+    struct CancelAfter {
+        token: CancellationToken,
+        cancel_at: u64,
+        steps: u64,
+    }
+
+    impl<DB: Database> Inspector<DB> for CancelAfter {
+        fn step(
+            &mut self,
+            _interp: &mut Interpreter,
+            _context: &mut EvmContext<DB>,
+        ) {
+            self.steps += 1;
+            if self.steps == self.cancel_at {
+                self.token.cancel();
+            }
+        }
+    }
+
+    impl<BS: BcState> EvmInspector<BS> for CancelAfter {}
+
+    #[test]
+    fn test_cancel_infinite_loop() {
+        let looper: Address = 0x1000.cvt();
+        let mut state = MemoryBcState::fresh();
+        // JUMPDEST; PUSH1 0; JUMP
+        let code = Bytecode::new_raw(vec![0x5b, 0x60, 0x00, 0x56].into());
+        state.replace_account_code(looper, code).unwrap();
+        let tx = TxEnv {
+            transact_to: TransactTo::Call(looper),
+            gas_limit: 30_000_000,
+            ..Default::default()
+        };
+        let token = CancellationToken::new();
+        let spec = TransitionSpecBuilder::new()
+            .bypass_check()
+            .append_tx_env(tx)
+            .set_cancellation_token(token.clone())
+            .build()
+            .unwrap();
+
+        let mut inspector = CancelAfter {
+            token,
+            cancel_at: 100,
+            steps: 0,
+        };
+        let err = state.transit(spec.clone(), &mut inspector).unwrap_err();
+        assert!(matches!(err, SoflError::Interrupted));
+        // the loop stops at the next check after the cancellation
+        assert!(inspector.steps < super::CANCELLATION_CHECK_INTERVAL);
+
+        // a cancelled transition does not execute anything
+        let err = state.transit_without_inspector(spec).unwrap_err();
+        assert!(matches!(err, SoflError::Interrupted));
+    }
+}
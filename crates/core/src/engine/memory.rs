@@ -1,9 +1,12 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use revm::db::CacheDB;
 
+use crate::error::SoflError;
+
 use super::types::{
-    AccountInfo, Address, BcStateRef, Bytecode, Hash, StateChange, U256,
+    AccountInfo, AccountState, Address, BcStateRef, Bytecode, Hash,
+    StateChange, U256,
 };
 
 /// In-memory BcState implementation, using revm's CacheDB.
@@ -89,6 +92,54 @@ impl<S: BcStateRef> MemoryBcState<S> {
     }
 }
 
+/// The storage of an account dumped from a state.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageDump {
+    pub slots: HashMap<U256, U256>,
+    /// Whether the storage may have slots not in `slots`,
+    /// e.g., slots of a forked state that are never loaded from the remote.
+    pub partial: bool,
+}
+
+/// Dump the full storage of an account, e.g., to export fixtures.
+pub trait DumpStorage {
+    fn dump_storage(&self, address: Address) -> Result<StorageDump, SoflError>;
+}
+
+impl<S: BcStateRef> MemoryBcState<S> {
+    /// The storage slots of the account cached in this state,
+    /// i.e., slots written or loaded so far.
+    /// The dump is partial unless the storage of the account is known to be
+    /// cleared (e.g., the account is created in this state),
+    /// since slots of the underlying state cannot be enumerated.
+    pub fn dump_cached_storage(&self, address: Address) -> StorageDump {
+        let Some(account) = self.0.accounts.get(&address) else {
+            return StorageDump {
+                slots: HashMap::new(),
+                partial: true,
+            };
+        };
+        StorageDump {
+            slots: account.storage.iter().map(|(k, v)| (*k, *v)).collect(),
+            partial: !matches!(
+                account.account_state,
+                AccountState::NotExisting | AccountState::StorageCleared
+            ),
+        }
+    }
+}
+
+/// A fresh state has no underlying storage,
+/// so its cached storage is the full storage.
+impl DumpStorage for EmptyMemoryBcState {
+    fn dump_storage(&self, address: Address) -> Result<StorageDump, SoflError> {
+        Ok(StorageDump {
+            partial: false,
+            ..self.dump_cached_storage(address)
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use revm::Database;
@@ -97,12 +148,12 @@ mod tests {
         conversion::ConvertTo,
         engine::{
             inspector::no_inspector,
-            memory::MemoryBcState,
+            memory::{DumpStorage, MemoryBcState},
             state::BcState,
             transition::TransitionSpecBuilder,
             types::{
                 AccountInfo, Address, BlockEnv, Bytecode, CfgEnv,
-                ExecutionResult, TransactTo, TxEnv, U256,
+                ExecutionResult, TransactTo, TxEnv, KECCAK_EMPTY, U256,
             },
         },
     };
@@ -152,7 +203,8 @@ mod tests {
             .set_cfg(cfg.clone())
             .set_block(block_env.clone())
             .append_tx_env(tx.clone())
-            .build();
+            .build()
+            .unwrap();
         let (_, mut results) = state.simulate(spec, no_inspector()).unwrap();
         let result = results.pop().unwrap();
 
@@ -175,7 +227,8 @@ mod tests {
             .set_cfg(cfg)
             .set_block(block_env)
             .append_tx_env(tx)
-            .build();
+            .build()
+            .unwrap();
         let mut result = state.transit_without_inspector(spec).unwrap();
         let result = result.pop().unwrap();
 
@@ -193,4 +246,58 @@ mod tests {
             "receiver balance should be increased by 500"
         );
     }
+
+    #[test]
+    fn test_code_size() {
+        let eoa: Address = 1.cvt();
+        let contract: Address = 2.cvt();
+        let mut state = MemoryBcState::fresh();
+        let info = AccountInfo::new(
+            U256::from(1000),
+            0,
+            KECCAK_EMPTY,
+            Bytecode::new(),
+        );
+        state.insert_account_info(eoa, info);
+        let code = Bytecode::new_raw(vec![0x60, 0x00, 0x60, 0x00, 0xf3].into());
+        state.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        assert_eq!(state.code_size(eoa).unwrap(), 0);
+        assert_eq!(state.code_size(contract).unwrap(), 5);
+        // non-existent account
+        assert_eq!(state.code_size(3.cvt()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_dump_storage() {
+        let contract: Address = 1.cvt();
+        let mut state = MemoryBcState::fresh();
+        for (slot, value) in [(0, 1), (1, 2), (7, 3), (1, 4)] {
+            state
+                .insert_account_storage(
+                    contract,
+                    U256::from(slot),
+                    U256::from(value),
+                )
+                .unwrap();
+        }
+
+        let dump = state.dump_storage(contract).unwrap();
+        assert!(!dump.partial);
+        let expected = [(0, 1), (1, 4), (7, 3)]
+            .into_iter()
+            .map(|(s, v)| (U256::from(s), U256::from(v)))
+            .collect();
+        assert_eq!(dump.slots, expected);
+        assert!(state.dump_storage(2.cvt()).unwrap().slots.is_empty());
+
+        // a forked state cannot tell the slots of the underlying state
+        let fork = state.fork();
+        let dump = fork.dump_cached_storage(contract);
+        assert!(dump.partial);
+        assert!(dump.slots.is_empty());
+    }
 }
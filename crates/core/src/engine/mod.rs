@@ -1,5 +1,9 @@
+pub mod block_context;
+mod guard;
 pub mod inspector;
 pub mod memory;
+pub mod overrides;
+pub mod report;
 pub mod revm;
 pub mod state;
 pub mod transition;
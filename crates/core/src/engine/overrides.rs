@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{Address, Bytes, U256, U64};
+
+/// Overrides of the state of an account before execution,
+/// in the same shape as the account override object of `eth_call`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AccountOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Replace the whole storage of the account,
+    /// i.e., slots not listed here are cleared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<HashMap<U256, U256>>,
+    /// Override the listed slots and keep the others.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<HashMap<U256, U256>>,
+}
+
+impl AccountOverride {
+    pub fn with_balance(mut self, balance: U256) -> Self {
+        self.balance = Some(balance);
+        self
+    }
+
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(U64::from(nonce));
+        self
+    }
+
+    pub fn with_code(mut self, code: Bytes) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_state(mut self, state: HashMap<U256, U256>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Override one slot on top of the existing storage.
+    pub fn with_slot(mut self, slot: U256, value: U256) -> Self {
+        self.state_diff
+            .get_or_insert_with(Default::default)
+            .insert(slot, value);
+        self
+    }
+}
+
+/// Overrides of the state before execution, keyed by account,
+/// in the same shape as the state override set of `eth_call`,
+/// e.g., `{"0x...": {"balance": "0x1", "stateDiff": {"0x0": "0x1"}}}`.
+pub type StateOverrides = HashMap<Address, AccountOverride>;
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        conversion::ConvertTo,
+        engine::{
+            inspector::no_inspector,
+            memory::MemoryBcState,
+            state::BcState,
+            types::{Address, Database, SpecId, U256},
+        },
+        solidity::{
+            caller::HighLevelCaller,
+            scripting::{compile_yul, deploy_contracts, SolScriptConfig},
+        },
+    };
+
+    use super::{AccountOverride, StateOverrides};
+
+    #[test]
+    fn test_override_code() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Answer {
+                uint256 public value = 1;
+                function answer() public view returns (uint256) {
+                    return value;
+                }
+            }
+        "#;
+        let answer = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Answer"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+
+        // the overridden code returns 42 regardless of the storage
+        let (_, code) = compile_yul(
+            "0.8.12",
+            r#"
+        object "Answer" {
+            code {
+                mstore(0, 42)
+                return(0, 0x20)
+            }
+        }
+        "#,
+        )
+        .unwrap()
+        .remove(0);
+        let mut overrides = StateOverrides::new();
+        overrides
+            .insert(answer, AccountOverride::default().with_code(code.clone()));
+        let mut state = state.with_overrides(overrides).unwrap();
+
+        let caller = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST);
+        let ret = caller
+            .view(
+                &mut state,
+                answer,
+                "answer() returns (uint256)",
+                &[],
+                no_inspector(),
+            )
+            .unwrap();
+        assert_eq!(ret[0].as_uint().unwrap().0, U256::from(42));
+        let overridden = state.get_account_code(answer).unwrap();
+        assert_eq!(overridden.original_bytes(), code);
+        // the storage is kept
+        assert_eq!(state.storage(answer, U256::ZERO).unwrap(), U256::from(1));
+    }
+
+    #[test]
+    fn test_state_replaces_storage() {
+        let account: Address = 0x1234.cvt();
+        let mut state = MemoryBcState::fresh();
+        state
+            .insert_account_storage(account, U256::from(0), U256::from(1))
+            .unwrap();
+        state
+            .insert_account_storage(account, U256::from(1), U256::from(2))
+            .unwrap();
+
+        // stateDiff keeps the other slots
+        let json = r#"{
+            "0x0000000000000000000000000000000000001234": {
+                "balance": "0x64",
+                "nonce": "0x2",
+                "stateDiff": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000000": "0x0000000000000000000000000000000000000000000000000000000000000005"
+                }
+            }
+        }"#;
+        let overrides: StateOverrides = serde_json::from_str(json).unwrap();
+        let mut state = state.with_overrides(overrides).unwrap();
+        let info = state.basic(account).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(100));
+        assert_eq!(info.nonce, 2);
+        let mut slot = |i: u64| state.storage(account, U256::from(i)).unwrap();
+        assert_eq!(slot(0), U256::from(5));
+        assert_eq!(slot(1), U256::from(2));
+
+        // state clears the other slots
+        let mut overrides = StateOverrides::new();
+        overrides.insert(
+            account,
+            AccountOverride::default()
+                .with_state([(U256::from(2), U256::from(3))].into()),
+        );
+        state.apply_overrides(overrides).unwrap();
+        let mut slot = |i: u64| state.storage(account, U256::from(i)).unwrap();
+        assert_eq!(slot(0), U256::ZERO);
+        assert_eq!(slot(1), U256::ZERO);
+        assert_eq!(slot(2), U256::from(3));
+        // the account info is kept
+        assert_eq!(state.basic(account).unwrap().unwrap().nonce, 2);
+
+        // state and stateDiff are exclusive
+        let mut overrides = StateOverrides::new();
+        overrides.insert(
+            account,
+            AccountOverride::default()
+                .with_state(Default::default())
+                .with_slot(U256::ZERO, U256::from(1)),
+        );
+        assert!(state.apply_overrides(overrides).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::blockchain::transaction::Log;
+
+use super::types::{
+    Address, Bytes, ExecutionResult, Output, StateChange, U256,
+};
+
+/// The state changes of one account after execution.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize,
+)]
+pub struct AccountChange {
+    pub balance: U256,
+    pub nonce: u64,
+    /// changed storage slots and their present values
+    pub storage: BTreeMap<U256, U256>,
+}
+
+/// ExecutionReport summarizes the observable outcome of executing one transaction.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize,
+)]
+pub struct ExecutionReport {
+    pub success: bool,
+    pub gas_used: u64,
+    pub output: Bytes,
+    pub logs: Vec<Log>,
+    pub state_changes: BTreeMap<Address, AccountChange>,
+    #[serde(default)]
+    pub created_contracts: Vec<Address>,
+}
+
+impl ExecutionReport {
+    /// Create a report from the execution result and state changes of a transaction,
+    /// i.e., one element of the vectors returned by `BcState::simulate`.
+    pub fn new(result: &ExecutionResult, changes: &StateChange) -> Self {
+        let logs = result
+            .logs()
+            .iter()
+            .map(|l| Log {
+                address: l.address,
+                topics: l.topics().to_vec(),
+                data: l.data.data.clone(),
+            })
+            .collect();
+        let state_changes = changes
+            .iter()
+            .filter(|(_, account)| account.is_touched())
+            .map(|(addr, account)| {
+                let storage = account
+                    .storage
+                    .iter()
+                    .filter(|(_, slot)| slot.is_changed())
+                    .map(|(k, slot)| (*k, slot.present_value()))
+                    .collect();
+                let change = AccountChange {
+                    balance: account.info.balance,
+                    nonce: account.info.nonce,
+                    storage,
+                };
+                (*addr, change)
+            })
+            .collect();
+        Self {
+            success: result.is_success(),
+            gas_used: result.gas_used(),
+            output: result.output().cloned().unwrap_or_default(),
+            logs,
+            state_changes,
+            created_contracts: created_contracts(result, Some(changes)),
+        }
+    }
+}
+
+/// The addresses of the contracts created by a transaction.
+///
+/// The contract created by a deployment transaction comes first,
+/// which is available from the execution result alone.
+/// Contracts created by internal CREATE and CREATE2 are marked as created in the state changes,
+/// so they are included (sorted by address) if the changes of the transaction are given,
+/// e.g., one element of the changes returned by `BcState::simulate`.
+pub fn created_contracts(
+    result: &ExecutionResult,
+    changes: Option<&StateChange>,
+) -> Vec<Address> {
+    let top = match result {
+        ExecutionResult::Success {
+            output: Output::Create(_, Some(address)),
+            ..
+        } => Some(*address),
+        _ => None,
+    };
+    let mut internal: Vec<Address> = changes
+        .into_iter()
+        .flatten()
+        .filter(|(address, account)| {
+            account.is_created() && Some(**address) != top
+        })
+        .map(|(address, _)| *address)
+        .collect();
+    internal.sort();
+    top.into_iter().chain(internal).collect()
+}
+
+/// The state field that differs between two reports.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum StateField {
+    Balance,
+    Nonce,
+    Storage(U256),
+}
+
+/// A difference of one state field.
+/// None means the field is not changed in the corresponding report.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct StateDiff {
+    pub address: Address,
+    pub field: StateField,
+    pub a: Option<U256>,
+    pub b: Option<U256>,
+}
+
+/// A difference of the log at the same index.
+/// None means there is no log at the index in the corresponding report.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct LogDiff {
+    pub index: usize,
+    pub a: Option<Log>,
+    pub b: Option<Log>,
+}
+
+/// ReportDiff is the structured difference between two execution reports.
+/// Each field is only present (or non-empty) if the two reports differ on it.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize,
+)]
+pub struct ReportDiff {
+    pub success: Option<(bool, bool)>,
+    pub gas_used: Option<(u64, u64)>,
+    pub output: Option<(Bytes, Bytes)>,
+    pub logs: Vec<LogDiff>,
+    pub state: Vec<StateDiff>,
+}
+
+impl ReportDiff {
+    /// Whether the two reports are identical.
+    pub fn is_empty(&self) -> bool {
+        self.success.is_none()
+            && self.gas_used.is_none()
+            && self.output.is_none()
+            && self.logs.is_empty()
+            && self.state.is_empty()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("failed to serialize report diff")
+    }
+}
+
+impl Display for ReportDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no difference");
+        }
+        if let Some((a, b)) = self.success {
+            writeln!(f, "success: {} -> {}", a, b)?;
+        }
+        if let Some((a, b)) = self.gas_used {
+            let delta = b as i128 - a as i128;
+            writeln!(f, "gas used: {} -> {} ({:+})", a, b, delta)?;
+        }
+        if let Some((a, b)) = &self.output {
+            writeln!(f, "output: {} -> {}", a, b)?;
+        }
+        for log in &self.logs {
+            writeln!(f, "log #{}:", log.index)?;
+            match &log.a {
+                Some(l) => writeln!(f, "  - {:?}", l)?,
+                None => writeln!(f, "  - <none>")?,
+            }
+            match &log.b {
+                Some(l) => writeln!(f, "  + {:?}", l)?,
+                None => writeln!(f, "  + <none>")?,
+            }
+        }
+        for s in &self.state {
+            let field = match &s.field {
+                StateField::Balance => "balance".to_string(),
+                StateField::Nonce => "nonce".to_string(),
+                StateField::Storage(slot) => format!("storage[{:#x}]", slot),
+            };
+            let fmt = |v: &Option<U256>| match v {
+                Some(v) => format!("{:#x}", v),
+                None => "<unchanged>".to_string(),
+            };
+            writeln!(
+                f,
+                "{} {}: {} -> {}",
+                s.address,
+                field,
+                fmt(&s.a),
+                fmt(&s.b)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Diff two execution reports of conceptually the same transaction (e.g., before and after a patch).
+pub fn diff_reports(a: &ExecutionReport, b: &ExecutionReport) -> ReportDiff {
+    let mut diff = ReportDiff::default();
+    if a.success != b.success {
+        diff.success = Some((a.success, b.success));
+    }
+    if a.gas_used != b.gas_used {
+        diff.gas_used = Some((a.gas_used, b.gas_used));
+    }
+    if a.output != b.output {
+        diff.output = Some((a.output.clone(), b.output.clone()));
+    }
+
+    // logs are compared index by index
+    let n_logs = a.logs.len().max(b.logs.len());
+    for index in 0..n_logs {
+        let (la, lb) = (a.logs.get(index), b.logs.get(index));
+        if la != lb {
+            diff.logs.push(LogDiff {
+                index,
+                a: la.cloned(),
+                b: lb.cloned(),
+            });
+        }
+    }
+
+    // state changes
+    let empty = AccountChange::default();
+    let mut addresses: Vec<&Address> = a.state_changes.keys().collect();
+    addresses.extend(b.state_changes.keys());
+    addresses.sort();
+    addresses.dedup();
+    for addr in addresses {
+        let ca = a.state_changes.get(addr);
+        let cb = b.state_changes.get(addr);
+        let balance = |c: Option<&AccountChange>| c.map(|c| c.balance);
+        let nonce = |c: Option<&AccountChange>| c.map(|c| U256::from(c.nonce));
+        if balance(ca) != balance(cb) {
+            diff.state.push(StateDiff {
+                address: *addr,
+                field: StateField::Balance,
+                a: balance(ca),
+                b: balance(cb),
+            });
+        }
+        if nonce(ca) != nonce(cb) {
+            diff.state.push(StateDiff {
+                address: *addr,
+                field: StateField::Nonce,
+                a: nonce(ca),
+                b: nonce(cb),
+            });
+        }
+        let sa = &ca.unwrap_or(&empty).storage;
+        let sb = &cb.unwrap_or(&empty).storage;
+        let mut slots: Vec<&U256> = sa.keys().chain(sb.keys()).collect();
+        slots.sort();
+        slots.dedup();
+        for slot in slots {
+            let (va, vb) = (sa.get(slot).cloned(), sb.get(slot).cloned());
+            if va != vb {
+                diff.state.push(StateDiff {
+                    address: *addr,
+                    field: StateField::Storage(*slot),
+                    a: va,
+                    b: vb,
+                });
+            }
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        blockchain::transaction::Log,
+        conversion::ConvertTo,
+        engine::{
+            inspector::no_inspector,
+            memory::MemoryBcState,
+            state::BcState,
+            transition::TransitionSpecBuilder,
+            types::{Address, Database, SpecId, TransactTo, TxEnv},
+        },
+        solidity::scripting::compile_solidity,
+    };
+
+    use super::{created_contracts, diff_reports, ExecutionReport, LogDiff};
+
+    #[test]
+    fn test_diff_gas_and_log() {
+        let token: Address = 0x1234.cvt();
+        let log0 = Log {
+            address: token,
+            topics: vec![],
+            data: "0x01".cvt(),
+        };
+        let log1 = Log {
+            address: token,
+            topics: vec![],
+            data: "0x02".cvt(),
+        };
+        let a = ExecutionReport {
+            success: true,
+            gas_used: 21000,
+            logs: vec![log0.clone(), log1.clone()],
+            ..Default::default()
+        };
+        let log1_patched = Log {
+            data: "0x03".cvt(),
+            ..log1.clone()
+        };
+        let b = ExecutionReport {
+            success: true,
+            gas_used: 25000,
+            logs: vec![log0, log1_patched.clone()],
+            ..Default::default()
+        };
+
+        let diff = diff_reports(&a, &b);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.success, None);
+        assert_eq!(diff.gas_used, Some((21000, 25000)));
+        assert_eq!(
+            diff.logs,
+            vec![LogDiff {
+                index: 1,
+                a: Some(log1),
+                b: Some(log1_patched),
+            }]
+        );
+        assert!(diff.state.is_empty());
+
+        let json = diff.to_json();
+        assert_eq!(json["gas_used"][1], 25000);
+        assert_eq!(json["logs"][0]["index"], 1);
+        let text = diff.to_string();
+        assert!(text.contains("gas used: 21000 -> 25000 (+4000)"));
+        assert!(text.contains("log #1"));
+
+        assert!(diff_reports(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn test_created_contracts() {
+        let code = r#"
+            contract Child {}
+            contract Factory {
+                Child public child = new Child();
+            }
+        "#;
+        let contracts = compile_solidity("0.8.12", code).unwrap();
+        let factory_code = contracts
+            .into_iter()
+            .find(|(name, _)| name == "Factory")
+            .unwrap()
+            .1;
+        let deployer: Address = 0x1234.cvt();
+        let tx = TxEnv {
+            caller: deployer,
+            transact_to: TransactTo::create(),
+            data: factory_code,
+            gas_limit: 10_000_000,
+            ..Default::default()
+        };
+        let spec = TransitionSpecBuilder::new()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .append_tx_env(tx)
+            .build()
+            .unwrap();
+        let factory = deployer.create(0);
+        let child = factory.create(1);
+
+        // without state changes, only the top-level contract is known
+        let mut state = MemoryBcState::fresh();
+        let result = state.transit(spec.clone(), no_inspector()).unwrap();
+        assert_eq!(created_contracts(&result[0], None), vec![factory]);
+        assert!(state.basic(child).unwrap().is_some());
+
+        let mut state = MemoryBcState::fresh();
+        let (changes, results) = state.simulate(spec, no_inspector()).unwrap();
+        let report = ExecutionReport::new(&results[0], &changes[0]);
+        assert_eq!(report.created_contracts, vec![factory, child]);
+    }
+}
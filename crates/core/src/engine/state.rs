@@ -5,11 +5,13 @@ use crate::error::SoflError;
 
 use super::types::{Bytecode, Database, Env};
 use super::{
-    inspector::EvmInspector,
+    guard::TransitionGuard,
+    inspector::{no_inspector, EvmInspector},
+    overrides::StateOverrides,
     transition::TransitionSpec,
     types::{
         Account, AccountInfo, AccountStatus, Address, ExecutionResult,
-        StateChange, Storage, U256,
+        StateChange, Storage, KECCAK_EMPTY, U256,
     },
 };
 
@@ -23,7 +25,7 @@ pub trait BcState:
     fn transit<'a, I>(
         &'a mut self,
         spec: TransitionSpec,
-        mut inspector: &mut I,
+        inspector: &mut I,
     ) -> Result<Vec<ExecutionResult>, SoflError>
     where
         <Self as revm::Database>::Error: std::fmt::Debug,
@@ -31,11 +33,17 @@ pub trait BcState:
         I: EvmInspector<&'a mut Self>,
     {
         let spec_id = spec.get_evm_version();
+        let guard = TransitionGuard::new(
+            inspector,
+            spec.step_limit,
+            spec.cancellation_token.clone(),
+            spec.origin,
+        );
         let envs: Vec<Env> = spec.into();
         let mut results = Vec::new();
         let mut evm = revm::EvmBuilder::default()
             .with_db(self)
-            .with_external_context(&mut inspector)
+            .with_external_context(guard)
             .spec_id(spec_id)
             .append_handler_register(inspector_handle_register)
             .build();
@@ -48,6 +56,8 @@ pub trait BcState:
                 })
                 .build();
 
+            evm.context.external.check_before_transaction()?;
+
             // inspector pre-transaction hook
             let insp = &mut evm.context.external;
             if !insp.transaction(&evm.context.evm.env.tx, &evm.context.evm.db) {
@@ -60,20 +70,24 @@ pub trait BcState:
             }
 
             // execute transaction
-            let result = evm.transact_commit().map_err(|e| match e {
-                revm::primitives::EVMError::Transaction(ee) => {
-                    SoflError::InvalidTransaction(ee)
-                }
-                revm::primitives::EVMError::Header(ee) => {
-                    SoflError::InvalidHeader(ee)
-                }
-                revm::primitives::EVMError::Database(ee) => {
-                    SoflError::BcState(format!("{:?}", ee))
-                }
-                revm::primitives::EVMError::Custom(ee) => {
-                    SoflError::BcState(format!("{:?}", ee))
-                }
-            })?;
+            let revm::primitives::ResultAndState { result, state } =
+                evm.transact().map_err(|e| match e {
+                    revm::primitives::EVMError::Transaction(ee) => {
+                        SoflError::InvalidTransaction(ee)
+                    }
+                    revm::primitives::EVMError::Header(ee) => {
+                        SoflError::InvalidHeader(ee)
+                    }
+                    revm::primitives::EVMError::Database(ee) => {
+                        SoflError::BcState(format!("{:?}", ee))
+                    }
+                    revm::primitives::EVMError::Custom(ee) => {
+                        SoflError::BcState(format!("{:?}", ee))
+                    }
+                })?;
+            // the aborted transaction is not committed
+            evm.context.external.check()?;
+            evm.context.evm.db.commit(state);
 
             // inspector post-transaction hook
             let insp = &mut evm.context.external;
@@ -97,6 +111,13 @@ pub trait BcState:
     where
         Self::Error: std::fmt::Debug,
     {
+        if spec.step_limit.is_some()
+            || spec.cancellation_token.is_some()
+            || spec.origin.is_some()
+        {
+            // the guards and the origin are enforced by the inspector hooks
+            return self.transit(spec, no_inspector());
+        }
         let spec_id = spec.get_evm_version();
         let envs: Vec<Env> = spec.into();
         let mut results = Vec::new();
@@ -141,19 +162,25 @@ pub trait BcState:
     fn simulate<'a, I>(
         &'a mut self,
         spec: TransitionSpec,
-        mut inspector: &mut I,
+        inspector: &mut I,
     ) -> Result<(Vec<StateChange>, Vec<ExecutionResult>), SoflError>
     where
         Self::Error: std::fmt::Debug,
         I: EvmInspector<&'a mut Self>,
     {
         let spec_id = spec.get_evm_version();
+        let guard = TransitionGuard::new(
+            inspector,
+            spec.step_limit,
+            spec.cancellation_token.clone(),
+            spec.origin,
+        );
         let envs: Vec<Env> = spec.into();
         let mut results = Vec::new();
         let mut changes = Vec::new();
         let mut evm = revm::EvmBuilder::default()
             .with_db(self)
-            .with_external_context(&mut inspector)
+            .with_external_context(guard)
             .spec_id(spec_id)
             .append_handler_register(inspector_handle_register)
             .build();
@@ -167,6 +194,8 @@ pub trait BcState:
                 })
                 .build();
 
+            evm.context.external.check_before_transaction()?;
+
             // inspector pre-transaction hook
             let insp = &mut evm.context.external;
             if !insp.transaction(&evm.context.evm.env.tx, &evm.context.evm.db) {
@@ -194,6 +223,7 @@ pub trait BcState:
                         SoflError::BcState(format!("{:?}", ee))
                     }
                 })?;
+            evm.context.external.check()?;
 
             // inspector post-transaction hook
             let insp = &mut evm.context.external;
@@ -214,6 +244,8 @@ pub trait BcState:
         changes.into_iter().for_each(|c| self.commit(c));
     }
 
+    /// The code of an account, loaded by its code hash
+    /// if it is not attached to the account info.
     fn get_account_code(
         &mut self,
         address: Address,
@@ -227,7 +259,37 @@ pub trait BcState:
                 ))
             })?
             .unwrap_or_default();
-        Ok(account.code.unwrap_or_default())
+        if let Some(code) = account.code {
+            return Ok(code);
+        }
+        if account.code_hash == KECCAK_EMPTY || account.code_hash.is_zero() {
+            return Ok(Bytecode::default());
+        }
+        self.code_by_hash(account.code_hash).map_err(|e| {
+            SoflError::BcState(format!("failed to get code by hash: {:?}", e))
+        })
+    }
+
+    /// The length of the code of an account, i.e., the result of `EXTCODESIZE`.
+    /// The code is not loaded if the code hash tells that it is empty
+    /// or the code is already attached to the account info.
+    fn code_size(&mut self, address: Address) -> Result<usize, SoflError> {
+        let account = match self.basic(address).map_err(|e| {
+            SoflError::BcState(format!("failed to get account basic: {:?}", e))
+        })? {
+            Some(account) => account,
+            None => return Ok(0),
+        };
+        if account.code_hash == KECCAK_EMPTY || account.code_hash.is_zero() {
+            return Ok(0);
+        }
+        if let Some(code) = account.code {
+            return Ok(code.len());
+        }
+        let code = self.code_by_hash(account.code_hash).map_err(|e| {
+            SoflError::BcState(format!("failed to get code by hash: {:?}", e))
+        })?;
+        Ok(code.len())
     }
 
     /// Set a new value to a storage slot of an account.
@@ -331,6 +393,81 @@ pub trait BcState:
         self.commit(changes);
         Ok(original_code)
     }
+
+    /// Apply the state overrides, following the semantics of `eth_call`:
+    /// the given balance, nonce, and code replace the original ones,
+    /// `stateDiff` overrides the listed slots,
+    /// and `state` replaces the whole storage of the account.
+    fn apply_overrides(
+        &mut self,
+        overrides: StateOverrides,
+    ) -> Result<(), SoflError> {
+        let mut changes = StateChange::new();
+        for (address, o) in overrides {
+            let mut info = self
+                .basic(address)
+                .map_err(|e| {
+                    SoflError::BcState(format!(
+                        "failed to get account basic: {:?}",
+                        e
+                    ))
+                })?
+                .unwrap_or_default();
+            if let Some(balance) = o.balance {
+                info.balance = balance;
+            }
+            if let Some(nonce) = o.nonce {
+                info.nonce = nonce.to();
+            }
+            if let Some(code) = o.code {
+                let code = Bytecode::new_raw(code);
+                info.code_hash = code.hash_slow();
+                info.code = Some(code);
+            }
+            let mut status = AccountStatus::Touched;
+            let slots = match (o.state, o.state_diff) {
+                (Some(_), Some(_)) => {
+                    return Err(SoflError::Custom(format!(
+                        "both state and stateDiff are overridden for {}",
+                        address
+                    )))
+                }
+                (Some(state), None) => {
+                    // the original storage is cleared on commit
+                    status |= AccountStatus::Created;
+                    state
+                }
+                (None, Some(state_diff)) => state_diff,
+                (None, None) => Default::default(),
+            };
+            let storage = slots
+                .into_iter()
+                .map(|(slot, value)| (slot, StorageSlot::new(value)))
+                .collect();
+            changes.insert(
+                address,
+                Account {
+                    info,
+                    storage,
+                    status,
+                },
+            );
+        }
+        self.commit(changes);
+        Ok(())
+    }
+
+    /// Apply the state overrides and return the state.
+    fn with_overrides(
+        mut self,
+        overrides: StateOverrides,
+    ) -> Result<Self, SoflError>
+    where
+        Self: Sized,
+    {
+        self.apply_overrides(overrides)?;
+        Ok(self)
+    }
 }
 
 /// Any type that implements revm::Database auto-implements BcState.
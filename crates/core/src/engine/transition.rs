@@ -1,4 +1,5 @@
 use revm_primitives::{BlockEnv, CfgEnv, SpecId, TxEnv};
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     blockchain::{
@@ -8,7 +9,10 @@ use crate::{
     error::SoflError,
 };
 
-use super::types::{BlockHashOrNumber, Env, TxHash};
+use super::{
+    block_context::{BlockContext, FromProvider},
+    types::{Address, BlockHashOrNumber, Env, TxHash},
+};
 
 #[derive(Default, Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct TransitionSpec {
@@ -17,6 +21,17 @@ pub struct TransitionSpec {
     pub cfg: CfgEnv,
     pub block: BlockEnv,
     pub txs: Vec<TxEnv>,
+    /// The maximum number of steps executed in the transition,
+    /// exceeding which aborts the transition with `SoflError::StepLimitExceeded`.
+    #[serde(default)]
+    pub step_limit: Option<u64>,
+    /// Cancelling the token aborts the transition with `SoflError::Interrupted`.
+    #[serde(skip)]
+    pub cancellation_token: Option<CancellationToken>,
+    /// The `tx.origin` seen by the transactions instead of their senders,
+    /// e.g., to pass or deliberately fail `tx.origin == msg.sender` checks.
+    #[serde(default)]
+    pub origin: Option<Address>,
 }
 
 impl TransitionSpec {
@@ -73,7 +88,8 @@ pub fn get_evm_version(chain_id: u64, block_number: u64) -> SpecId {
         13773000..=15049999 => SpecId::ARROW_GLACIER,
         15050000..=15537393 => SpecId::GRAY_GLACIER,
         15537394..=17034869 => SpecId::MERGE,
-        17034870.. => SpecId::SHANGHAI,
+        17034870..=19426586 => SpecId::SHANGHAI,
+        19426587.. => SpecId::CANCUN,
     };
     spec_id
 }
@@ -96,6 +112,11 @@ pub struct TransitionSpecBuilder {
     block: BlockEnv,
     txs: Vec<TxEnv>,
     bypass_check: bool,
+    step_limit: Option<u64>,
+    cancellation_token: Option<CancellationToken>,
+    origin: Option<Address>,
+    /// The first failure in filling the env, reported by `build`.
+    error: Option<SoflError>,
 }
 
 impl TransitionSpecBuilder {
@@ -119,7 +140,10 @@ impl From<TransitionSpec> for Vec<Env> {
 }
 
 impl TransitionSpecBuilder {
-    pub fn build(mut self) -> TransitionSpec {
+    pub fn build(mut self) -> Result<TransitionSpec, SoflError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
         if self.bypass_check {
             self.cfg.disable_balance_check = true;
             self.cfg.disable_base_fee = true;
@@ -129,12 +153,15 @@ impl TransitionSpecBuilder {
                 tx.nonce = None;
             });
         }
-        TransitionSpec {
+        Ok(TransitionSpec {
             evm_version: self.evm_version,
             cfg: self.cfg,
             block: self.block,
             txs: self.txs,
-        }
+            step_limit: self.step_limit,
+            cancellation_token: self.cancellation_token,
+            origin: self.origin,
+        })
     }
 
     pub fn set_evm_version(mut self, evm_version: SpecId) -> Self {
@@ -155,6 +182,26 @@ impl TransitionSpecBuilder {
         self
     }
 
+    /// Bound the number of steps executed in the transition,
+    /// e.g., to stop adversarial inputs that loop until running out of gas.
+    pub fn set_step_limit(mut self, step_limit: u64) -> Self {
+        self.step_limit.replace(step_limit);
+        self
+    }
+
+    /// Allow the transition to be cancelled in the middle of a transaction,
+    /// e.g., on SIGINT.
+    pub fn set_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token.replace(token);
+        self
+    }
+
+    /// Spoof `tx.origin` of the transactions, independent of their senders.
+    pub fn set_origin(mut self, origin: Address) -> Self {
+        self.origin.replace(origin);
+        self
+    }
+
     pub fn set_cfg(mut self, cfg: CfgEnv) -> Self {
         self.cfg = cfg;
         self
@@ -173,14 +220,20 @@ impl TransitionSpecBuilder {
     }
 
     pub fn at_block<T: Tx, P: BcProvider<T>>(
-        mut self,
+        self,
         p: P,
-        block: BlockHashOrNumber,
+        block: impl Into<BlockHashOrNumber>,
     ) -> Self {
-        p.fill_cfg_env(&mut self.cfg, block)
-            .expect("assumption: chain cfg must exist");
-        p.fill_block_env(&mut self.block, block)
-            .expect("assumption: block must exist");
+        self.at_block_context(FromProvider::new(p, block))
+    }
+
+    /// Execute the transition in the block env given by the context,
+    /// e.g., a block in the future with `Offset`.
+    /// Failing to fill the env makes `build` return the error.
+    pub fn at_block_context<C: BlockContext>(mut self, ctx: C) -> Self {
+        if let Err(err) = ctx.fill_env(&mut self.cfg, &mut self.block) {
+            self.error.get_or_insert(err);
+        }
         self
     }
 }
@@ -71,6 +71,8 @@ pub type FixedBytes<const M: usize> = revm::primitives::FixedBytes<M>;
 pub type AccountInfo = revm::primitives::AccountInfo;
 pub type Account = revm::primitives::Account;
 pub type AccountStatus = revm::primitives::AccountStatus;
+/// Status of an account cached in a MemoryBcState.
+pub type AccountState = revm::db::AccountState;
 pub type StorageKey = alloy_primitives::StorageKey;
 pub type StorageValue = alloy_primitives::StorageValue;
 pub type Storage =
@@ -93,6 +95,8 @@ pub type BlobExcessGasAndPrice = revm::primitives::BlobExcessGasAndPrice;
 
 pub type TransactTo = revm::primitives::TransactTo;
 pub type TxEnv = revm::primitives::TxEnv;
+/// EIP-2930 access list in the format of `TxEnv::access_list`.
+pub type AccessList = Vec<(Address, Vec<U256>)>;
 pub type BlockEnv = revm::primitives::BlockEnv;
 pub type CfgEnv = revm::primitives::CfgEnv;
 pub type StateChange = revm::primitives::State;
@@ -106,6 +110,7 @@ pub type CallScheme = revm::interpreter::CallScheme;
 pub type Output = revm::primitives::Output;
 pub type CreateScheme = revm::primitives::CreateScheme;
 pub type CreateOutcome = revm::interpreter::CreateOutcome;
+pub type EvmLog = alloy_primitives::Log;
 
 pub const KECCAK_EMPTY: B256 = revm::primitives::KECCAK_EMPTY;
 pub use revm::primitives::keccak256;
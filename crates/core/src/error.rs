@@ -2,7 +2,7 @@ use crate::engine::types::ExecutionResult;
 
 pub type Result<T, E = SoflError> = std::result::Result<T, E>;
 
-#[derive(Debug, derive_more::Display, thiserror::Error)]
+#[derive(Debug, Clone, derive_more::Display, thiserror::Error)]
 pub enum SoflError {
     #[display(fmt = "Err not found: {}", _0)]
     NotFound(String),
@@ -34,6 +34,9 @@ pub enum SoflError {
     #[display(fmt = "Execution interrupted")]
     Interrupted,
 
+    #[display(fmt = "Err step limit exceeded: {}", _0)]
+    StepLimitExceeded(u64),
+
     #[display(fmt = "Err: {}", _0)]
     Custom(String),
 }
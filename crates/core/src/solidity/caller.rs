@@ -2,7 +2,8 @@ use crate::{
     blockchain::{provider::BcProvider, transaction::Tx},
     conversion::ConvertTo,
     engine::{
-        inspector::EvmInspector,
+        block_context::BlockContext,
+        inspector::{no_inspector, EvmInspector},
         state::BcState,
         transition::TransitionSpecBuilder,
         types::{
@@ -63,6 +64,14 @@ impl HighLevelCaller {
         self
     }
 
+    /// Spoof `tx.origin` of the calls, which is the caller address by default,
+    /// e.g., to pass EOA-only checks (`tx.origin == msg.sender`) when calling
+    /// from a contract, or to deliberately fail them.
+    pub fn set_origin(mut self, origin: Address) -> Self {
+        self.spec_builder = self.spec_builder.set_origin(origin);
+        self
+    }
+
     pub fn set_cfg(mut self, cfg: CfgEnv) -> Self {
         self.spec_builder = self.spec_builder.set_cfg(cfg);
         self
@@ -83,7 +92,12 @@ impl HighLevelCaller {
         p: P,
         block: B,
     ) -> Self {
-        self.spec_builder = self.spec_builder.at_block(p, block.into());
+        self.spec_builder = self.spec_builder.at_block(p, block);
+        self
+    }
+
+    pub fn at_block_context<C: BlockContext>(mut self, ctx: C) -> Self {
+        self.spec_builder = self.spec_builder.at_block_context(ctx);
         self
     }
 
@@ -116,7 +130,7 @@ impl HighLevelCaller {
         tx.transact_to = TransactTo::Call(callee);
         tx.gas_limit = self.gas_limit;
         tx.data = calldata;
-        let spec = self.spec_builder.clone().append_tx_env(tx).build();
+        let spec = self.spec_builder.clone().append_tx_env(tx).build()?;
 
         let (_, mut result) = state.simulate(spec, inspector)?;
         let result = result.pop().unwrap();
@@ -154,7 +168,7 @@ impl HighLevelCaller {
         tx.gas_limit = self.gas_limit;
         tx.value = value.unwrap_or(U256::default());
 
-        let spec = self.spec_builder.clone().append_tx_env(tx).build();
+        let spec = self.spec_builder.clone().append_tx_env(tx).build()?;
         let mut result = state.transit(spec, inspector)?;
 
         let result = result.pop().unwrap();
@@ -193,7 +207,7 @@ impl HighLevelCaller {
         tx.gas_limit = self.gas_limit;
         tx.value = value.unwrap_or(U256::default());
 
-        let spec = self.spec_builder.clone().append_tx_env(tx).build();
+        let spec = self.spec_builder.clone().append_tx_env(tx).build()?;
         let (mut changes, mut result) = state.simulate(spec, inspector)?;
         let change = changes.pop().unwrap();
 
@@ -227,7 +241,7 @@ impl HighLevelCaller {
         tx.gas_limit = self.gas_limit;
         tx.value = value.unwrap_or(U256::default());
 
-        let spec = self.spec_builder.clone().append_tx_env(tx).build();
+        let spec = self.spec_builder.clone().append_tx_env(tx).build()?;
         let mut result = state.transit(spec, inspector)?;
         let result = result.pop().unwrap();
         match result.clone() {
@@ -266,7 +280,7 @@ impl HighLevelCaller {
         tx.gas_limit = self.gas_limit;
         tx.value = value.unwrap_or(U256::default());
 
-        let spec = self.spec_builder.clone().append_tx_env(tx).build();
+        let spec = self.spec_builder.clone().append_tx_env(tx).build()?;
         let (mut changes, mut result) = state.simulate(spec, inspector)?;
         let result = result.pop().unwrap();
         let change = changes.pop().unwrap();
@@ -285,6 +299,72 @@ impl HighLevelCaller {
         }
     }
 
+    /// Estimate the gas limit needed by a call like `eth_estimateGas`,
+    /// i.e., the minimum gas limit with which the call succeeds.
+    /// This can be more than the gas used by the call, since gas refunds are
+    /// deducted afterwards and only 63/64 of the remaining gas is passed to
+    /// subcalls, so the limit is binary-searched by executing the call.
+    /// The gas limit of the caller (or the block gas limit if not set) is
+    /// the upper bound. State will not be changed.
+    pub fn estimate_gas<BS: BcState>(
+        &self,
+        state: &mut BS,
+        callee: Address,
+        calldata: Bytes,
+        value: Option<U256>,
+    ) -> Result<u64, SoflError>
+    where
+        BS::Error: std::fmt::Debug,
+    {
+        let mut tx = TxEnv::default();
+        tx.caller = self.address;
+        tx.transact_to = TransactTo::Call(callee);
+        tx.data = calldata;
+        tx.value = value.unwrap_or(U256::default());
+        let spec = self.spec_builder.clone().append_tx_env(tx).build()?;
+        let cap = if self.gas_limit > 0 {
+            self.gas_limit
+        } else {
+            spec.block.gas_limit.min(U256::from(u64::MAX)).cvt()
+        };
+        let mut run = |gas_limit: u64| {
+            let mut spec = spec.clone();
+            spec.txs[0].gas_limit = gas_limit;
+            match state.simulate(spec, no_inspector()) {
+                Ok((_, mut results)) => Ok(Ok(results.remove(0))),
+                // e.g., the gas limit is lower than the intrinsic gas
+                Err(SoflError::InvalidTransaction(e)) => Ok(Err(e)),
+                Err(e) => Err(e),
+            }
+        };
+
+        let result = match run(cap)? {
+            Ok(result) if result.is_success() => result,
+            Ok(result) => return Err(SoflError::Exec(result)),
+            Err(e) => return Err(SoflError::InvalidTransaction(e)),
+        };
+        let succeeds = |r: Result<ExecutionResult, _>| {
+            r.is_ok_and(|result: ExecutionResult| result.is_success())
+        };
+        // the gas used after refunds is a lower bound
+        let mut lo = result.gas_used().saturating_sub(1);
+        let mut hi = cap;
+        // optimistically try the gas used with the 63/64 rule
+        let guess = result.gas_used().saturating_mul(64) / 63;
+        if guess < hi && succeeds(run(guess)?) {
+            hi = guess;
+        }
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if succeeds(run(mid)?) {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Ok(hi)
+    }
+
     pub fn view<'a, BS: BcState, I: EvmInspector<&'a mut BS>>(
         &self,
         state: &'a mut BS,
@@ -322,3 +402,158 @@ impl HighLevelCaller {
             .map_err(|e| SoflError::Abi(format!("{:?}", e)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+    use alloy_json_abi::Function;
+
+    use crate::{
+        conversion::ConvertTo,
+        engine::{
+            inspector::no_inspector,
+            memory::MemoryBcState,
+            types::{Address, Bytes, SpecId, U256},
+        },
+        solidity::scripting::{deploy_contracts, SolScriptConfig},
+    };
+
+    use super::HighLevelCaller;
+
+    #[test]
+    fn test_spoof_origin() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract EoaOnly {
+                uint256 public hits;
+                function hit() external {
+                    require(msg.sender == tx.origin, "not eoa");
+                    hits += 1;
+                }
+            }
+            contract Relay {
+                function relay(EoaOnly target) external {
+                    target.hit();
+                }
+            }
+        "#;
+        let contracts = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["EoaOnly", "Relay"],
+            SolScriptConfig::default(),
+        )
+        .unwrap();
+        let (target, relay) = (contracts[0], contracts[1]);
+        let args = [DynSolValue::Address(target)];
+        let caller = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST);
+
+        // the relay is not tx.origin
+        let err = caller.invoke(
+            &mut state,
+            relay,
+            "relay(address)",
+            &args,
+            None,
+            no_inspector(),
+        );
+        assert!(err.is_err());
+
+        // the relay is tx.origin, as if it were an EOA
+        caller
+            .clone()
+            .set_origin(relay)
+            .invoke(
+                &mut state,
+                relay,
+                "relay(address)",
+                &args,
+                None,
+                no_inspector(),
+            )
+            .unwrap();
+        let hits = caller
+            .view(
+                &mut state,
+                target,
+                "hits() returns (uint256)",
+                &[],
+                no_inspector(),
+            )
+            .unwrap();
+        assert_eq!(hits[0].as_uint().unwrap().0, U256::from(1));
+
+        // a direct call fails with another origin
+        let other: Address = 0x1234.cvt();
+        let err = caller.set_origin(other).invoke(
+            &mut state,
+            target,
+            "hit()",
+            &[],
+            None,
+            no_inspector(),
+        );
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_estimate_transfer_gas() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Token {
+                mapping(address => uint256) public balanceOf;
+                constructor() {
+                    balanceOf[msg.sender] = 1000;
+                }
+                function transfer(address to, uint256 amount) external {
+                    balanceOf[msg.sender] -= amount;
+                    balanceOf[to] += amount;
+                }
+            }
+        "#;
+        let token = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Token"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        // the deployer holds the tokens
+        let holder = Address::ZERO;
+        let caller = HighLevelCaller::new(holder)
+            .set_gas_limit(1_000_000)
+            .set_evm_version(SpecId::LATEST);
+        let to: Address = 0x1234.cvt();
+        let calldata: Bytes = Function::parse("transfer(address,uint256)")
+            .unwrap()
+            .abi_encode_input(&[
+                DynSolValue::Address(to),
+                DynSolValue::Uint(U256::from(10), 256),
+            ])
+            .unwrap()
+            .cvt();
+
+        let estimate = caller
+            .estimate_gas(&mut state, token, calldata.clone(), None)
+            .unwrap();
+        assert!(estimate > 21000 && estimate < 1_000_000);
+
+        let err = caller.clone().set_gas_limit(estimate - 1).call(
+            &mut state,
+            token,
+            calldata.clone(),
+            None,
+            no_inspector(),
+        );
+        assert!(err.is_err());
+        caller
+            .set_gas_limit(estimate)
+            .call(&mut state, token, calldata, None, no_inspector())
+            .unwrap();
+    }
+}
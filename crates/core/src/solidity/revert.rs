@@ -0,0 +1,148 @@
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::JsonAbi;
+use alloy_sol_types::{Panic, Revert, SolError};
+
+use crate::engine::types::Bytes;
+
+/// The decoded reason of a reverted execution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevertReason {
+    /// `Error(string)`, e.g., `require(cond, "reason")` or `revert("reason")`.
+    String(String),
+
+    /// `Panic(uint256)`, e.g., arithmetic overflow (0x11) or division by zero (0x12).
+    Panic(u64),
+
+    /// A custom error defined in the contract ABI.
+    Custom {
+        name: String,
+        params: Vec<DynSolValue>,
+    },
+
+    /// The revert data can not be decoded.
+    Unknown(Bytes),
+}
+
+/// Decode the revert data of a reverted execution.
+/// The standard `Error(string)` and `Panic(uint256)` are always recognized.
+/// If `abi` is given, the 4-byte selector is matched against the custom errors in the ABI.
+pub fn decode_revert(data: &Bytes, abi: Option<&JsonAbi>) -> RevertReason {
+    if let Ok(revert) = Revert::abi_decode(data, true) {
+        return RevertReason::String(revert.reason);
+    }
+    if let Ok(panic) = Panic::abi_decode(data, true) {
+        if let Ok(code) = u64::try_from(panic.code) {
+            return RevertReason::Panic(code);
+        }
+    }
+    if data.len() >= 4 {
+        if let Some(abi) = abi {
+            let (selector, params) = data.split_at(4);
+            for error in abi.errors() {
+                if error.selector().as_slice() != selector {
+                    continue;
+                }
+                if let Ok(params) = error.abi_decode_input(params, true) {
+                    return RevertReason::Custom {
+                        name: error.name.clone(),
+                        params,
+                    };
+                }
+            }
+        }
+    }
+    RevertReason::Unknown(data.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_dyn_abi::DynSolValue;
+    use alloy_json_abi::JsonAbi;
+
+    use crate::{
+        engine::{
+            inspector::no_inspector,
+            memory::MemoryBcState,
+            types::{Bytes, ExecutionResult, SpecId, U256},
+        },
+        error::SoflError,
+        solidity::{caller::HighLevelCaller, scripting::deploy_contracts},
+    };
+
+    use super::{decode_revert, RevertReason};
+
+    fn revert_data(func: &str) -> Bytes {
+        let mut state = MemoryBcState::fresh();
+        let contract = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            r#"
+            contract A {
+                error Insufficient(uint256 available, uint256 required);
+                function str() public {
+                    revert("not allowed");
+                }
+                function overflow() public returns (uint256) {
+                    uint256 x = type(uint256).max;
+                    return x + 1;
+                }
+                function custom() public {
+                    revert Insufficient(1, 2);
+                }
+            }
+            "#,
+            vec!["A"],
+            Default::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let err = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .invoke(&mut state, contract, func, &[], None, no_inspector())
+            .unwrap_err();
+        match err {
+            SoflError::Exec(ExecutionResult::Revert { output, .. }) => output,
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_decode_string_revert() {
+        let data = revert_data("str()");
+        let reason = decode_revert(&data, None);
+        assert_eq!(reason, RevertReason::String("not allowed".to_string()));
+    }
+
+    #[test]
+    fn test_decode_panic() {
+        let data = revert_data("overflow()");
+        let reason = decode_revert(&data, None);
+        assert_eq!(reason, RevertReason::Panic(0x11));
+    }
+
+    #[test]
+    fn test_decode_custom_error() {
+        let data = revert_data("custom()");
+        assert_eq!(
+            decode_revert(&data, None),
+            RevertReason::Unknown(data.clone())
+        );
+
+        let abi = JsonAbi::parse([
+            "error Insufficient(uint256 available, uint256 required)",
+        ])
+        .unwrap();
+        let reason = decode_revert(&data, Some(&abi));
+        assert_eq!(
+            reason,
+            RevertReason::Custom {
+                name: "Insufficient".to_string(),
+                params: vec![
+                    DynSolValue::Uint(U256::from(1), 256),
+                    DynSolValue::Uint(U256::from(2), 256),
+                ],
+            }
+        );
+    }
+}
@@ -107,6 +107,12 @@ impl Tx for JsonRpcTx {
 
     #[doc = " Returns the gas used by the transaction."]
     #[doc = " None if the transaction is not executed."]
+    fn gas_used(&self) -> Option<u64> {
+        self.receipt.as_ref()?.gas_used.map(|g| g.cvt())
+    }
+
+    #[doc = " Returns the logs emitted by the transaction."]
+    #[doc = " None if the transaction is not executed."]
     fn logs(&self) -> Option<Vec<Log>> {
         if self.success().is_none() {
             None
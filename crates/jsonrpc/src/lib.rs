@@ -1,4 +1,5 @@
 pub mod blockchain;
 pub mod config;
 pub mod provider;
+pub mod retry;
 pub mod state;
@@ -1,10 +1,16 @@
 use std::{
     collections::HashMap,
-    sync::{Arc, RwLock},
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
 };
 
 use alloy_providers::provider::{Provider, TempProvider};
 use alloy_rpc_types::{Block, BlockNumberOrTag};
+use alloy_transport::TransportResult;
 use alloy_transport_http::Http;
 use libsofl_core::{
     blockchain::{
@@ -20,13 +26,15 @@ use libsofl_core::{
 use libsofl_utils::sync::runtime::AsyncRuntime;
 use reqwest::Client;
 
-use crate::blockchain::JsonRpcTx;
+use crate::{blockchain::JsonRpcTx, retry::RetryPolicy};
 
 pub struct JsonRpcProvider {
     pub url: String,
     pub p: Arc<Provider<Http<Client>>>,
 
     pub(crate) rt: AsyncRuntime,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) request_count: Arc<AtomicUsize>,
 
     // caches
     pub(crate) chain_id: u64,
@@ -51,6 +59,8 @@ impl JsonRpcProvider {
             url,
             p,
             rt,
+            retry: RetryPolicy::default(),
+            request_count: Default::default(),
             chain_id: chain_id.cvt(),
             txs: Default::default(),
             txs_in_block: Default::default(),
@@ -66,6 +76,8 @@ impl Clone for JsonRpcProvider {
             url: self.url.clone(),
             p: self.p.clone(),
             rt: self.rt.clone(),
+            retry: self.retry,
+            request_count: self.request_count.clone(),
             chain_id: self.chain_id,
             txs: self.txs.clone(),
             txs_in_block: self.txs_in_block.clone(),
@@ -75,6 +87,40 @@ impl Clone for JsonRpcProvider {
     }
 }
 
+impl JsonRpcProvider {
+    /// Retry requests failed with transient errors (e.g., transport failures and rate limiting)
+    /// up to `max_attempts` attempts in total, with exponential backoff starting from `base_delay`.
+    pub fn with_retry(
+        mut self,
+        max_attempts: u32,
+        base_delay: Duration,
+    ) -> Self {
+        self.retry = RetryPolicy::new(max_attempts, base_delay);
+        self
+    }
+
+    /// The number of JSON-RPC requests sent by this provider and its clones,
+    /// including retries.
+    pub fn request_count(&self) -> usize {
+        self.request_count.load(Ordering::Relaxed)
+    }
+
+    /// Send a JSON-RPC request with the retry policy.
+    pub(crate) fn request<T, Fut, F>(
+        &self,
+        mut request: F,
+    ) -> TransportResult<T>
+    where
+        Fut: Future<Output = TransportResult<T>>,
+        F: FnMut() -> Fut,
+    {
+        self.retry.run(&self.rt, || {
+            self.request_count.fetch_add(1, Ordering::Relaxed);
+            request()
+        })
+    }
+}
+
 impl JsonRpcProvider {
     fn block(&self, block: BlockHashOrNumber) -> Result<Block, SoflError> {
         match block {
@@ -87,10 +133,11 @@ impl JsonRpcProvider {
                     .get(&hash)
                     .map(|b| Result::<Block, SoflError>::Ok(b.clone()))
                     .unwrap_or_else(|| {
-                        let task = self.p.get_block_by_hash(hash, false);
-                        let blk = self.rt.block_on(task).map_err(|e| {
-                            SoflError::Provider(format!("{:?}", e))
-                        })?;
+                        let blk = self
+                            .request(|| self.p.get_block_by_hash(hash, false))
+                            .map_err(|e| {
+                                SoflError::Provider(format!("{:?}", e))
+                            })?;
                         let blk = blk.ok_or(SoflError::NotFound(format!(
                             "block {}",
                             hash
@@ -116,13 +163,16 @@ impl JsonRpcProvider {
                     .get(&number)
                     .map(|b| Result::<Block, SoflError>::Ok(b.clone()))
                     .unwrap_or_else(|| {
-                        let task = self.p.get_block_by_number(
-                            BlockNumberOrTag::Number(number),
-                            false,
-                        );
-                        let blk = self.rt.block_on(task).map_err(|e| {
-                            SoflError::Provider(format!("{:?}", e))
-                        })?;
+                        let blk = self
+                            .request(|| {
+                                self.p.get_block_by_number(
+                                    BlockNumberOrTag::Number(number),
+                                    false,
+                                )
+                            })
+                            .map_err(|e| {
+                                SoflError::Provider(format!("{:?}", e))
+                            })?;
                         let blk = blk.ok_or(SoflError::NotFound(format!(
                             "block {}",
                             number
@@ -152,10 +202,8 @@ impl BcProvider<JsonRpcTx> for JsonRpcProvider {
         txs.get(&tx)
             .map(|t| Result::<JsonRpcTx, SoflError>::Ok(t.clone()))
             .unwrap_or_else(move || {
-                let task = match &tx {
-                    TxHashOrPosition::Hash(hash) => {
-                        self.p.get_transaction_by_hash(*hash)
-                    }
+                let hash = match &tx {
+                    TxHashOrPosition::Hash(hash) => *hash,
                     TxHashOrPosition::Position(TxPosition { block, index }) => {
                         let blk = self.block(*block)?;
                         let hash = blk
@@ -167,22 +215,27 @@ impl BcProvider<JsonRpcTx> for JsonRpcProvider {
                             "transaction {} in block {}",
                             index, block
                         )))?;
-                        self.p.get_transaction_by_hash(*hash)
+                        *hash
                     }
                 };
-                let transaction = self.rt.block_on(task).map_err(|e| {
-                    SoflError::Provider(format!(
-                        "failed to get transaction {}: {:?}",
-                        tx, e
-                    ))
-                })?;
-                let task = self.p.get_transaction_receipt(transaction.hash);
-                let receipt = self.rt.block_on(task).map_err(|e| {
-                    SoflError::Provider(format!(
-                        "failed to get transaction {} receipt: {:?}",
-                        transaction.hash, e
-                    ))
-                })?;
+                let transaction = self
+                    .request(|| self.p.get_transaction_by_hash(hash))
+                    .map_err(|e| {
+                        SoflError::Provider(format!(
+                            "failed to get transaction {}: {:?}",
+                            tx, e
+                        ))
+                    })?;
+                let receipt = self
+                    .request(|| {
+                        self.p.get_transaction_receipt(transaction.hash)
+                    })
+                    .map_err(|e| {
+                        SoflError::Provider(format!(
+                            "failed to get transaction {} receipt: {:?}",
+                            transaction.hash, e
+                        ))
+                    })?;
                 let t = JsonRpcTx {
                     tx: transaction,
                     receipt,
@@ -270,7 +323,8 @@ impl BcProvider<JsonRpcTx> for JsonRpcProvider {
         //     13773000..=15049999 => SpecId::ARROW_GLACIER,
         //     15050000..=15537393 => SpecId::GRAY_GLACIER,
         //     15537394..=17034869 => SpecId::MERGE,
-        //     17034870.. => SpecId::SHANGHAI,
+        //     17034870..=19426586 => SpecId::SHANGHAI,
+        //     19426587.. => SpecId::CANCUN,
         // };
         Ok(())
     }
@@ -0,0 +1,186 @@
+use std::{future::Future, time::Duration};
+
+use alloy_json_rpc::RpcError;
+use alloy_transport::TransportError;
+use libsofl_utils::sync::runtime::AsyncRuntime;
+
+/// Whether an error is worth retrying, e.g., transport failures and rate limiting,
+/// as opposed to logical errors returned by the node.
+pub trait Transient {
+    fn is_transient(&self) -> bool;
+}
+
+impl Transient for TransportError {
+    fn is_transient(&self) -> bool {
+        let rate_limited = |text: &str| {
+            let text = text.to_lowercase();
+            text.contains("429")
+                || text.contains("too many requests")
+                || text.contains("rate limit")
+        };
+        match self {
+            RpcError::Transport(_) => true,
+            // -32005: limit exceeded
+            RpcError::ErrorResp(payload) => {
+                matches!(payload.code, 429 | -32005)
+                    || rate_limited(&payload.message)
+            }
+            // HTTP error pages are not valid JSON-RPC responses
+            RpcError::DeserError { text, .. } => rate_limited(text),
+            _ => false,
+        }
+    }
+}
+
+/// The default upper bound of the delay between two attempts.
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Exponential backoff retry of JSON-RPC requests.
+/// The n-th retry waits `base_delay * 2^(n-1)`, capped at `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// No retry.
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            base_delay: Duration::from_millis(500),
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// The delay before the n-th retry, i.e., after the n-th attempt fails.
+    /// It saturates instead of overflowing for large n.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Run the request until it succeeds, fails with a non-transient error,
+    /// or runs out of attempts.
+    /// The request is rebuilt for each attempt.
+    pub fn run<T, E, Fut, F>(
+        &self,
+        rt: &AsyncRuntime,
+        mut request: F,
+    ) -> Result<T, E>
+    where
+        E: Transient,
+        Fut: Future<Output = Result<T, E>>,
+        F: FnMut() -> Fut,
+    {
+        let mut attempt = 1;
+        loop {
+            match rt.block_on(request()) {
+                Err(e) if e.is_transient() && attempt < self.max_attempts => {
+                    // the runtime may not be tokio, so sleep the thread
+                    std::thread::sleep(self.delay(attempt));
+                    attempt += 1;
+                }
+                r => return r,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, time::Duration};
+
+    use libsofl_utils::sync::runtime::AsyncRuntime;
+
+    use super::{RetryPolicy, Transient};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum MockError {
+        RateLimited,
+        NotFound,
+    }
+
+    impl Transient for MockError {
+        fn is_transient(&self) -> bool {
+            *self == MockError::RateLimited
+        }
+    }
+
+    /// A transport that fails with the errors in order and then succeeds.
+    struct MockTransport {
+        errors: Vec<MockError>,
+        requests: Cell<usize>,
+    }
+
+    impl MockTransport {
+        async fn get_storage(&self) -> Result<u64, MockError> {
+            let i = self.requests.get();
+            self.requests.set(i + 1);
+            match self.errors.get(i) {
+                Some(e) => Err(e.clone()),
+                None => Ok(42),
+            }
+        }
+    }
+
+    #[test]
+    fn test_retry_transient_errors() {
+        let rt = AsyncRuntime::new();
+        let policy = RetryPolicy::new(3, Duration::from_millis(1));
+        let transport = MockTransport {
+            errors: vec![MockError::RateLimited, MockError::RateLimited],
+            requests: Cell::new(0),
+        };
+        let value = policy.run(&rt, || transport.get_storage());
+        assert_eq!(value, Ok(42));
+        assert_eq!(transport.requests.get(), 3);
+
+        // out of attempts
+        let transport = MockTransport {
+            errors: vec![MockError::RateLimited; 3],
+            requests: Cell::new(0),
+        };
+        let value = policy.run(&rt, || transport.get_storage());
+        assert_eq!(value, Err(MockError::RateLimited));
+
+        // logical errors are not retried
+        let transport = MockTransport {
+            errors: vec![MockError::NotFound],
+            requests: Cell::new(0),
+        };
+        let value = policy.run(&rt, || transport.get_storage());
+        assert_eq!(value, Err(MockError::NotFound));
+        assert_eq!(transport.requests.get(), 1);
+    }
+
+    #[test]
+    fn test_delay_is_capped() {
+        let policy = RetryPolicy::new(100, Duration::from_millis(500));
+        assert_eq!(policy.delay(1), Duration::from_millis(500));
+        assert_eq!(policy.delay(3), Duration::from_secs(2));
+        assert_eq!(policy.delay(40), policy.max_delay);
+        assert_eq!(policy.delay(u32::MAX), policy.max_delay);
+
+        let policy =
+            RetryPolicy::new(100, Duration::MAX).with_max_delay(Duration::MAX);
+        assert_eq!(policy.delay(64), Duration::MAX);
+    }
+}
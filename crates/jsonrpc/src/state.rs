@@ -1,10 +1,11 @@
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex},
+    sync::{atomic::Ordering, Arc, Mutex},
 };
 
 use alloy_providers::provider::TempProvider;
 use alloy_rpc_types::BlockNumberOrTag;
+use futures::future::join_all;
 use libsofl_core::{
     blockchain::{
         provider::{BcProvider, BcStateProvider},
@@ -12,16 +13,16 @@ use libsofl_core::{
     },
     conversion::ConvertTo,
     engine::{
-        memory::MemoryBcState,
+        memory::{DumpStorage, MemoryBcState, StorageDump},
         types::{
-            keccak256, AccountInfo, Address, BlockHashOrNumber, Bytecode,
-            DatabaseRef, Hash, B256, KECCAK_EMPTY, U256,
+            keccak256, AccountInfo, AccountState, Address, BlockHashOrNumber,
+            Bytecode, DatabaseRef, Hash, B256, KECCAK_EMPTY, U256,
         },
     },
     error::SoflError,
 };
 
-use crate::provider::JsonRpcProvider;
+use crate::{provider::JsonRpcProvider, retry::Transient};
 
 pub struct JsonrRpcBcStateRef {
     pub(crate) provider: JsonRpcProvider,
@@ -39,6 +40,51 @@ impl BcStateProvider<JsonrRpcBcStateRef> for JsonRpcProvider {
             pos,
         }))
     }
+
+    /// Accounts are fetched concurrently.
+    /// Accounts failed with transient errors are fetched again one by one
+    /// with the retry policy.
+    fn basic_batch(
+        &self,
+        pos: TxPosition,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, SoflError> {
+        let state_ref = JsonrRpcBcStateRef {
+            provider: self.clone(),
+            pos,
+        };
+        let bn = (state_ref.bn()? - 1).into();
+        let p = &self.p;
+        let tasks = addresses.iter().map(|address| {
+            // balance, nonce, and code
+            self.request_count.fetch_add(3, Ordering::Relaxed);
+            async move {
+                futures::try_join!(
+                    p.get_balance(*address, Some(bn)),
+                    p.get_transaction_count(*address, Some(bn)),
+                    p.get_code_at(*address, bn),
+                )
+            }
+        });
+        let results = self.rt.block_on(join_all(tasks));
+        let mut infos = Vec::with_capacity(addresses.len());
+        for (address, result) in addresses.iter().zip(results) {
+            let info = match result {
+                Ok((balance, nonce, code)) => {
+                    Some(account_info(balance, nonce.cvt(), code.cvt()))
+                }
+                Err(e) if e.is_transient() => state_ref.basic_ref(*address)?,
+                Err(e) => {
+                    return Err(SoflError::Provider(format!(
+                        "failed to get account: {}",
+                        e
+                    )))
+                }
+            };
+            infos.push(info);
+        }
+        Ok(infos)
+    }
 }
 
 impl JsonrRpcBcStateRef {
@@ -61,57 +107,31 @@ impl DatabaseRef for JsonrRpcBcStateRef {
         &self,
         address: Address,
     ) -> Result<Option<AccountInfo>, Self::Error> {
-        let task = async {
-            let bn = (self.bn()? - 1).into();
-            let balance = self
-                .provider
-                .p
-                .get_balance(address, Some(bn))
-                .await
-                .map_err(|e| {
-                    SoflError::Provider(format!("failed to get balance: {}", e))
-                })?;
-            let nonce = self
-                .provider
-                .p
-                .get_transaction_count(address, Some(bn))
-                .await
-                .map_err(|e| {
-                    SoflError::Provider(format!(
-                        "failed to get transaction count: {}",
-                        e
-                    ))
-                })?;
-            let code: Bytecode = self
-                .provider
-                .p
-                .get_code_at(address, bn)
-                .await
-                .map_err(|e| {
-                    SoflError::Provider(format!(
-                        "failed to get code hash: {}",
-                        e
-                    ))
-                })?
-                .cvt();
-            let code_hash = if code.is_empty() {
-                KECCAK_EMPTY
-            } else {
-                keccak256(code.bytes())
-            };
-            get_code_hash_map()
-                .lock()
-                .unwrap()
-                .entry(code_hash)
-                .or_insert(code.clone());
-            Ok(Some(AccountInfo {
-                balance,
-                nonce: nonce.cvt(),
-                code_hash,
-                code: Some(code),
-            }))
-        };
-        self.provider.rt.block_on(task)
+        let bn = (self.bn()? - 1).into();
+        let p = &self.provider.p;
+        let balance = self
+            .provider
+            .request(|| p.get_balance(address, Some(bn)))
+            .map_err(|e| {
+                SoflError::Provider(format!("failed to get balance: {}", e))
+            })?;
+        let nonce = self
+            .provider
+            .request(|| p.get_transaction_count(address, Some(bn)))
+            .map_err(|e| {
+                SoflError::Provider(format!(
+                    "failed to get transaction count: {}",
+                    e
+                ))
+            })?;
+        let code: Bytecode = self
+            .provider
+            .request(|| p.get_code_at(address, bn))
+            .map_err(|e| {
+                SoflError::Provider(format!("failed to get code hash: {}", e))
+            })?
+            .cvt();
+        Ok(Some(account_info(balance, nonce.cvt(), code)))
     }
 
     #[doc = " Get account code by its hash."]
@@ -133,47 +153,110 @@ impl DatabaseRef for JsonrRpcBcStateRef {
         address: Address,
         index: U256,
     ) -> Result<U256, Self::Error> {
-        let task = async {
-            let bn = (self.bn()? - 1).into();
-            let value = self
-                .provider
-                .p
-                .get_storage_at(address, index.cvt(), Some(bn))
-                .await
-                .map_err(|e| {
-                    SoflError::Provider(format!("failed to get storage: {}", e))
-                })?;
-            Ok(value)
-        };
-        self.provider.rt.block_on(task)
+        let bn = (self.bn()? - 1).into();
+        let p = &self.provider.p;
+        self.provider
+            .request(|| p.get_storage_at(address, index.cvt(), Some(bn)))
+            .map_err(|e| {
+                SoflError::Provider(format!("failed to get storage: {}", e))
+            })
     }
 
     #[doc = " Get block hash by block number."]
     fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
-        let task = async {
-            let blk = self
-                .provider
-                .p
-                .get_block_by_number(
+        let p = &self.provider.p;
+        let blk = self
+            .provider
+            .request(|| {
+                p.get_block_by_number(
                     BlockNumberOrTag::Number(number.cvt()),
                     false,
                 )
-                .await
-                .map_err(|e| {
-                    SoflError::Provider(format!(
-                        "failed to get block hash: {}",
+            })
+            .map_err(|e| {
+                SoflError::Provider(format!("failed to get block hash: {}", e))
+            })?
+            .ok_or(SoflError::NotFound(format!("block number {}", number)))?;
+        blk.header
+            .hash
+            .ok_or(SoflError::NotFound(format!("block number {}", number)))
+    }
+}
+
+/// Assemble the account info and cache the code by its hash.
+fn account_info(balance: U256, nonce: u64, code: Bytecode) -> AccountInfo {
+    let code_hash = if code.is_empty() {
+        KECCAK_EMPTY
+    } else {
+        keccak256(code.bytes())
+    };
+    get_code_hash_map()
+        .lock()
+        .unwrap()
+        .entry(code_hash)
+        .or_insert(code.clone());
+    AccountInfo {
+        balance,
+        nonce,
+        code_hash,
+        code: Some(code),
+    }
+}
+
+/// Warm up the storage cache of a forked state.
+pub trait Prefetch {
+    /// Fetch the storage slots concurrently into the cache,
+    /// so that subsequent reads of them do not go to the network.
+    /// This is useful when the access set is known upfront, e.g., from a prior trace.
+    ///
+    /// Slots already cached are skipped.
+    /// Slots failed with transient errors are left to be fetched on read.
+    fn prefetch(&mut self, slots: &[(Address, U256)]) -> Result<(), SoflError>;
+}
+
+impl Prefetch for MemoryBcState<JsonrRpcBcStateRef> {
+    fn prefetch(&mut self, slots: &[(Address, U256)]) -> Result<(), SoflError> {
+        let slots: Vec<(Address, U256)> = slots
+            .iter()
+            .filter(|(address, slot)| match self.accounts.get(address) {
+                Some(account) => {
+                    !matches!(
+                        account.account_state,
+                        AccountState::NotExisting
+                            | AccountState::StorageCleared
+                    ) && !account.storage.contains_key(slot)
+                }
+                None => true,
+            })
+            .copied()
+            .collect();
+        if slots.is_empty() {
+            return Ok(());
+        }
+
+        let state_ref = self.db.clone();
+        let provider = &state_ref.provider;
+        let bn = (state_ref.bn()? - 1).into();
+        let tasks = slots.iter().map(|(address, slot)| {
+            provider.request_count.fetch_add(1, Ordering::Relaxed);
+            provider.p.get_storage_at(*address, slot.cvt(), Some(bn))
+        });
+        let values = provider.rt.block_on(join_all(tasks));
+        for ((address, slot), value) in slots.into_iter().zip(values) {
+            match value {
+                Ok(value) => {
+                    self.insert_account_storage(address, slot, value)?
+                }
+                Err(e) if e.is_transient() => continue,
+                Err(e) => {
+                    return Err(SoflError::Provider(format!(
+                        "failed to get storage: {}",
                         e
-                    ))
-                })?
-                .ok_or(SoflError::NotFound(format!(
-                    "block number {}",
-                    number
-                )))?;
-            blk.header
-                .hash
-                .ok_or(SoflError::NotFound(format!("block number {}", number)))
-        };
-        self.provider.rt.block_on(task)
+                    )))
+                }
+            }
+        }
+        Ok(())
     }
 }
 
@@ -191,3 +274,69 @@ fn get_code_hash_map() -> Arc<Mutex<HashMap<Hash, Bytecode>>> {
     }
     maybe_map.as_ref().unwrap().clone()
 }
+
+/// Only slots cached locally are dumped,
+/// since the storage of the remote node cannot be enumerated.
+impl DumpStorage for MemoryBcState<JsonrRpcBcStateRef> {
+    fn dump_storage(&self, address: Address) -> Result<StorageDump, SoflError> {
+        Ok(self.dump_cached_storage(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        blockchain::{provider::BcStateProvider, tx_position::TxPosition},
+        conversion::ConvertTo,
+        engine::types::{Address, Database, DatabaseRef, U256},
+    };
+    use libsofl_utils::config::Config;
+
+    use crate::config::JsonRpcConfig;
+
+    use super::Prefetch;
+
+    #[test]
+    fn test_prefetch_storage() {
+        let bp = JsonRpcConfig::must_load().bc_provider().unwrap();
+        let mut state = bp.bc_state_at(TxPosition::new(17000000, 0)).unwrap();
+        // USDC
+        let token: Address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".cvt();
+        let slots: Vec<(Address, U256)> =
+            (0..8u64).map(|i| (token, U256::from(i))).collect();
+        state.prefetch(&slots).unwrap();
+
+        // the account is also loaded into the cache by prefetching
+        let count = bp.request_count();
+        for (address, slot) in &slots {
+            state.storage(*address, *slot).unwrap();
+        }
+        assert_eq!(bp.request_count(), count);
+
+        // cached slots are not fetched again
+        state.prefetch(&slots).unwrap();
+        assert_eq!(bp.request_count(), count);
+    }
+
+    #[test]
+    fn test_basic_batch() {
+        let bp = JsonRpcConfig::must_load().bc_provider().unwrap();
+        let pos = TxPosition::new(17000000, 0);
+        let state = bp.bc_state_at(pos).unwrap();
+        let addresses: Vec<Address> = vec![
+            // USDC
+            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".cvt(),
+            // WETH
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".cvt(),
+            // an account without code
+            "0x00000000219ab540356cBB839Cbe05303d7705Fa".cvt(),
+            Address::ZERO,
+        ];
+        let batch = bp.basic_batch(pos, &addresses).unwrap();
+        let sequential: Vec<_> = addresses
+            .iter()
+            .map(|address| state.db.basic_ref(*address).unwrap())
+            .collect();
+        assert_eq!(batch, sequential);
+    }
+}
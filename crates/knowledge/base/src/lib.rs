@@ -1,4 +1,5 @@
 pub mod config;
 pub mod entities;
+pub mod metrics;
 pub mod rpc;
 pub mod service;
@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+pub use prometheus::{IntCounter, IntGauge};
+
+/// KnowledgeMetrics is the metrics registry of the knowledge server,
+/// exported in Prometheus text format.
+/// Cloning it is cheap, and all clones share the same registry.
+#[derive(Clone)]
+pub struct KnowledgeMetrics {
+    registry: Registry,
+    rpc_requests: IntCounterVec,
+    rpc_latency: HistogramVec,
+    blocks_indexed: IntCounterVec,
+    last_indexed_block: IntGaugeVec,
+}
+
+impl KnowledgeMetrics {
+    pub fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("knowledge".into()), None)?;
+        let rpc_requests = IntCounterVec::new(
+            Opts::new("rpc_requests_total", "Number of RPC requests"),
+            &["method", "outcome"],
+        )?;
+        let rpc_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "rpc_request_duration_seconds",
+                "Latency of RPC requests",
+            ),
+            &["method"],
+        )?;
+        let blocks_indexed = IntCounterVec::new(
+            Opts::new("blocks_indexed_total", "Number of blocks indexed"),
+            &["service"],
+        )?;
+        let last_indexed_block = IntGaugeVec::new(
+            Opts::new("last_indexed_block", "The last block indexed"),
+            &["service"],
+        )?;
+        registry.register(Box::new(rpc_requests.clone()))?;
+        registry.register(Box::new(rpc_latency.clone()))?;
+        registry.register(Box::new(blocks_indexed.clone()))?;
+        registry.register(Box::new(last_indexed_block.clone()))?;
+        Ok(Self {
+            registry,
+            rpc_requests,
+            rpc_latency,
+            blocks_indexed,
+            last_indexed_block,
+        })
+    }
+
+    /// The handle given to the service to report its own metrics.
+    pub fn service(&self, name: &str) -> ServiceMetrics {
+        ServiceMetrics {
+            name: name.to_string(),
+            metrics: self.clone(),
+        }
+    }
+
+    pub fn observe_rpc(&self, method: &str, success: bool, elapsed: Duration) {
+        let outcome = if success { "success" } else { "error" };
+        self.rpc_requests
+            .with_label_values(&[method, outcome])
+            .inc();
+        self.rpc_latency
+            .with_label_values(&[method])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Encode all metrics in Prometheus text format.
+    pub fn encode(&self) -> prometheus::Result<String> {
+        let mut buf = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buf)?;
+        Ok(String::from_utf8(buf).expect("metrics are not utf-8"))
+    }
+}
+
+/// ServiceMetrics reports the metrics of a single service,
+/// labeled with the service name.
+#[derive(Clone)]
+pub struct ServiceMetrics {
+    name: String,
+    metrics: KnowledgeMetrics,
+}
+
+impl ServiceMetrics {
+    pub fn inc_blocks_indexed(&self) {
+        self.metrics
+            .blocks_indexed
+            .with_label_values(&[&self.name])
+            .inc();
+    }
+
+    pub fn set_last_indexed_block(&self, block: u64) {
+        self.metrics
+            .last_indexed_block
+            .with_label_values(&[&self.name])
+            .set(block as i64);
+    }
+
+    /// Register a custom counter of the service.
+    /// The name must be unique among the metrics of the server.
+    pub fn register_counter(
+        &self,
+        name: &str,
+        help: &str,
+    ) -> prometheus::Result<IntCounter> {
+        let counter = IntCounter::with_opts(self.opts(name, help))?;
+        self.metrics.registry.register(Box::new(counter.clone()))?;
+        Ok(counter)
+    }
+
+    /// Register a custom gauge of the service.
+    /// The name must be unique among the metrics of the server.
+    pub fn register_gauge(
+        &self,
+        name: &str,
+        help: &str,
+    ) -> prometheus::Result<IntGauge> {
+        let gauge = IntGauge::with_opts(self.opts(name, help))?;
+        self.metrics.registry.register(Box::new(gauge.clone()))?;
+        Ok(gauge)
+    }
+
+    fn opts(&self, name: &str, help: &str) -> Opts {
+        Opts::new(name, help).const_label("service", &self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::KnowledgeMetrics;
+
+    #[test]
+    fn test_encode_metrics() {
+        let metrics = KnowledgeMetrics::new().unwrap();
+        let code = metrics.service("code");
+        code.inc_blocks_indexed();
+        code.set_last_indexed_block(17000000);
+        let help = "Number of contracts fetched";
+        let fetched = code.register_counter("contracts_fetched", help).unwrap();
+        fetched.inc_by(3);
+        assert!(code.register_counter("contracts_fetched", help).is_err());
+        metrics.observe_rpc("code_getAbi", true, Duration::from_millis(5));
+
+        let text = metrics.encode().unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        for expected in [
+            r#"knowledge_blocks_indexed_total{service="code"} 1"#,
+            r#"knowledge_last_indexed_block{service="code"} 17000000"#,
+            r#"knowledge_contracts_fetched{service="code"} 3"#,
+            r#"knowledge_rpc_requests_total{method="code_getAbi",outcome="success"} 1"#,
+        ] {
+            assert!(lines.contains(&expected), "missing {}", expected);
+        }
+    }
+}
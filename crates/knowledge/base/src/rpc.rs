@@ -1,8 +1,12 @@
-use std::sync::Arc;
+use std::{future::Future, sync::Arc, time::Duration};
 
 use jsonrpsee::{core::async_trait, proc_macros::rpc};
+use libsofl_reth::blockchain::provider::{BlockNumReader, RethProvider};
 use sea_orm::{DatabaseConnection, EntityTrait};
 
+/// The time limit of each dependency check of the readiness probe.
+pub const READY_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
 #[derive(Debug)]
 pub enum Error {
     NotFound(String),
@@ -26,14 +30,49 @@ impl From<Error> for jsonrpsee::types::ErrorObject<'static> {
     }
 }
 
+/// The head block of the chain, queried by the readiness probe.
+pub trait ChainHead: Send + Sync {
+    fn head_block(&self) -> eyre::Result<u64>;
+}
+
+impl ChainHead for RethProvider {
+    fn head_block(&self) -> eyre::Result<u64> {
+        Ok(self.best_block_number()?)
+    }
+}
+
+/// Whether the dependencies of the knowledge base are healthy.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReadyStatus {
+    pub ready: bool,
+    pub provider: bool,
+    pub database: bool,
+    /// The head block of the provider, if it is up.
+    pub head_block: Option<u64>,
+}
+
 #[rpc(client, server, namespace = "kb")]
 pub trait BaseRpc {
     #[method(name = "metadata")]
     async fn metadata(&self, key: String) -> Result<String, Error>;
+
+    /// Check whether the provider and the database are reachable,
+    /// each within `READY_CHECK_TIMEOUT`.
+    #[method(name = "ready")]
+    async fn ready(&self) -> Result<ReadyStatus, Error>;
 }
 
 pub struct BaseRpcImpl {
     pub db: Arc<DatabaseConnection>,
+    pub head: Arc<dyn ChainHead>,
+}
+
+/// Run the check, taking a timeout as a failure.
+async fn check<T, E>(
+    f: impl Future<Output = Result<T, E>>,
+    timeout: Duration,
+) -> Option<T> {
+    tokio::time::timeout(timeout, f).await.ok()?.ok()
 }
 
 #[async_trait]
@@ -49,4 +88,65 @@ impl BaseRpcServer for BaseRpcImpl {
             })
             .map(|metadata| metadata.value)
     }
+
+    async fn ready(&self) -> Result<ReadyStatus, Error> {
+        // the provider reads the local database synchronously
+        let head = self.head.clone();
+        let head_block = check(
+            async move {
+                tokio::task::spawn_blocking(move || head.head_block())
+                    .await
+                    .map_err(eyre::Report::from)?
+            },
+            READY_CHECK_TIMEOUT,
+        )
+        .await;
+        let database =
+            check(self.db.ping(), READY_CHECK_TIMEOUT).await.is_some();
+        let provider = head_block.is_some();
+        Ok(ReadyStatus {
+            ready: provider && database,
+            provider,
+            database,
+            head_block,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_nodep {
+    use std::sync::Arc;
+
+    use sea_orm::Database;
+
+    use super::{BaseRpcImpl, BaseRpcServer, ChainHead, ReadyStatus};
+
+    struct FixedHead(u64);
+
+    impl ChainHead for FixedHead {
+        fn head_block(&self) -> eyre::Result<u64> {
+            Ok(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_not_ready_when_db_disconnected() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let rpc = BaseRpcImpl {
+            db: Arc::new(db.clone()),
+            head: Arc::new(FixedHead(17000000)),
+        };
+        let mut expected = ReadyStatus {
+            ready: true,
+            provider: true,
+            database: true,
+            head_block: Some(17000000),
+        };
+        assert_eq!(rpc.ready().await.unwrap(), expected);
+
+        db.close().await.unwrap();
+        expected.ready = false;
+        expected.database = false;
+        assert_eq!(rpc.ready().await.unwrap(), expected);
+    }
 }
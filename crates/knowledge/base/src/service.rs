@@ -2,16 +2,22 @@ use std::sync::Arc;
 
 use eyre::Result;
 use jsonrpsee::{core::async_trait, Methods};
-use libsofl_reth::blockchain::transaction::RethTx;
+use libsofl_reth::blockchain::{provider::RethProvider, transaction::RethTx};
 use sea_orm::DatabaseConnection;
 
-use crate::rpc::{BaseRpcImpl, BaseRpcServer};
+use crate::{
+    metrics::ServiceMetrics,
+    rpc::{BaseRpcImpl, BaseRpcServer},
+};
 
 #[async_trait]
 pub trait KnowledgeService: Send + Sync {
     fn name(&self) -> &str;
 
-    async fn start(&mut self) -> Result<()>;
+    /// Start the service.
+    /// The service may report its metrics, e.g., the last indexed block,
+    /// or register custom ones via `metrics`.
+    async fn start(&mut self, metrics: ServiceMetrics) -> Result<()>;
     async fn stop(&mut self) -> Result<()>;
 
     /// Get the RPC methods for this service.
@@ -27,6 +33,7 @@ pub trait KnowledgeService: Send + Sync {
 
 pub struct BaseService {
     pub db: Arc<DatabaseConnection>,
+    pub provider: Arc<RethProvider>,
 }
 
 #[async_trait]
@@ -35,7 +42,7 @@ impl KnowledgeService for BaseService {
         "base"
     }
 
-    async fn start(&mut self) -> Result<()> {
+    async fn start(&mut self, _metrics: ServiceMetrics) -> Result<()> {
         Ok(())
     }
 
@@ -46,6 +53,7 @@ impl KnowledgeService for BaseService {
     fn rpc_methods(&self) -> Methods {
         let rpc = BaseRpcImpl {
             db: self.db.clone(),
+            head: self.provider.clone(),
         };
         rpc.into_rpc().into()
     }
@@ -0,0 +1,222 @@
+use alloy_json_abi::{Event, Function, JsonAbi, StateMutability};
+use libsofl_core::engine::types::{FixedBytes, B256};
+
+/// A function in an ABI. Only external and public functions are in an ABI,
+/// so the visibility of a function is reflected by its presence.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AbiFunction {
+    pub selector: FixedBytes<4>,
+    /// The signature, e.g., `transfer(address,uint256)`.
+    pub signature: String,
+    pub outputs: Vec<String>,
+    pub state_mutability: StateMutability,
+}
+
+impl From<&Function> for AbiFunction {
+    fn from(f: &Function) -> Self {
+        Self {
+            selector: f.selector(),
+            signature: f.signature(),
+            outputs: f
+                .outputs
+                .iter()
+                .map(|p| p.selector_type().into_owned())
+                .collect(),
+            state_mutability: f.state_mutability,
+        }
+    }
+}
+
+impl AbiFunction {
+    fn name(&self) -> &str {
+        self.signature.split('(').next().unwrap_or_default()
+    }
+}
+
+/// An event in an ABI.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AbiEvent {
+    pub selector: B256,
+    /// The signature, e.g., `Transfer(address,address,uint256)`.
+    pub signature: String,
+    /// Whether each parameter is indexed.
+    pub indexed: Vec<bool>,
+    pub anonymous: bool,
+}
+
+impl From<&Event> for AbiEvent {
+    fn from(e: &Event) -> Self {
+        Self {
+            selector: e.selector(),
+            signature: e.signature(),
+            indexed: e.inputs.iter().map(|p| p.indexed).collect(),
+            anonymous: e.anonymous,
+        }
+    }
+}
+
+impl AbiEvent {
+    fn name(&self) -> &str {
+        self.signature.split('(').next().unwrap_or_default()
+    }
+}
+
+/// The difference between the ABIs of two versions of a contract.
+/// Items are matched by their selectors first, so that a selector collision
+/// is a change rather than a removal and an addition.
+/// The remaining items are then matched by their names,
+/// e.g., a function whose parameter types change.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+)]
+pub struct AbiDiff {
+    pub added_functions: Vec<AbiFunction>,
+    pub removed_functions: Vec<AbiFunction>,
+    /// Functions whose signature, outputs, or mutability changes,
+    /// as `(old, new)`.
+    pub changed_functions: Vec<(AbiFunction, AbiFunction)>,
+    pub added_events: Vec<AbiEvent>,
+    pub removed_events: Vec<AbiEvent>,
+    /// Events whose signature, indexed parameters, or anonymity changes,
+    /// as `(old, new)`.
+    pub changed_events: Vec<(AbiEvent, AbiEvent)>,
+}
+
+impl AbiDiff {
+    /// Whether callers of the old ABI keep working with the new one,
+    /// i.e., functions and events are only added.
+    pub fn is_backward_compatible(&self) -> bool {
+        self.removed_functions.is_empty()
+            && self.changed_functions.is_empty()
+            && self.removed_events.is_empty()
+            && self.changed_events.is_empty()
+    }
+}
+
+/// Compare the ABIs of an old and a new version of a contract.
+pub fn diff_abis(old: &JsonAbi, new: &JsonAbi) -> AbiDiff {
+    let old_fns: Vec<AbiFunction> = old.functions().map(Into::into).collect();
+    let new_fns: Vec<AbiFunction> = new.functions().map(Into::into).collect();
+    let (added_functions, removed_functions, changed_functions) = diff_items(
+        &old_fns,
+        &new_fns,
+        |f| f.selector.to_string(),
+        |f| f.name().to_string(),
+    );
+
+    let old_events: Vec<AbiEvent> = old.events().map(Into::into).collect();
+    let new_events: Vec<AbiEvent> = new.events().map(Into::into).collect();
+    let (added_events, removed_events, changed_events) = diff_items(
+        &old_events,
+        &new_events,
+        |e| e.selector.to_string(),
+        |e| e.name().to_string(),
+    );
+
+    AbiDiff {
+        added_functions,
+        removed_functions,
+        changed_functions,
+        added_events,
+        removed_events,
+        changed_events,
+    }
+}
+
+/// The added, removed, and changed (as `(old, new)`) items.
+type ItemDiff<T> = (Vec<T>, Vec<T>, Vec<(T, T)>);
+
+/// Match the items by the selector first and then by the name.
+fn diff_items<T: Clone + PartialEq>(
+    old: &[T],
+    new: &[T],
+    selector: impl Fn(&T) -> String,
+    name: impl Fn(&T) -> String,
+) -> ItemDiff<T> {
+    let mut new_matched = vec![false; new.len()];
+    let mut remaining: Vec<&T> = old.iter().collect();
+    let mut changed = Vec::new();
+    for key in [&selector as &dyn Fn(&T) -> String, &name] {
+        remaining.retain(|o| {
+            let j = (0..new.len())
+                .find(|&j| !new_matched[j] && key(&new[j]) == key(o));
+            let Some(j) = j else {
+                return true;
+            };
+            new_matched[j] = true;
+            if **o != new[j] {
+                changed.push(((*o).clone(), new[j].clone()));
+            }
+            false
+        });
+    }
+    let removed = remaining.into_iter().cloned().collect();
+    let added = new
+        .iter()
+        .zip(new_matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(n, _)| n.clone())
+        .collect();
+    (added, removed, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_json_abi::{JsonAbi, StateMutability};
+
+    use super::diff_abis;
+
+    fn abi(items: &[&str]) -> JsonAbi {
+        JsonAbi::parse(items.iter().copied()).unwrap()
+    }
+
+    #[test]
+    fn test_parameter_type_change() {
+        let old = abi(&[
+            "function transfer(address to, uint256 value) returns (bool)",
+            "function burn(uint256 value)",
+            "event Transfer(address indexed, address indexed, uint256)",
+        ]);
+        let new = abi(&[
+            "function transfer(address to, uint128 value) returns (bool)",
+            "function mint(uint256 value)",
+            "event Transfer(address indexed, address indexed, uint256)",
+        ]);
+        let diff = diff_abis(&old, &new);
+        assert_eq!(diff.changed_functions.len(), 1);
+        let (old_fn, new_fn) = &diff.changed_functions[0];
+        assert_eq!(old_fn.signature, "transfer(address,uint256)");
+        assert_eq!(new_fn.signature, "transfer(address,uint128)");
+        assert_eq!(diff.removed_functions[0].signature, "burn(uint256)");
+        assert_eq!(diff.added_functions[0].signature, "mint(uint256)");
+        assert!(diff.changed_events.is_empty());
+        assert!(!diff.is_backward_compatible());
+    }
+
+    #[test]
+    fn test_mutability_and_indexed_change() {
+        let old = abi(&[
+            "function total() returns (uint256)",
+            "event Paused(address)",
+        ]);
+        let new = abi(&[
+            "function total() view returns (uint256)",
+            "event Paused(address indexed)",
+            "event Unpaused(address indexed)",
+        ]);
+        let diff = diff_abis(&old, &new);
+        let (old_fn, new_fn) = &diff.changed_functions[0];
+        assert_eq!(old_fn.state_mutability, StateMutability::NonPayable);
+        assert_eq!(new_fn.state_mutability, StateMutability::View);
+        let (old_event, new_event) = &diff.changed_events[0];
+        assert_eq!(old_event.indexed, vec![false]);
+        assert_eq!(new_event.indexed, vec![true]);
+        assert_eq!(diff.added_events[0].signature, "Unpaused(address)");
+        assert!(diff.removed_functions.is_empty());
+        assert!(diff.added_functions.is_empty());
+
+        let diff = diff_abis(&new, &new);
+        assert_eq!(diff, Default::default());
+        assert!(diff.is_backward_compatible());
+    }
+}
@@ -1,4 +1,7 @@
-use std::sync::{atomic::AtomicU64, Arc};
+use std::{
+    future::Future,
+    sync::{atomic::AtomicU64, Arc},
+};
 
 use crossbeam::atomic::AtomicConsume;
 use foundry_block_explorers::errors::EtherscanError;
@@ -12,8 +15,11 @@ use libsofl_core::{
         state::BcState, transition::TransitionSpecBuilder, types::BcStateRef,
     },
 };
+use libsofl_knowledge_base::metrics::ServiceMetrics;
 use libsofl_utils::log::{error, info};
 use sea_orm::{sea_query::OnConflict, DatabaseConnection, EntityTrait};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     collect::{
@@ -34,6 +40,10 @@ where
     query: Arc<CodeQuery>,
     db: Arc<DatabaseConnection>,
     current_bn: Arc<AtomicU64>,
+    // bounds the number of contracts being fetched at the same time
+    in_flight: Option<Arc<Semaphore>>,
+    cancellation_token: Option<CancellationToken>,
+    metrics: Option<ServiceMetrics>,
 
     _phantom: std::marker::PhantomData<(T, D)>,
 }
@@ -63,23 +73,59 @@ where
             query: query,
             db,
             current_bn,
+            in_flight: None,
+            cancellation_token: None,
+            metrics: None,
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Limit the number of contracts being fetched at the same time,
+    /// in addition to the requests per second limit of the query.
+    pub fn with_max_in_flight(mut self, max_in_flight: Option<usize>) -> Self {
+        self.in_flight = max_in_flight.map(|n| Arc::new(Semaphore::new(n)));
+        self
+    }
+
+    /// Stop taking new blocks once the token is cancelled.
+    /// The block being processed is finished and its progress is saved,
+    /// so that collecting resumes from the next block.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Report the number of processed blocks and the last processed block.
+    pub fn with_metrics(mut self, metrics: Option<ServiceMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
     pub async fn worker_loop(&self, until: u64) {
-        while self.current_bn.load_consume() <= until {
-            let bn = self
-                .current_bn
-                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-            match self.process_one_block(bn).await {
-                Ok(_) => {}
-                Err(err) => {
-                    error!(block = bn, err = ?err, "failed to process block");
+        let token = self.cancellation_token.clone().unwrap_or_default();
+        let finished = drive_blocks(
+            &self.current_bn,
+            until,
+            &token,
+            |bn| async move {
+                let r = self.process_one_block(bn).await;
+                if let Some(metrics) = &self.metrics {
+                    metrics.inc_blocks_indexed();
+                    metrics.set_last_indexed_block(bn);
                 }
-            };
+                r
+            },
+            |progress| self.save_progress(progress),
+        )
+        .await;
+        if finished {
+            info!(block = until, "finished collecting code knowledge")
+        } else {
+            info!(
+                progress = self.current_bn.load_consume(),
+                "stopped collecting code knowledge"
+            )
         }
-        info!(block = until, "finished collecting code knowledge")
     }
 
     pub async fn process_one_block(&self, bn: u64) -> Result<(), Error> {
@@ -87,11 +133,11 @@ where
         info!(block = bn, txs = txs.len(), "collecting code knowledge");
 
         let mut spec_builder =
-            TransitionSpecBuilder::default().at_block(&self.provider, bn.cvt());
+            TransitionSpecBuilder::default().at_block(&self.provider, bn);
         for tx in txs {
             spec_builder = spec_builder.append_tx(tx);
         }
-        let spec = spec_builder.build();
+        let spec = spec_builder.build().map_err(Error::Sofl)?;
 
         let mut insp = contract_inspector::ContractInspector {
             contracts: Default::default(),
@@ -105,19 +151,16 @@ where
             "found contracts"
         );
 
-        let tasks = insp
-            .contracts
-            .into_iter()
-            .map(|c| {
+        let results =
+            run_limited(insp.contracts, self.in_flight.clone(), |c| {
                 let query = self.query.clone();
-                tokio::spawn(async move { (c, query.get_model_async(c).await) })
+                async move { query.get_model_async(c).await }
             })
-            .collect::<Vec<_>>();
+            .await;
         let mut verified_contracts = 0;
         let mut unverified_contracts = 0;
         let mut failed_contracts = 0;
-        for task in tasks {
-            let (addr, result) = task.await.unwrap();
+        for (addr, result) in results {
             match result {
                 Ok(c) => {
                     if c.is_some() {
@@ -145,12 +188,11 @@ where
             "processed block"
         );
 
-        // save progress
-        let metadata = CodeKnowledgeMetadata {
-            progress: self
-                .current_bn
-                .load(std::sync::atomic::Ordering::Acquire),
-        };
+        Ok(())
+    }
+
+    pub async fn save_progress(&self, progress: u64) -> Result<(), Error> {
+        let metadata = CodeKnowledgeMetadata { progress };
         let model =
             libsofl_knowledge_base::entities::metadata::ActiveModel::from((
                 CODE_KNOWLEDGE_METADATA_KEY.to_string(),
@@ -172,3 +214,153 @@ where
         Ok(())
     }
 }
+
+/// Process blocks one by one from `current_bn` until `until` (inclusive),
+/// saving the progress after each block.
+/// Once the token is cancelled, no new block is taken,
+/// while the block being processed is finished.
+/// Returns whether all blocks until `until` are processed.
+pub(crate) async fn drive_blocks<F, Fut, S, SFut>(
+    current_bn: &AtomicU64,
+    until: u64,
+    token: &CancellationToken,
+    process: F,
+    save_progress: S,
+) -> bool
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<(), Error>>,
+    S: Fn(u64) -> SFut,
+    SFut: Future<Output = Result<(), Error>>,
+{
+    while current_bn.load_consume() <= until {
+        if token.is_cancelled() {
+            return false;
+        }
+        let bn = current_bn.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if let Err(err) = process(bn).await {
+            error!(block = bn, err = ?err, "failed to process block");
+        }
+        let progress = current_bn.load(std::sync::atomic::Ordering::Acquire);
+        if let Err(err) = save_progress(progress).await {
+            error!(progress = progress, err = ?err, "failed to save progress");
+        }
+    }
+    true
+}
+
+/// Run `f` on each item in a separate task,
+/// where at most as many tasks as the permits of `limit` run at the same time.
+/// The results are in the order of the items.
+pub(crate) async fn run_limited<I, F, Fut>(
+    items: I,
+    limit: Option<Arc<Semaphore>>,
+    f: F,
+) -> Vec<(I::Item, Fut::Output)>
+where
+    I: IntoIterator,
+    I::Item: Clone + Send + 'static,
+    F: Fn(I::Item) -> Fut,
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    let tasks = items
+        .into_iter()
+        .map(|item| {
+            let fut = f(item.clone());
+            let limit = limit.clone();
+            tokio::spawn(async move {
+                let _permit = match limit {
+                    Some(limit) => Some(
+                        limit.acquire_owned().await.expect("semaphore closed"),
+                    ),
+                    None => None,
+                };
+                (item, fut.await)
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut results = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        results.push(task.await.expect("task panicked"));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicU64, AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use tokio::sync::Semaphore;
+    use tokio_util::sync::CancellationToken;
+
+    use super::{drive_blocks, run_limited};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_max_in_flight() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        // a mock query that takes a while to respond
+        let query = |c: u64| {
+            let in_flight = in_flight.clone();
+            let peak = peak.clone();
+            async move {
+                let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(n, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                c * 2
+            }
+        };
+
+        let limit = Some(Arc::new(Semaphore::new(3)));
+        let results = run_limited(0..20u64, limit, query).await;
+        assert_eq!(results.len(), 20);
+        assert!(results.iter().all(|(c, r)| *r == c * 2));
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+
+        // unlimited
+        peak.store(0, Ordering::SeqCst);
+        run_limited(0..20u64, None, query).await;
+        assert!(peak.load(Ordering::SeqCst) > 3);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_mid_batch() {
+        let current_bn = AtomicU64::new(100);
+        let token = CancellationToken::new();
+        let processed = Mutex::new(Vec::new());
+        let saved = Mutex::new(Vec::new());
+        let process = |bn: u64| {
+            let (token, processed) = (&token, &processed);
+            async move {
+                // the shutdown is initiated while processing block 102
+                if bn == 102 {
+                    token.cancel();
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                processed.lock().unwrap().push(bn);
+                Ok(())
+            }
+        };
+        let save_progress = |progress: u64| {
+            saved.lock().unwrap().push(progress);
+            async { Ok(()) }
+        };
+
+        let finished =
+            drive_blocks(&current_bn, 200, &token, process, save_progress)
+                .await;
+        assert!(!finished);
+        // the block in process is finished, and no new block is taken
+        assert_eq!(*processed.lock().unwrap(), vec![100, 101, 102]);
+        assert_eq!(*saved.lock().unwrap(), vec![101, 102, 103]);
+        assert_eq!(current_bn.load(Ordering::SeqCst), 103);
+    }
+}
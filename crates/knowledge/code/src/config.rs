@@ -5,6 +5,10 @@ pub struct CodeKnowledgeConfig {
     pub chain_id: u64,
     pub api_keys: Vec<String>,
     pub requests_per_second: Option<f32>,
+    /// The maximum number of contracts being fetched at the same time
+    /// by the collector. None means unlimited.
+    #[serde(default)]
+    pub max_in_flight_requests: Option<usize>,
     pub cache_size: u64,
     // eager mode will recheck if a previously checked contract has been verified now
     pub eager: bool,
@@ -16,6 +20,7 @@ impl Default for CodeKnowledgeConfig {
             chain_id: 1,
             api_keys: vec![],
             requests_per_second: None,
+            max_in_flight_requests: None,
             cache_size: 999,
             eager: false,
         }
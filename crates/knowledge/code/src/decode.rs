@@ -0,0 +1,148 @@
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::JsonAbi;
+use libsofl_core::engine::types::{Address, Bytes, FixedBytes};
+
+/// The calldata of a transaction decoded with the ABI of its target.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DecodedCall {
+    #[serde(rename_all = "camelCase")]
+    Call {
+        /// The contract whose ABI decodes the call,
+        /// i.e., the implementation if the target is a proxy.
+        abi_address: Address,
+        function_name: String,
+        /// The name and the formatted value of each argument.
+        /// Unnamed arguments are named by their index, e.g., `arg0`.
+        args: Vec<(String, String)>,
+    },
+    /// No ABI is available, or the calldata matches no function in it.
+    #[serde(rename_all = "camelCase")]
+    Unknown {
+        /// None if the calldata is shorter than a selector.
+        selector: Option<FixedBytes<4>>,
+        input: Bytes,
+    },
+}
+
+impl DecodedCall {
+    pub fn unknown(input: &[u8]) -> Self {
+        Self::Unknown {
+            selector: input.get(..4).map(FixedBytes::from_slice),
+            input: Bytes::copy_from_slice(input),
+        }
+    }
+}
+
+/// Decode the calldata with the function of the ABI matching its selector.
+/// Returns None if no function matches or the arguments fail to decode.
+pub fn decode_call(
+    abi_address: Address,
+    abi: &JsonAbi,
+    input: &[u8],
+) -> Option<DecodedCall> {
+    let selector = input.get(..4)?;
+    let function = abi.functions().find(|f| f.selector() == selector)?;
+    let values = function.abi_decode_input(&input[4..], true).ok()?;
+    let args = function
+        .inputs
+        .iter()
+        .zip(values.iter())
+        .enumerate()
+        .map(|(i, (param, value))| {
+            let name = if param.name.is_empty() {
+                format!("arg{}", i)
+            } else {
+                param.name.clone()
+            };
+            (name, format_value(value))
+        })
+        .collect();
+    Some(DecodedCall::Call {
+        abi_address,
+        function_name: function.name.clone(),
+        args,
+    })
+}
+
+/// Format the value in the way it is written in Solidity,
+/// e.g., `[1, 2]` for arrays and `(0x.., true)` for tuples.
+pub fn format_value(value: &DynSolValue) -> String {
+    let join = |values: &[DynSolValue]| {
+        values
+            .iter()
+            .map(format_value)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match value {
+        DynSolValue::Bool(b) => b.to_string(),
+        DynSolValue::Int(i, _) => i.to_string(),
+        DynSolValue::Uint(u, _) => u.to_string(),
+        DynSolValue::FixedBytes(word, size) => {
+            Bytes::copy_from_slice(&word[..*size]).to_string()
+        }
+        DynSolValue::Address(address) => address.to_string(),
+        DynSolValue::Function(function) => {
+            Bytes::copy_from_slice(function.as_slice()).to_string()
+        }
+        DynSolValue::Bytes(bytes) => Bytes::copy_from_slice(bytes).to_string(),
+        DynSolValue::String(s) => s.clone(),
+        DynSolValue::Array(values) | DynSolValue::FixedArray(values) => {
+            format!("[{}]", join(values))
+        }
+        DynSolValue::Tuple(values) => format!("({})", join(values)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_dyn_abi::DynSolValue;
+    use alloy_json_abi::JsonAbi;
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::types::{Address, FixedBytes, I256, U256},
+    };
+
+    use super::{decode_call, format_value, DecodedCall};
+
+    #[test]
+    fn test_decode_unknown_selector() {
+        let abi =
+            JsonAbi::parse(["function approve(address, uint256)"]).unwrap();
+        let input = [0xa9, 0x05, 0x9c, 0xbb, 0x00];
+        assert_eq!(decode_call(Address::ZERO, &abi, &input), None);
+        assert_eq!(
+            DecodedCall::unknown(&input),
+            DecodedCall::Unknown {
+                selector: Some(FixedBytes::from([0xa9, 0x05, 0x9c, 0xbb])),
+                input: input.to_vec().into(),
+            }
+        );
+        assert_eq!(decode_call(Address::ZERO, &abi, &[0x01]), None);
+    }
+
+    #[test]
+    fn test_format_value() {
+        let address: Address = 0x1234.cvt();
+        let value = DynSolValue::Tuple(vec![
+            DynSolValue::Int(I256::try_from(-1).unwrap(), 256),
+            DynSolValue::Array(vec![
+                DynSolValue::Uint(U256::from(1), 8),
+                DynSolValue::Uint(U256::from(2), 8),
+            ]),
+            DynSolValue::Address(address),
+            DynSolValue::FixedBytes(FixedBytes::with_last_byte(0xff), 32),
+            DynSolValue::Bytes(vec![0xde, 0xad]),
+            DynSolValue::String("sofl".to_string()),
+        ]);
+        assert_eq!(
+            format_value(&value),
+            format!(
+                "(-1, [1, 2], {}, 0x{}ff, 0xdead, sofl)",
+                address,
+                "00".repeat(31)
+            )
+        );
+    }
+}
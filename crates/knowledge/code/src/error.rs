@@ -13,6 +13,8 @@ pub enum Error {
     CompilationFailed(Vec<foundry_compilers::artifacts::Error>),
     Database(DbErr),
     Sofl(libsofl_core::error::SoflError),
+    /// A proxy chain that is cyclic or longer than the limit.
+    ProxyResolution(String),
 }
 
 impl From<Error> for jsonrpsee::types::ErrorObject<'static> {
@@ -41,6 +43,9 @@ impl Display for Error {
             }
             Error::Database(err) => write!(f, "Database error: {}", err),
             Error::Sofl(err) => write!(f, "Sofl error: {}", err),
+            Error::ProxyResolution(msg) => {
+                write!(f, "Proxy resolution error: {}", msg)
+            }
         }
     }
 }
@@ -0,0 +1,255 @@
+use foundry_compilers::artifacts::{Storage, StorageLayout};
+use libsofl_core::engine::types::U256;
+
+/// The location of a state variable in storage.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct VariablePosition {
+    pub slot: U256,
+    /// The byte offset in the slot, non-zero for packed variables.
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl VariablePosition {
+    /// The byte range in the whole storage.
+    fn range(&self) -> (U256, U256) {
+        let start = self.slot * U256::from(32) + U256::from(self.offset);
+        (start, start + U256::from(self.size))
+    }
+
+    fn overlaps(&self, other: &VariablePosition) -> bool {
+        let (a0, a1) = self.range();
+        let (b0, b1) = other.range();
+        a0 < b1 && b0 < a1
+    }
+}
+
+/// A state variable in a storage layout.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LayoutVariable {
+    pub contract: String,
+    pub label: String,
+    /// The type label, e.g., `uint256` or `mapping(address => uint256)`.
+    pub ty: String,
+    pub position: VariablePosition,
+}
+
+impl LayoutVariable {
+    fn from_storage(layout: &StorageLayout, storage: &Storage) -> Self {
+        let ty = layout.types.get(&storage.storage_type);
+        let size = ty
+            .and_then(|t| t.number_of_bytes.parse().ok())
+            .unwrap_or(32);
+        Self {
+            contract: storage.contract.clone(),
+            label: storage.label.clone(),
+            // type ids contain AST ids, which differ across compilations
+            ty: ty
+                .map(|t| t.label.clone())
+                .unwrap_or_else(|| storage.storage_type.clone()),
+            position: VariablePosition {
+                slot: storage
+                    .slot
+                    .parse()
+                    .expect("invalid slot in storage layout"),
+                offset: storage.offset as u64,
+                size,
+            },
+        }
+    }
+}
+
+/// A variable of the new layout occupying the storage of a different variable in the old layout,
+/// which reads or corrupts the old data after the upgrade.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LayoutCollision {
+    pub old: LayoutVariable,
+    pub new: LayoutVariable,
+}
+
+/// The difference between the storage layouts of two implementations.
+/// Variables are matched by their labels, in the order of declaration.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+)]
+pub struct LayoutDiff {
+    /// Variables whose slot or offset changes, as `(old, new)`.
+    pub moved: Vec<(LayoutVariable, LayoutVariable)>,
+    /// Variables whose type changes, as `(old, new)`.
+    pub type_changed: Vec<(LayoutVariable, LayoutVariable)>,
+    pub removed: Vec<LayoutVariable>,
+    pub added: Vec<LayoutVariable>,
+    pub collisions: Vec<LayoutCollision>,
+}
+
+impl LayoutDiff {
+    /// Whether upgrading from the old layout to the new one preserves the state,
+    /// i.e., variables are only appended.
+    pub fn is_upgrade_safe(&self) -> bool {
+        self.moved.is_empty()
+            && self.type_changed.is_empty()
+            && self.removed.is_empty()
+            && self.collisions.is_empty()
+    }
+}
+
+/// Compare the storage layouts of an old and a new implementation.
+pub fn diff_storage_layouts(
+    old: &StorageLayout,
+    new: &StorageLayout,
+) -> LayoutDiff {
+    let old_vars: Vec<LayoutVariable> = old
+        .storage
+        .iter()
+        .map(|s| LayoutVariable::from_storage(old, s))
+        .collect();
+    let new_vars: Vec<LayoutVariable> = new
+        .storage
+        .iter()
+        .map(|s| LayoutVariable::from_storage(new, s))
+        .collect();
+
+    let mut diff = LayoutDiff::default();
+    // the index of the matched new variable for each old variable
+    let mut matched: Vec<Option<usize>> = vec![None; old_vars.len()];
+    let mut new_matched = vec![false; new_vars.len()];
+    for (i, old_var) in old_vars.iter().enumerate() {
+        let j = new_vars
+            .iter()
+            .enumerate()
+            .position(|(j, v)| !new_matched[j] && v.label == old_var.label);
+        let j = match j {
+            Some(j) => j,
+            None => {
+                diff.removed.push(old_var.clone());
+                continue;
+            }
+        };
+        matched[i] = Some(j);
+        new_matched[j] = true;
+        let new_var = &new_vars[j];
+        if old_var.position.slot != new_var.position.slot
+            || old_var.position.offset != new_var.position.offset
+        {
+            diff.moved.push((old_var.clone(), new_var.clone()));
+        }
+        if old_var.ty != new_var.ty {
+            diff.type_changed.push((old_var.clone(), new_var.clone()));
+        }
+    }
+    for (j, new_var) in new_vars.iter().enumerate() {
+        if !new_matched[j] {
+            diff.added.push(new_var.clone());
+        }
+    }
+
+    for (j, new_var) in new_vars.iter().enumerate() {
+        for (i, old_var) in old_vars.iter().enumerate() {
+            let same = matched[i] == Some(j)
+                && old_var.position == new_var.position
+                && old_var.ty == new_var.ty;
+            if !same && old_var.position.overlaps(&new_var.position) {
+                diff.collisions.push(LayoutCollision {
+                    old: old_var.clone(),
+                    new: new_var.clone(),
+                });
+            }
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use foundry_compilers::artifacts::StorageLayout;
+    use libsofl_core::engine::types::U256;
+
+    use super::diff_storage_layouts;
+
+    fn layout(vars: &[(&str, &str, u64, &str)]) -> StorageLayout {
+        let storage: Vec<_> = vars
+            .iter()
+            .enumerate()
+            .map(|(i, (label, slot, offset, ty))| {
+                serde_json::json!({
+                    "astId": i,
+                    "contract": "Impl.sol:Impl",
+                    "label": label,
+                    "offset": offset,
+                    "slot": slot,
+                    "type": ty,
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "storage": storage,
+            "types": {
+                "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"},
+                "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+                "t_bool": {"encoding": "inplace", "label": "bool", "numberOfBytes": "1"},
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_inserted_variable_shifts_layout() {
+        let old = layout(&[
+            ("total", "0", 0, "t_uint256"),
+            ("owner", "1", 0, "t_address"),
+        ]);
+        let new = layout(&[
+            ("fee", "0", 0, "t_uint256"),
+            ("total", "1", 0, "t_uint256"),
+            ("owner", "2", 0, "t_address"),
+        ]);
+        let diff = diff_storage_layouts(&old, &new);
+        assert!(!diff.is_upgrade_safe());
+        let moved: Vec<_> = diff
+            .moved
+            .iter()
+            .map(|(o, n)| (o.label.as_str(), n.position.slot))
+            .collect();
+        assert_eq!(
+            moved,
+            vec![("total", U256::from(1)), ("owner", U256::from(2))]
+        );
+        assert_eq!(diff.added[0].label, "fee");
+        assert!(diff.removed.is_empty());
+        // fee reads the old total, and total reads the old owner
+        let collisions: Vec<_> = diff
+            .collisions
+            .iter()
+            .map(|c| (c.new.label.as_str(), c.old.label.as_str()))
+            .collect();
+        assert_eq!(collisions, vec![("fee", "total"), ("total", "owner")]);
+
+        // appending is safe
+        let new = layout(&[
+            ("total", "0", 0, "t_uint256"),
+            ("owner", "1", 0, "t_address"),
+            ("paused", "1", 20, "t_bool"),
+        ]);
+        let diff = diff_storage_layouts(&old, &new);
+        assert!(diff.is_upgrade_safe());
+        assert_eq!(diff.added.len(), 1);
+    }
+
+    #[test]
+    fn test_packed_offset_change() {
+        let old = layout(&[
+            ("owner", "0", 0, "t_address"),
+            ("paused", "0", 20, "t_bool"),
+        ]);
+        let new = layout(&[
+            ("owner", "0", 0, "t_address"),
+            ("locked", "0", 20, "t_bool"),
+            ("paused", "0", 21, "t_bool"),
+        ]);
+        let diff = diff_storage_layouts(&old, &new);
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].1.position.offset, 21);
+        assert_eq!(diff.collisions.len(), 1);
+        assert_eq!(diff.collisions[0].new.label, "locked");
+    }
+}
@@ -1,6 +1,12 @@
+pub mod abi;
 pub mod collect;
 pub mod config;
+pub mod decode;
 pub mod entities;
 pub mod error;
+pub mod layout;
 pub mod query;
 pub mod rpc;
+pub mod source_map;
+pub mod state_diff;
+pub mod verify;
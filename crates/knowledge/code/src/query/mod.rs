@@ -1,2 +1,3 @@
 pub mod fetcher;
+pub mod proxy;
 pub mod query;
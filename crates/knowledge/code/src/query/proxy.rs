@@ -0,0 +1,47 @@
+use libsofl_core::{
+    conversion::ConvertTo,
+    engine::types::{Address, U256},
+    error::SoflError,
+};
+
+/// The maximum number of proxies followed before giving up.
+pub const MAX_PROXY_HOPS: usize = 8;
+
+/// Storage slots holding the implementation address of proxies, in the order they are checked.
+pub const IMPLEMENTATION_SLOTS: [&str; 3] = [
+    // EIP-1967: bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc",
+    // EIP-1822 (UUPS): keccak256("PROXIABLE")
+    "0xc5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7",
+    // OpenZeppelin transparent proxy before EIP-1967: keccak256("org.zeppelinos.proxy.implementation")
+    "0x7050c9e0f4ca769c69bd3a8ef740bc37934f8e2c036e5a723fd8ee048ed3f8c3",
+];
+
+/// Read the storage of contracts on chain, used to find proxy implementations.
+pub trait StorageReader: Send + Sync {
+    fn storage(&self, address: Address, slot: U256) -> Result<U256, SoflError>;
+}
+
+impl<F> StorageReader for F
+where
+    F: Fn(Address, U256) -> Result<U256, SoflError> + Send + Sync,
+{
+    fn storage(&self, address: Address, slot: U256) -> Result<U256, SoflError> {
+        self(address, slot)
+    }
+}
+
+/// The implementation address stored in the well-known proxy slots of the contract, if any.
+pub fn implementation_in_slots(
+    reader: &dyn StorageReader,
+    address: Address,
+) -> Result<Option<Address>, SoflError> {
+    for slot in IMPLEMENTATION_SLOTS {
+        let value = reader.storage(address, slot.cvt())?;
+        if value != U256::ZERO {
+            let word = value.to_be_bytes::<32>();
+            return Ok(Some(Address::from_slice(&word[12..])));
+        }
+    }
+    Ok(None)
+}
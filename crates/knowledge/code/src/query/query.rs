@@ -1,4 +1,8 @@
-use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use alloy_json_abi::JsonAbi;
 use foundry_block_explorers::{contract::Metadata, errors::EtherscanError};
@@ -15,7 +19,16 @@ use moka::sync::Cache;
 use sea_orm::{sea_query::OnConflict, DatabaseConnection, EntityTrait};
 use semver::Version;
 
-use crate::{config::CodeKnowledgeConfig, entities, error::Error};
+use crate::{
+    config::CodeKnowledgeConfig,
+    decode::{decode_call, DecodedCall},
+    entities,
+    error::Error,
+    source_map::{resolve_pc, SourceLocation},
+    verify::{verify_contract, VerificationResult},
+};
+
+use super::proxy::{implementation_in_slots, StorageReader, MAX_PROXY_HOPS};
 
 pub struct CodeQuery {
     fetcher: super::fetcher::MultiplexedFetcher,
@@ -29,6 +42,7 @@ pub struct CodeQuery {
     abi_cache: Cache<Address, Arc<JsonAbi>>,
     function_signatures_cache:
         Cache<Address, Arc<BTreeMap<FixedBytes<4>, String>>>,
+    proxy_reader: Option<Arc<dyn StorageReader>>,
 }
 
 impl CodeQuery {
@@ -37,15 +51,23 @@ impl CodeQuery {
         cfg: &CodeKnowledgeConfig,
         eager: bool,
     ) -> Result<Self, Error> {
-        let fetcher = super::fetcher::MultiplexedFetcher::new(cfg);
         let db = db_cfg
             .get_database_connection()
             .await
             .map_err(Error::Database)?;
+        Ok(Self::with_db(db, cfg, eager))
+    }
+
+    fn with_db(
+        db: DatabaseConnection,
+        cfg: &CodeKnowledgeConfig,
+        eager: bool,
+    ) -> Self {
+        let fetcher = super::fetcher::MultiplexedFetcher::new(cfg);
         let source_code_cache = Cache::new(cfg.cache_size);
         let compiler_input_cache = Cache::new(cfg.cache_size);
         let compiler_output_cache = Cache::new(cfg.cache_size);
-        Ok(Self {
+        Self {
             fetcher,
             db,
             eager,
@@ -56,7 +78,104 @@ impl CodeQuery {
             storage_layout_cache: Cache::new(cfg.cache_size),
             abi_cache: Cache::new(cfg.cache_size),
             function_signatures_cache: Cache::new(cfg.cache_size),
-        })
+            proxy_reader: None,
+        }
+    }
+
+    /// Follow proxies by reading their implementation slots (EIP-1967, EIP-1822, and transparent proxies)
+    /// with the reader, instead of relying on the implementation reported by the block explorer.
+    pub fn with_proxy_reader(
+        mut self,
+        reader: impl StorageReader + 'static,
+    ) -> Self {
+        self.proxy_reader = Some(Arc::new(reader));
+        self
+    }
+}
+
+impl CodeQuery {
+    /// The implementation of the proxy, or None if the contract is not a proxy.
+    async fn next_implementation(
+        &self,
+        address: Address,
+    ) -> Result<Option<Address>, Error> {
+        if let Some(reader) = &self.proxy_reader {
+            return implementation_in_slots(reader.as_ref(), address)
+                .map_err(Error::Sofl);
+        }
+        let model = self.get_model_async(address).await?;
+        Ok(model
+            .filter(|m| m.proxy)
+            .and_then(|m| m.implementation.as_ref()?.parse().ok()))
+    }
+
+    /// Resolve the implementation chain of the proxy, through multiple hops if needed.
+    /// The address itself is returned if it is not a proxy.
+    pub async fn resolve_implementation_async(
+        &self,
+        address: Address,
+    ) -> Result<Address, Error> {
+        let mut visited = vec![address];
+        let mut current = address;
+        while let Some(next) = self.next_implementation(current).await? {
+            if visited.contains(&next) {
+                return Err(Error::ProxyResolution(format!(
+                    "cyclic proxy chain from {}: {:?}",
+                    address, visited
+                )));
+            }
+            if visited.len() > MAX_PROXY_HOPS {
+                return Err(Error::ProxyResolution(format!(
+                    "more than {} proxies from {}",
+                    MAX_PROXY_HOPS, address
+                )));
+            }
+            visited.push(next);
+            current = next;
+        }
+        Ok(current)
+    }
+
+    /// The ABI of the implementation behind the proxy, tagged with the resolved implementation address.
+    pub async fn get_abi_following_proxy_async(
+        &self,
+        address: Address,
+    ) -> Result<Option<(Address, Arc<JsonAbi>)>, Error> {
+        let implementation = self.resolve_implementation_async(address).await?;
+        let abi = self.get_abi_async(implementation).await?;
+        Ok(abi.map(|abi| (implementation, abi)))
+    }
+
+    /// The storage layout of the implementation behind the proxy, tagged with the resolved implementation address.
+    pub async fn get_storage_layout_following_proxy_async(
+        &self,
+        address: Address,
+    ) -> Result<Option<(Address, Arc<StorageLayout>)>, Error> {
+        let implementation = self.resolve_implementation_async(address).await?;
+        let layout = self.get_storage_layout_async(implementation).await?;
+        Ok(layout.map(|layout| (implementation, layout)))
+    }
+
+    /// Decode the calldata of a call to the contract with the ABI of its implementation if it is a proxy,
+    /// falling back to the ABI of the proxy itself (e.g., for `upgradeTo` of transparent proxies).
+    pub async fn decode_call_async(
+        &self,
+        address: Address,
+        input: &[u8],
+    ) -> Result<DecodedCall, Error> {
+        let mut abis = Vec::new();
+        if let Some(abi) = self.get_abi_following_proxy_async(address).await? {
+            abis.push(abi);
+        }
+        if abis.first().map_or(true, |(a, _)| *a != address) {
+            if let Some(abi) = self.get_abi_async(address).await? {
+                abis.push((address, abi));
+            }
+        }
+        Ok(abis
+            .iter()
+            .find_map(|(a, abi)| decode_call(*a, abi, input))
+            .unwrap_or_else(|| DecodedCall::unknown(input)))
     }
 }
 
@@ -168,6 +287,81 @@ impl CodeQuery {
         Ok(Some(output))
     }
 
+    /// Recompile the verified source of the contract with the stored compiler input and version,
+    /// and compare the runtime code of the contract with `onchain`, its code on chain.
+    pub async fn verify_bytecode_async(
+        &self,
+        address: Address,
+        onchain: &[u8],
+    ) -> Result<VerificationResult, Error> {
+        let Some(model) = self.get_model_async(address).await? else {
+            return Ok(VerificationResult::Unverifiable);
+        };
+        let Some(output) = self.get_compiler_output_async(address).await?
+        else {
+            return Ok(VerificationResult::Unverifiable);
+        };
+        // contracts of the same name may be in different files
+        let mut result = VerificationResult::Unverifiable;
+        for contract in
+            output.contracts.values().flat_map(|f| f.get(&model.name))
+        {
+            result = verify_contract(contract, onchain);
+            if result == VerificationResult::Match {
+                break;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Resolve the pc of the runtime code of the contract to the span in its verified source,
+    /// with the source map of the compiler output.
+    pub async fn resolve_pc_to_source_async(
+        &self,
+        address: Address,
+        pc: usize,
+    ) -> Result<Option<SourceLocation>, Error> {
+        let Some(model) = self.get_model_async(address).await? else {
+            return Ok(None);
+        };
+        let Some(output) = self.get_compiler_output_async(address).await?
+        else {
+            return Ok(None);
+        };
+        let Some((_, input)) =
+            self.get_compiler_version_and_input_async(address).await?
+        else {
+            return Ok(None);
+        };
+        let Some(contract) =
+            output.contracts.values().find_map(|f| f.get(&model.name))
+        else {
+            return Ok(None);
+        };
+        let Some(bytecode) = contract
+            .evm
+            .as_ref()
+            .and_then(|evm| evm.deployed_bytecode.as_ref())
+            .and_then(|deployed| deployed.bytecode.as_ref())
+        else {
+            return Ok(None);
+        };
+        let (Some(code), Some(source_map)) =
+            (bytecode.object.as_bytes(), bytecode.source_map.as_ref())
+        else {
+            return Ok(None);
+        };
+        let sources: BTreeMap<u32, (String, String)> = output
+            .sources
+            .iter()
+            .filter_map(|(file, source)| {
+                let content = input.sources.get(Path::new(file))?;
+                Some((source.id, (file.clone(), content.content.to_string())))
+            })
+            .collect();
+        Ok(resolve_pc(code, source_map, &sources, pc))
+    }
+
     pub async fn get_compiler_version_and_input_async(
         &self,
         address: Address,
@@ -464,3 +658,153 @@ impl CodeQuery {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::types::{keccak256, Address, Bytes, U256},
+        error::SoflError,
+    };
+    use sea_orm::DatabaseConnection;
+
+    use crate::{
+        config::CodeKnowledgeConfig, decode::DecodedCall, entities,
+        error::Error, query::proxy::IMPLEMENTATION_SLOTS,
+    };
+
+    use super::CodeQuery;
+
+    fn verified(
+        address: Address,
+        abi: serde_json::Value,
+    ) -> entities::code::Model {
+        entities::code::Model {
+            contract: address.to_string(),
+            verified: true,
+            abi,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_abi_following_proxy() {
+        let proxy: Address = 0x1111.cvt();
+        let implementation: Address = 0x2222.cvt();
+        let proxy_abi = serde_json::json!([{"type": "function", "name": "upgradeTo", "inputs": [{"name": "impl", "type": "address"}], "outputs": [], "stateMutability": "nonpayable"}]);
+        let impl_abi = serde_json::json!([{"type": "function", "name": "deposit", "inputs": [], "outputs": [], "stateMutability": "payable"}]);
+
+        // the models are cached, so that neither the database nor the block explorer is queried
+        let query = |slots: HashMap<(Address, U256), U256>| {
+            let query = CodeQuery::with_db(
+                DatabaseConnection::Disconnected,
+                &CodeKnowledgeConfig::default(),
+                false,
+            )
+            .with_proxy_reader(move |address, slot| {
+                Ok::<_, SoflError>(
+                    slots.get(&(address, slot)).copied().unwrap_or_default(),
+                )
+            });
+            for (address, abi) in [
+                (proxy, proxy_abi.clone()),
+                (implementation, impl_abi.clone()),
+            ] {
+                query
+                    .model_cache
+                    .insert(address, Arc::new(verified(address, abi)));
+            }
+            query
+        };
+        let eip1967: U256 = IMPLEMENTATION_SLOTS[0].cvt();
+        let word = |address: Address| U256::from_be_slice(address.as_slice());
+
+        let mut slots = HashMap::new();
+        slots.insert((proxy, eip1967), word(implementation));
+        let (resolved, abi) = query(slots)
+            .get_abi_following_proxy_async(proxy)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved, implementation);
+        assert!(abi.functions.contains_key("deposit"));
+
+        // not a proxy
+        let (resolved, _) = query(HashMap::new())
+            .get_abi_following_proxy_async(proxy)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved, proxy);
+
+        // the implementation points back to the proxy
+        let mut slots = HashMap::new();
+        slots.insert((proxy, eip1967), word(implementation));
+        slots.insert((implementation, eip1967), word(proxy));
+        let err = query(slots)
+            .get_abi_following_proxy_async(proxy)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ProxyResolution(_)));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_decode_erc20_transfer_through_proxy() {
+        let proxy: Address = 0x1111.cvt();
+        let token: Address = 0x2222.cvt();
+        let proxy_abi = serde_json::json!([{"type": "function", "name": "upgradeTo", "inputs": [{"name": "impl", "type": "address"}], "outputs": [], "stateMutability": "nonpayable"}]);
+        let token_abi = serde_json::json!([{"type": "function", "name": "transfer", "inputs": [{"name": "to", "type": "address"}, {"name": "value", "type": "uint256"}], "outputs": [{"name": "", "type": "bool"}], "stateMutability": "nonpayable"}]);
+        let eip1967: U256 = IMPLEMENTATION_SLOTS[0].cvt();
+        let impl_word = U256::from_be_slice(token.as_slice());
+        let query = CodeQuery::with_db(
+            DatabaseConnection::Disconnected,
+            &CodeKnowledgeConfig::default(),
+            false,
+        )
+        .with_proxy_reader(move |address, slot| {
+            let is_impl_slot = address == proxy && slot == eip1967;
+            let value = if is_impl_slot { impl_word } else { U256::ZERO };
+            Ok::<_, SoflError>(value)
+        });
+        for (address, abi) in [(proxy, proxy_abi), (token, token_abi)] {
+            query
+                .model_cache
+                .insert(address, Arc::new(verified(address, abi)));
+        }
+
+        // transfer(0x28C6c06298d514Db089934071355E5743bf21d60, 1e18)
+        let input: Bytes = "0xa9059cbb00000000000000000000000028c6c06298d514db089934071355e5743bf21d600000000000000000000000000000000000000000000000000de0b6b3a7640000".parse().unwrap();
+        let decoded = query.decode_call_async(proxy, &input).await.unwrap();
+        assert_eq!(
+            decoded,
+            DecodedCall::Call {
+                abi_address: token,
+                function_name: "transfer".to_string(),
+                args: vec![
+                    (
+                        "to".to_string(),
+                        "0x28C6c06298d514Db089934071355E5743bf21d60"
+                            .to_string()
+                    ),
+                    ("value".to_string(), "1000000000000000000".to_string()),
+                ],
+            }
+        );
+
+        // the function of the proxy itself
+        let mut input = vec![0; 36];
+        input[..4].copy_from_slice(&keccak256("upgradeTo(address)")[..4]);
+        let decoded = query.decode_call_async(proxy, &input).await.unwrap();
+        assert!(matches!(
+            decoded,
+            DecodedCall::Call { abi_address, .. } if abi_address == proxy
+        ));
+
+        // no function of either ABI matches
+        let input = [0xde, 0xad, 0xbe, 0xef];
+        let decoded = query.decode_call_async(proxy, &input).await.unwrap();
+        assert_eq!(decoded, DecodedCall::unknown(&input));
+    }
+}
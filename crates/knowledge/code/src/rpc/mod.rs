@@ -7,10 +7,30 @@ use foundry_compilers::{
     artifacts::StorageLayout, CompilerInput, CompilerOutput,
 };
 use jsonrpsee::{core::async_trait, proc_macros::rpc};
-use libsofl_core::engine::types::{Address, FixedBytes};
+use libsofl_core::{
+    blockchain::{
+        provider::{BcProvider, BcStateProvider},
+        transaction::{Tx, TxKind},
+    },
+    conversion::ConvertTo,
+    engine::{
+        state::BcState,
+        types::{Address, FixedBytes, TxHash},
+    },
+    error::SoflError,
+};
+use libsofl_reth::blockchain::provider::{BlockNumReader, RethProvider};
 use semver::Version;
 
-use crate::{error::Error, query::query::CodeQuery};
+use crate::{
+    abi::{diff_abis, AbiDiff},
+    decode::DecodedCall,
+    error::Error,
+    layout::{diff_storage_layouts, LayoutDiff},
+    query::query::CodeQuery,
+    source_map::SourceLocation,
+    verify::VerificationResult,
+};
 
 #[rpc(client, server, namespace = "kb")]
 pub trait CodeRpc {
@@ -50,18 +70,58 @@ pub trait CodeRpc {
         address: Address,
     ) -> Result<Option<StorageLayout>, Error>;
 
+    #[method(name = "storageLayoutDiff")]
+    async fn storage_layout_diff(
+        &self,
+        old_address: Address,
+        new_address: Address,
+    ) -> Result<Option<LayoutDiff>, Error>;
+
     #[method(name = "abi")]
     async fn abi(&self, address: Address) -> Result<Option<JsonAbi>, Error>;
 
+    #[method(name = "abiDiff")]
+    async fn abi_diff(
+        &self,
+        old_address: Address,
+        new_address: Address,
+    ) -> Result<Option<AbiDiff>, Error>;
+
     #[method(name = "functionSignatures")]
     async fn function_signatures(
         &self,
         address: Address,
     ) -> Result<Option<BTreeMap<FixedBytes<4>, String>>, Error>;
+
+    /// Recompile the verified source of the contract and compare it with
+    /// the code of the contract at the latest block.
+    #[method(name = "verifyBytecode")]
+    async fn verify_bytecode(
+        &self,
+        address: Address,
+    ) -> Result<VerificationResult, Error>;
+
+    /// Resolve the pc of the runtime code of the contract to the span in
+    /// its verified source.
+    #[method(name = "resolvePcToSource")]
+    async fn resolve_pc_to_source(
+        &self,
+        address: Address,
+        pc: usize,
+    ) -> Result<Option<SourceLocation>, Error>;
+
+    /// Decode the calldata of the transaction with the ABI of its target,
+    /// following proxies.
+    #[method(name = "decodeTxInput")]
+    async fn decode_tx_input(
+        &self,
+        tx_hash: TxHash,
+    ) -> Result<DecodedCall, Error>;
 }
 
 pub struct CodeRpcImpl {
     pub query: Arc<CodeQuery>,
+    pub provider: Arc<RethProvider>,
 }
 
 #[async_trait]
@@ -126,6 +186,19 @@ impl CodeRpcServer for CodeRpcImpl {
             .map(|x| x.map(|l| (*l).clone()))
     }
 
+    async fn storage_layout_diff(
+        &self,
+        old_address: Address,
+        new_address: Address,
+    ) -> Result<Option<LayoutDiff>, Error> {
+        let old = self.query.get_storage_layout_async(old_address).await?;
+        let new = self.query.get_storage_layout_async(new_address).await?;
+        Ok(match (old, new) {
+            (Some(old), Some(new)) => Some(diff_storage_layouts(&old, &new)),
+            _ => None,
+        })
+    }
+
     async fn abi(&self, address: Address) -> Result<Option<JsonAbi>, Error> {
         self.query
             .get_abi_async(address)
@@ -133,6 +206,19 @@ impl CodeRpcServer for CodeRpcImpl {
             .map(|x| x.map(|a| (*a).clone()))
     }
 
+    async fn abi_diff(
+        &self,
+        old_address: Address,
+        new_address: Address,
+    ) -> Result<Option<AbiDiff>, Error> {
+        let old = self.query.get_abi_async(old_address).await?;
+        let new = self.query.get_abi_async(new_address).await?;
+        Ok(match (old, new) {
+            (Some(old), Some(new)) => Some(diff_abis(&old, &new)),
+            _ => None,
+        })
+    }
+
     async fn function_signatures(
         &self,
         address: Address,
@@ -142,4 +228,52 @@ impl CodeRpcServer for CodeRpcImpl {
             .await
             .map(|x| x.map(|s| (*s).clone()))
     }
+
+    async fn verify_bytecode(
+        &self,
+        address: Address,
+    ) -> Result<VerificationResult, Error> {
+        let provider = self.provider.clone();
+        let code = tokio::task::spawn_blocking(move || {
+            let bn = provider.best_block_number().map_err(|e| {
+                SoflError::Provider(format!(
+                    "failed to get best block number: {}",
+                    e
+                ))
+            })?;
+            let mut state = provider.bc_state_at((bn + 1).cvt())?;
+            state.get_account_code(address)
+        })
+        .await
+        .expect("code lookup panicked")
+        .map_err(Error::Sofl)?;
+        self.query
+            .verify_bytecode_async(address, &code.original_bytes())
+            .await
+    }
+
+    async fn resolve_pc_to_source(
+        &self,
+        address: Address,
+        pc: usize,
+    ) -> Result<Option<SourceLocation>, Error> {
+        self.query.resolve_pc_to_source_async(address, pc).await
+    }
+
+    async fn decode_tx_input(
+        &self,
+        tx_hash: TxHash,
+    ) -> Result<DecodedCall, Error> {
+        let provider = self.provider.clone();
+        let tx =
+            tokio::task::spawn_blocking(move || provider.tx(tx_hash.into()))
+                .await
+                .expect("transaction lookup panicked")
+                .map_err(Error::Sofl)?;
+        let input = tx.input();
+        match tx.kind() {
+            TxKind::Call(to) => self.query.decode_call_async(to, &input).await,
+            TxKind::Create => Ok(DecodedCall::unknown(&input)),
+        }
+    }
 }
@@ -1,20 +1,22 @@
 use std::{
     net::SocketAddr,
     sync::{atomic::AtomicU64, Arc},
+    time::Duration,
 };
 
 use eyre::Result;
 use jsonrpsee::{core::async_trait, server::ServerBuilder, Methods};
 use libsofl_knowledge_base::{
-    config::KnowledgeConfig, service::KnowledgeService,
+    config::KnowledgeConfig, metrics::ServiceMetrics, service::KnowledgeService,
 };
 use libsofl_reth::blockchain::{
     provider::{BlockNumReader, RethProvider},
     transaction::RethTx,
 };
-use libsofl_utils::log::info;
+use libsofl_utils::log::{info, warn};
 use sea_orm::{DatabaseConnection, EntityTrait};
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     collect::collector::Collector, config::CodeKnowledgeConfig,
@@ -24,6 +26,10 @@ use crate::{
 use super::{CodeRpcImpl, CodeRpcServer};
 use crate::collect::{CodeKnowledgeMetadata, CODE_KNOWLEDGE_METADATA_KEY};
 
+/// How long the collector is given to finish the block in process
+/// when the service is stopped.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub struct CodeRpcService {
     pub socket_addr: SocketAddr,
     handle: JoinHandle<()>,
@@ -32,6 +38,7 @@ pub struct CodeRpcService {
 impl CodeRpcService {
     pub async fn new(
         query: Arc<CodeQuery>,
+        provider: Arc<RethProvider>,
         host: &str,
         port: usize,
     ) -> Result<Self, std::io::Error> {
@@ -39,7 +46,7 @@ impl CodeRpcService {
             .build(&format!("{}:{}", host, port))
             .await?;
         let addr = server.local_addr().unwrap();
-        let rpc_impl = CodeRpcImpl { query };
+        let rpc_impl = CodeRpcImpl { query, provider };
         let server_handle = server.start(rpc_impl.into_rpc());
         let handle = tokio::task::spawn(server_handle.stopped());
 
@@ -63,8 +70,11 @@ pub struct CodeService {
     pub provider: Arc<RethProvider>,
     pub query: Arc<CodeQuery>,
     pub db: Arc<DatabaseConnection>,
+    pub max_in_flight_requests: Option<usize>,
 
     _collector_task: Option<JoinHandle<()>>,
+    collector_token: CancellationToken,
+    metrics: Option<ServiceMetrics>,
 }
 
 impl CodeService {
@@ -79,7 +89,10 @@ impl CodeService {
             provider,
             query,
             db,
+            max_in_flight_requests: code_cfg.max_in_flight_requests,
             _collector_task: None,
+            collector_token: CancellationToken::new(),
+            metrics: None,
         })
     }
 
@@ -105,6 +118,27 @@ impl CodeService {
         };
         progress
     }
+
+    /// Stop the collector from taking new blocks and wait for the block
+    /// in process to finish, so that its progress is saved.
+    /// The collector is aborted if it does not finish within `timeout`.
+    pub async fn shutdown(&mut self, timeout: Duration) -> Result<()> {
+        self.collector_token.cancel();
+        let Some(mut handle) = self._collector_task.take() else {
+            return Ok(());
+        };
+        match tokio::time::timeout(timeout, &mut handle).await {
+            Ok(r) => r?,
+            Err(_) => {
+                warn!(
+                    timeout = ?timeout,
+                    "collector did not stop in time, aborting"
+                );
+                handle.abort();
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -113,7 +147,8 @@ impl KnowledgeService for CodeService {
         "code"
     }
 
-    async fn start(&mut self) -> Result<()> {
+    async fn start(&mut self, metrics: ServiceMetrics) -> Result<()> {
+        self.metrics = Some(metrics);
         // start a task that collect until current best block
         let from = self.load_progress().await;
         let until = self.provider.best_block_number()?;
@@ -123,7 +158,10 @@ impl KnowledgeService for CodeService {
             self.query.clone(),
             self.provider.clone(),
             Arc::new(current_bn),
-        );
+        )
+        .with_max_in_flight(self.max_in_flight_requests)
+        .with_cancellation_token(self.collector_token.clone())
+        .with_metrics(self.metrics.clone());
         let task = async move {
             collector.worker_loop(until).await;
         };
@@ -133,18 +171,14 @@ impl KnowledgeService for CodeService {
     }
 
     async fn stop(&mut self) -> Result<()> {
-        // stop the collector task if any
-        if let Some(handle) = self._collector_task.take() {
-            if !handle.is_finished() {
-                handle.abort();
-            }
-        }
-        Ok(())
+        // let the collector task (if any) finish the block in process
+        self.shutdown(DEFAULT_SHUTDOWN_TIMEOUT).await
     }
 
     fn rpc_methods(&self) -> Methods {
         let rpc = CodeRpcImpl {
             query: self.query.clone(),
+            provider: self.provider.clone(),
         };
         rpc.into_rpc().into()
     }
@@ -163,7 +197,10 @@ impl KnowledgeService for CodeService {
                 self.query.clone(),
                 self.provider.clone(),
                 Arc::new(AtomicU64::new(from)),
-            );
+            )
+            .with_max_in_flight(self.max_in_flight_requests)
+            .with_cancellation_token(self.collector_token.clone())
+            .with_metrics(self.metrics.clone());
             let task = async move {
                 collector.worker_loop(block_number).await;
             };
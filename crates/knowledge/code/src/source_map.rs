@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+
+/// A span in a source file.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    /// The byte offset of the span in the file.
+    pub start: usize,
+    pub length: usize,
+    /// The 1-based line of the start.
+    pub line: usize,
+    /// The 1-based column (in bytes) of the start.
+    pub col: usize,
+}
+
+/// An entry of a solc source map, mapping an instruction to a source span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceMapEntry {
+    pub start: i64,
+    pub length: i64,
+    /// The source id of the file, or -1 for compiler-generated code.
+    pub file: i64,
+    /// `i` into a function, `o` out of a function, or `-` for other jumps.
+    pub jump: char,
+    pub modifier_depth: i64,
+}
+
+/// Decode the compressed source map, with one entry per instruction.
+/// Entries are `s:l:f:j:m` separated by `;`, where empty or missing fields
+/// are the same as those of the previous entry.
+pub fn decode_source_map(map: &str) -> Vec<SourceMapEntry> {
+    if map.is_empty() {
+        return Vec::new();
+    }
+    let mut prev = SourceMapEntry {
+        start: -1,
+        length: -1,
+        file: -1,
+        jump: '-',
+        modifier_depth: 0,
+    };
+    map.split(';')
+        .map(|entry| {
+            let mut fields = entry.split(':');
+            let mut int = |prev: i64| {
+                fields
+                    .next()
+                    .filter(|f| !f.is_empty())
+                    .and_then(|f| f.parse().ok())
+                    .unwrap_or(prev)
+            };
+            let start = int(prev.start);
+            let length = int(prev.length);
+            let file = int(prev.file);
+            let jump = fields
+                .next()
+                .and_then(|f| f.chars().next())
+                .unwrap_or(prev.jump);
+            let modifier_depth = fields
+                .next()
+                .filter(|f| !f.is_empty())
+                .and_then(|f| f.parse().ok())
+                .unwrap_or(prev.modifier_depth);
+            prev = SourceMapEntry {
+                start,
+                length,
+                file,
+                jump,
+                modifier_depth,
+            };
+            prev
+        })
+        .collect()
+}
+
+/// The index of the instruction at `pc`, since source maps are indexed by
+/// instructions rather than bytes. None if `pc` is in the immediate bytes
+/// of a PUSH instruction or out of the code.
+pub fn instruction_index(code: &[u8], pc: usize) -> Option<usize> {
+    let mut current = 0;
+    let mut index = 0;
+    while current < code.len() {
+        if current == pc {
+            return Some(index);
+        }
+        if current > pc {
+            return None;
+        }
+        let op = code[current];
+        // PUSH1 (0x60) to PUSH32 (0x7f)
+        let immediate = if (0x60..=0x7f).contains(&op) {
+            (op - 0x5f) as usize
+        } else {
+            0
+        };
+        current += 1 + immediate;
+        index += 1;
+    }
+    None
+}
+
+/// Resolve the pc of the runtime code to the source span with the source map.
+/// `sources` maps source ids to file names and contents.
+/// None if the instruction has no source, e.g., compiler-generated code.
+pub fn resolve_pc(
+    code: &[u8],
+    source_map: &str,
+    sources: &BTreeMap<u32, (String, String)>,
+    pc: usize,
+) -> Option<SourceLocation> {
+    let index = instruction_index(code, pc)?;
+    let entry = *decode_source_map(source_map).get(index)?;
+    if entry.file < 0 || entry.start < 0 || entry.length < 0 {
+        return None;
+    }
+    let (file, content) = sources.get(&u32::try_from(entry.file).ok()?)?;
+    let start = entry.start as usize;
+    let before = content.as_bytes().get(..start)?;
+    let line = before.iter().filter(|b| **b == b'\n').count() + 1;
+    let line_start = before
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    Some(SourceLocation {
+        file: file.clone(),
+        start,
+        length: entry.length as usize,
+        line,
+        col: start - line_start + 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{decode_source_map, instruction_index, resolve_pc};
+
+    #[test]
+    fn test_decode_compressed_source_map() {
+        let entries = decode_source_map("1:2:0:-:0;;5:6;:3:-1:i;::0:o:1");
+        let fields: Vec<_> = entries
+            .iter()
+            .map(|e| (e.start, e.length, e.file, e.jump, e.modifier_depth))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (1, 2, 0, '-', 0),
+                (1, 2, 0, '-', 0),
+                (5, 6, 0, '-', 0),
+                (5, 3, -1, 'i', 0),
+                (5, 3, 0, 'o', 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_pc() {
+        let source = "contract C {\n    function f() external {\n        revert();\n    }\n}\n";
+        let revert_start = source.find("revert()").unwrap();
+        // PUSH1 0x80, PUSH1 0x40, MSTORE, PUSH1 0, DUP1, REVERT
+        let code = [0x60, 0x80, 0x60, 0x40, 0x52, 0x60, 0x00, 0x80, 0xfd];
+        let source_map = format!("0:70:0:-:0;;;-1;{}:8:0;", revert_start);
+        let file = ("C.sol".to_string(), source.to_string());
+        let sources = BTreeMap::from([(0, file)]);
+
+        assert_eq!(instruction_index(&code, 7), Some(4));
+        // in the immediate bytes of PUSH1
+        assert_eq!(instruction_index(&code, 1), None);
+
+        let location = resolve_pc(&code, &source_map, &sources, 7).unwrap();
+        assert_eq!(location.file, "C.sol");
+        assert_eq!(location.start, revert_start);
+        assert_eq!(location.length, 8);
+        assert_eq!((location.line, location.col), (3, 9));
+        // the last instruction inherits the span of the previous one
+        let location = resolve_pc(&code, &source_map, &sources, 8).unwrap();
+        assert_eq!(location.start, revert_start);
+        // compiler-generated code
+        assert_eq!(resolve_pc(&code, &source_map, &sources, 5), None);
+        let location = resolve_pc(&code, &source_map, &sources, 0).unwrap();
+        assert_eq!((location.line, location.col), (1, 1));
+    }
+}
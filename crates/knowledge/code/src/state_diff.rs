@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, HashMap};
+
+use foundry_compilers::artifacts::{StorageLayout, StorageType};
+use libsofl_core::{
+    conversion::{uint::as_i256, ConvertTo},
+    engine::{
+        inspector::EvmInspector,
+        state::BcState,
+        types::{
+            keccak256, opcode, Address, Bytes, EvmContext, Inspector,
+            Interpreter, StateChange, U256,
+        },
+    },
+};
+
+/// A storage slot changed by a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotChange {
+    pub address: Address,
+    pub slot: U256,
+    pub before: U256,
+    pub after: U256,
+}
+
+/// The storage changes of a transaction,
+/// with the preimages of hashed slots to resolve mapping keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageDiff {
+    /// Sorted by address and slot.
+    pub changes: Vec<SlotChange>,
+    /// The preimages of hashes, e.g., recorded by `PreimageInspector`.
+    pub preimages: HashMap<U256, Bytes>,
+}
+
+impl StorageDiff {
+    /// Collect the changed slots from the state changes of a transaction,
+    /// i.e., one element of the changes returned by `BcState::simulate`.
+    pub fn new(changes: &StateChange, preimages: HashMap<U256, Bytes>) -> Self {
+        let mut slots: Vec<SlotChange> = changes
+            .iter()
+            .flat_map(|(address, account)| {
+                account
+                    .storage
+                    .iter()
+                    .filter(|(_, slot)| slot.is_changed())
+                    .map(|(key, slot)| SlotChange {
+                        address: *address,
+                        slot: *key,
+                        before: slot.original_value(),
+                        after: slot.present_value(),
+                    })
+            })
+            .collect();
+        slots.sort_by_key(|c| (c.address, c.slot));
+        Self {
+            changes: slots,
+            preimages,
+        }
+    }
+}
+
+/// PreimageInspector records the preimages of `KECCAK256` over two words,
+/// which is how Solidity computes the slots of mapping entries.
+#[derive(Debug, Clone, Default)]
+pub struct PreimageInspector {
+    pub preimages: HashMap<U256, Bytes>,
+}
+
+impl<S: BcState> Inspector<S> for PreimageInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<S>) {
+        if interp.current_opcode() != opcode::KECCAK256 {
+            return;
+        }
+        let mut stack = interp.stack.data().iter().rev();
+        let (Some(offset), Some(len)) = (stack.next(), stack.next()) else {
+            return;
+        };
+        if *len != U256::from(64) || *offset > U256::from(usize::MAX - 64) {
+            return;
+        }
+        let offset: usize = offset.cvt();
+        if offset + 64 > interp.shared_memory.len() {
+            return;
+        }
+        let preimage = interp.shared_memory.slice(offset, 64);
+        let hash = U256::from_be_bytes(keccak256(preimage).0);
+        self.preimages.insert(hash, preimage.to_vec().into());
+    }
+}
+
+impl<S: BcState> EvmInspector<S> for PreimageInspector {}
+
+/// A variable (or mapping entry) of a storage layout in a slot.
+struct SlotVariable<'a> {
+    contract: &'a str,
+    /// The label with the mapping keys, e.g., `balanceOf[0x...]`.
+    path: String,
+    ty: Option<&'a StorageType>,
+    /// The byte offset in the slot, non-zero for packed variables.
+    offset: u64,
+}
+
+/// Render the storage changes of a transaction line by line,
+/// e.g., `WETH9.balanceOf[0x...]: 1000 -> 2000`,
+/// with the storage layouts of the changed contracts.
+/// Mapping entries are resolved with the recorded preimages.
+/// Slots that cannot be resolved, e.g., of contracts without a layout,
+/// are rendered as raw slots, e.g., `0x...[0x3]: 0x1 -> 0x2`.
+pub fn format_state_diff(
+    diff: &StorageDiff,
+    layouts: &BTreeMap<Address, StorageLayout>,
+) -> String {
+    let mut lines = Vec::new();
+    for change in &diff.changes {
+        let vars = layouts.get(&change.address).map_or(vec![], |layout| {
+            resolve_slot(layout, &diff.preimages, change.slot)
+        });
+        if vars.is_empty() {
+            lines.push(format!(
+                "{}[{:#x}]: {:#x} -> {:#x}",
+                change.address, change.slot, change.before, change.after
+            ));
+            continue;
+        }
+        for var in &vars {
+            let before = format_value(var.ty, change.before, var.offset);
+            let after = format_value(var.ty, change.after, var.offset);
+            // unchanged variables packed in the same slot
+            if before == after && vars.len() > 1 {
+                continue;
+            }
+            lines.push(format!(
+                "{}.{}: {} -> {}",
+                var.contract, var.path, before, after
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// The variables stored in the slot, which are more than one if packed,
+/// or the entry of a (nested) mapping at the slot.
+fn resolve_slot<'a>(
+    layout: &'a StorageLayout,
+    preimages: &HashMap<U256, Bytes>,
+    slot: U256,
+) -> Vec<SlotVariable<'a>> {
+    let vars: Vec<SlotVariable<'a>> = layout
+        .storage
+        .iter()
+        .filter(|s| s.slot.parse::<U256>().ok() == Some(slot))
+        .map(|s| SlotVariable {
+            contract: contract_name(&s.contract),
+            path: s.label.clone(),
+            ty: layout.types.get(&s.storage_type),
+            offset: s.offset as u64,
+        })
+        .collect();
+    if !vars.is_empty() {
+        return vars;
+    }
+    resolve_mapping_entry(layout, preimages, slot)
+        .into_iter()
+        .collect()
+}
+
+/// The entry `mapping[key]`, which is at `keccak256(key ++ base_slot)`.
+fn resolve_mapping_entry<'a>(
+    layout: &'a StorageLayout,
+    preimages: &HashMap<U256, Bytes>,
+    slot: U256,
+) -> Option<SlotVariable<'a>> {
+    let preimage = preimages.get(&slot).filter(|p| p.len() == 64)?;
+    let key = U256::from_be_slice(&preimage[..32]);
+    let base = U256::from_be_slice(&preimage[32..]);
+    let mut parents = resolve_slot(layout, preimages, base);
+    if parents.len() != 1 {
+        return None;
+    }
+    let parent = parents.remove(0);
+    let ty = parent.ty.filter(|t| t.encoding == "mapping")?;
+    let key_ty = layout.types.get(ty.key.as_ref()?);
+    Some(SlotVariable {
+        contract: parent.contract,
+        path: format!("{}[{}]", parent.path, format_value(key_ty, key, 0)),
+        ty: layout.types.get(ty.value.as_ref()?),
+        offset: 0,
+    })
+}
+
+/// `Contract` of the fully qualified name `path/to/File.sol:Contract`.
+fn contract_name(qualified: &str) -> &str {
+    qualified.rsplit(':').next().unwrap_or(qualified)
+}
+
+/// Decode the value of the type at the byte offset of the slot.
+/// Values of non-value types, e.g., structs, are rendered as raw words.
+fn format_value(ty: Option<&StorageType>, word: U256, offset: u64) -> String {
+    let Some(ty) = ty.filter(|t| t.encoding == "inplace") else {
+        return format!("{:#x}", word);
+    };
+    let size: usize = ty.number_of_bytes.parse().unwrap_or(32).min(32);
+    let bits = size * 8;
+    let value = word >> (offset as usize * 8);
+    let value = if bits < 256 {
+        value & ((U256::from(1) << bits) - U256::from(1))
+    } else {
+        value
+    };
+    let label = ty.label.as_str();
+    if label == "bool" {
+        (value != U256::ZERO).to_string()
+    } else if label.starts_with("address") || label.starts_with("contract ") {
+        Address::from_slice(&value.to_be_bytes::<32>()[12..]).to_string()
+    } else if label.starts_with("uint") || label.starts_with("enum ") {
+        value.to_string()
+    } else if label.starts_with("int") {
+        // sign-extend to 256 bits
+        let negative = value.bit(bits - 1);
+        let value = if negative && bits < 256 {
+            value | (U256::MAX << bits)
+        } else {
+            value
+        };
+        as_i256(value).to_string()
+    } else {
+        format!("{:#x}", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use alloy_dyn_abi::DynSolValue;
+    use foundry_compilers::artifacts::StorageLayout;
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::types::{Address, Bytes, U256},
+    };
+    use libsofl_utils::slot::mapping_slot_of;
+
+    use super::{format_state_diff, SlotChange, StorageDiff};
+
+    /// The storage layout of WETH9.
+    fn weth_layout() -> StorageLayout {
+        let var = |label: &str, slot: &str, ty: &str| {
+            serde_json::json!({
+                "astId": 0,
+                "contract": "WETH9.sol:WETH9",
+                "label": label,
+                "offset": 0,
+                "slot": slot,
+                "type": ty,
+            })
+        };
+        serde_json::from_value(serde_json::json!({
+            "storage": [
+                var("name", "0", "t_string_storage"),
+                var("symbol", "1", "t_string_storage"),
+                var("decimals", "2", "t_uint8"),
+                var("balanceOf", "3", "t_mapping(t_address,t_uint256)"),
+            ],
+            "types": {
+                "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+                "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"},
+                "t_uint8": {"encoding": "inplace", "label": "uint8", "numberOfBytes": "1"},
+                "t_string_storage": {"encoding": "bytes", "label": "string", "numberOfBytes": "32"},
+                "t_mapping(t_address,t_uint256)": {
+                    "encoding": "mapping",
+                    "key": "t_address",
+                    "label": "mapping(address => uint256)",
+                    "numberOfBytes": "32",
+                    "value": "t_uint256",
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_format_weth_balance_change() {
+        let weth: Address = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".cvt();
+        let holder: Address = 0xabc.cvt();
+        let slot =
+            mapping_slot_of(U256::from(3), &DynSolValue::Address(holder));
+        let mut preimage = holder.into_word().to_vec();
+        preimage.extend_from_slice(&U256::from(3).to_be_bytes::<32>());
+        let ether = U256::from(10).pow(U256::from(18));
+        let unknown: Address = 0x1234.cvt();
+        let diff = StorageDiff {
+            changes: vec![
+                SlotChange {
+                    address: weth,
+                    slot,
+                    before: ether,
+                    after: ether * U256::from(2),
+                },
+                SlotChange {
+                    address: unknown,
+                    slot: U256::from(3),
+                    before: U256::from(1),
+                    after: U256::from(2),
+                },
+            ],
+            preimages: HashMap::from([(slot, Bytes::from(preimage))]),
+        };
+        let layouts = BTreeMap::from([(weth, weth_layout())]);
+        let lines: Vec<String> = format_state_diff(&diff, &layouts)
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                format!(
+                    "WETH9.balanceOf[{}]: 1000000000000000000 -> 2000000000000000000",
+                    holder
+                ),
+                // no layout
+                format!("{}[0x3]: 0x1 -> 0x2", unknown),
+            ]
+        );
+
+        // the preimage is unknown
+        let diff = StorageDiff {
+            preimages: HashMap::new(),
+            ..diff
+        };
+        let formatted = format_state_diff(&diff, &layouts);
+        assert!(formatted.starts_with(&format!("{}[{:#x}]", weth, slot)));
+    }
+}
@@ -0,0 +1,188 @@
+use foundry_compilers::artifacts::Contract;
+
+/// The result of comparing the runtime code compiled from the verified source
+/// with the code on chain.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "result", rename_all = "camelCase")]
+pub enum VerificationResult {
+    /// The codes match, ignoring the metadata trailer and immutables.
+    Match,
+    /// The codes differ, i.e., the verification data is stale or tampered.
+    #[serde(rename_all = "camelCase")]
+    Mismatch {
+        compiled_len: usize,
+        onchain_len: usize,
+        /// The offset of the first differing byte.
+        first_difference: usize,
+    },
+    /// The contract is not verified, or its runtime code is not available
+    /// in the compiler output (e.g., it links external libraries).
+    Unverifiable,
+}
+
+/// Compare the runtime code of the compiled contract with the code on chain.
+pub fn verify_contract(
+    contract: &Contract,
+    onchain: &[u8],
+) -> VerificationResult {
+    let Some(deployed) = contract
+        .evm
+        .as_ref()
+        .and_then(|evm| evm.deployed_bytecode.as_ref())
+    else {
+        return VerificationResult::Unverifiable;
+    };
+    let Some(compiled) = deployed
+        .bytecode
+        .as_ref()
+        .and_then(|bytecode| bytecode.object.as_bytes())
+    else {
+        return VerificationResult::Unverifiable;
+    };
+    let immutables: Vec<(usize, usize)> = deployed
+        .immutable_references
+        .values()
+        .flatten()
+        .map(|o| (o.start as usize, o.length as usize))
+        .collect();
+    compare_runtime_code(compiled, &immutables, onchain)
+}
+
+/// Compare the compiled runtime code with the code on chain.
+/// Immutables, given as `(start, length)`, are zeros in the compiled code
+/// but are filled by the constructor (e.g., from constructor arguments),
+/// so they are masked in both codes.
+/// The metadata trailer, which differs across builds of the same source
+/// (e.g., by file paths), is also ignored.
+pub fn compare_runtime_code(
+    compiled: &[u8],
+    immutables: &[(usize, usize)],
+    onchain: &[u8],
+) -> VerificationResult {
+    let mask = |code: &[u8]| {
+        let mut code = code.to_vec();
+        for (start, length) in immutables {
+            let end = (start + length).min(code.len());
+            if *start < end {
+                code[*start..end].fill(0);
+            }
+        }
+        code
+    };
+    let compiled = mask(compiled);
+    let onchain = mask(onchain);
+    let (a, b) = (strip_metadata(&compiled), strip_metadata(&onchain));
+    if a == b {
+        return VerificationResult::Match;
+    }
+    let first_difference = a
+        .iter()
+        .zip(b.iter())
+        .position(|(x, y)| x != y)
+        .unwrap_or(a.len().min(b.len()));
+    VerificationResult::Mismatch {
+        compiled_len: compiled.len(),
+        onchain_len: onchain.len(),
+        first_difference,
+    }
+}
+
+/// Strip the CBOR-encoded metadata appended by solc,
+/// whose length is given by the last two bytes.
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    if code.len() < 2 {
+        return code;
+    }
+    let len_bytes = [code[code.len() - 2], code[code.len() - 1]];
+    let len = u16::from_be_bytes(len_bytes) as usize;
+    match code.len().checked_sub(len + 2) {
+        // the metadata is a CBOR map with at most five entries
+        Some(start) if len > 0 && (0xa1..=0xa5).contains(&code[start]) => {
+            &code[..start]
+        }
+        _ => code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use foundry_compilers::artifacts::Contract;
+    use libsofl_core::engine::types::Bytes;
+
+    use super::{strip_metadata, verify_contract, VerificationResult};
+
+    /// The metadata trailer with the IPFS hash and the solc version.
+    fn metadata(ipfs_byte: u8) -> String {
+        format!(
+            "a264697066735822{}64736f6c63430008140033",
+            format!("{:02x}", ipfs_byte).repeat(34)
+        )
+    }
+
+    /// A contract returning an immutable, i.e., `PUSH32 <immutable>`,
+    /// `PUSH1 0`, `MSTORE`, `PUSH1 32`, `PUSH1 0`, `RETURN`.
+    fn contract() -> Contract {
+        let code = format!(
+            "0x7f{}60005260206000f3{}",
+            "00".repeat(32),
+            metadata(0x11)
+        );
+        serde_json::from_value(serde_json::json!({
+            "evm": {
+                "deployedBytecode": {
+                    "object": code,
+                    "immutableReferences": {
+                        "3": [{"start": 1, "length": 32}],
+                    },
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    fn onchain(immutable: u8, ipfs_byte: u8) -> Bytes {
+        format!(
+            "0x7f{}60005260206000f3{}",
+            format!("{:02x}", immutable).repeat(32),
+            metadata(ipfs_byte)
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_strip_metadata() {
+        let code: Bytes = format!("0x6000{}", metadata(0)).parse().unwrap();
+        assert_eq!(strip_metadata(&code), &[0x60, 0x00]);
+        // no metadata
+        assert_eq!(strip_metadata(&[0x60, 0x00]), &[0x60, 0x00]);
+    }
+
+    #[test]
+    fn test_verify_contract_with_immutable() {
+        let contract = contract();
+        // the immutable is set by the constructor,
+        // and the metadata differs due to a different build environment
+        let code = onchain(0xab, 0x22);
+        let result = verify_contract(&contract, &code);
+        assert_eq!(result, VerificationResult::Match);
+
+        // the code is tampered, e.g., returning from a different offset
+        let mut code = onchain(0xab, 0x11).to_vec();
+        code[34] = 0x01;
+        assert_eq!(
+            verify_contract(&contract, &code),
+            VerificationResult::Mismatch {
+                compiled_len: code.len(),
+                onchain_len: code.len(),
+                first_difference: 34,
+            }
+        );
+
+        // no runtime code in the compiler output
+        assert_eq!(
+            verify_contract(&Contract::default(), &code),
+            VerificationResult::Unverifiable
+        );
+    }
+}
@@ -15,16 +15,19 @@ use libsofl_core::{
     error::SoflError,
 };
 use libsofl_knowledge_index::inspectors::{
-    extract_creation::ExtractCreationInspector,
-    extract_invocation::ExtractInvocationInspector,
+    creation::CreationInspector, extract_invocation::ExtractInvocationInspector,
 };
 use libsofl_utils::log::debug;
+use tokio_util::sync::CancellationToken;
+
+use crate::data::Creation;
 
 pub struct Analyzer<T: Tx, S: BcStateRef, P: BcProvider<T> + BcStateProvider<S>>
 where
     S::Error: std::fmt::Debug,
 {
     provider: Arc<P>,
+    cancellation_token: Option<CancellationToken>,
 
     _phantom: std::marker::PhantomData<(T, S)>,
 }
@@ -37,6 +40,7 @@ where
     fn clone(&self) -> Self {
         Self {
             provider: self.provider.clone(),
+            cancellation_token: self.cancellation_token.clone(),
             _phantom: std::marker::PhantomData,
         }
     }
@@ -50,9 +54,17 @@ where
     pub fn new(provider: Arc<P>) -> Self {
         Self {
             provider,
+            cancellation_token: None,
             _phantom: std::marker::PhantomData,
         }
     }
+
+    /// Abort the analysis of a block with `SoflError::Interrupted`
+    /// once the token is cancelled.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
 }
 
 impl<T: Tx, S: BcStateRef, P: BcProvider<T> + BcStateProvider<S>>
@@ -63,7 +75,7 @@ where
     pub fn analyze_one_block(
         &mut self,
         block: u64,
-    ) -> Result<(Vec<(String, String, bool)>, HashSet<String>), SoflError> {
+    ) -> Result<(Vec<Creation>, HashSet<String>), SoflError> {
         let txs = self.provider.txs_in_block(block.cvt())?;
         let mut cfg_env = CfgEnv::default();
         self.provider.fill_cfg_env(&mut cfg_env, block.cvt())?;
@@ -71,46 +83,50 @@ where
         self.provider.fill_block_env(&mut block_env, block.cvt())?;
         let mut state = self.provider.bc_state_at(block.cvt())?;
 
-        let mut total_creations = Vec::new();
-        let mut total_invocations = HashSet::new();
-
+        let mut tx_envs = Vec::new();
+        let mut tx_hashes: Vec<String> = Vec::new();
         for tx in txs {
             let mut tx_env = TxEnv::default();
             tx.fill_tx_env(&mut tx_env)?;
-            let spec = TransitionSpec {
-                evm_version: None,
-                cfg: cfg_env.clone(),
-                block: block_env.clone(),
-                txs: vec![tx_env],
-            };
-
-            let mut creation_insp = ExtractCreationInspector::default();
-            let mut invocation_insp = ExtractInvocationInspector::default();
-            let mut insp = CombinedInspector::default();
-            insp.add(&mut creation_insp);
-            insp.add(&mut invocation_insp);
-
-            state.transit(spec, &mut insp)?;
-
-            drop(insp);
-
-            let tx_hash: String = tx.hash().cvt();
-            let creations: Vec<(String, String, bool)> = creation_insp
-                .created
-                .iter()
-                .map(|(addr, destruct)| {
-                    (ConvertTo::<String>::cvt(addr), tx_hash.clone(), *destruct)
-                })
-                .collect();
-            total_creations.extend(creations);
-
-            let invocations: Vec<String> = invocation_insp
-                .invocations
-                .iter()
-                .map(|addr| ConvertTo::<String>::cvt(addr))
-                .collect();
-            total_invocations.extend(invocations);
+            tx_envs.push(tx_env);
+            tx_hashes.push(tx.hash().cvt());
         }
+        let spec = TransitionSpec {
+            evm_version: None,
+            cfg: cfg_env,
+            block: block_env,
+            txs: tx_envs,
+            cancellation_token: self.cancellation_token.clone(),
+            ..Default::default()
+        };
+
+        let mut creation_insp = CreationInspector::default();
+        let mut invocation_insp = ExtractInvocationInspector::default();
+        let mut insp = CombinedInspector::default();
+        insp.add(&mut creation_insp);
+        insp.add(&mut invocation_insp);
+
+        state.transit(spec, &mut insp)?;
+
+        drop(insp);
+
+        let total_creations: Vec<Creation> = creation_insp
+            .records
+            .iter()
+            .map(|r| {
+                (
+                    ConvertTo::<String>::cvt(&r.address),
+                    tx_hashes[r.tx_index].clone(),
+                    r.creator.map(|c| c.cvt()),
+                    r.destruct,
+                )
+            })
+            .collect();
+        let total_invocations: HashSet<String> = invocation_insp
+            .invocations
+            .iter()
+            .map(|addr| ConvertTo::<String>::cvt(addr))
+            .collect();
         debug!(
             block = block,
             creations = total_creations.len(),
@@ -1,7 +1,14 @@
 use std::collections::{BTreeMap, HashSet};
 
+use libsofl_core::engine::types::Address;
 use libsofl_knowledge_base::entities as base_entities;
-use libsofl_knowledge_index::entities;
+use libsofl_knowledge_index::{
+    bloom::{
+        bloom_bucket, delete_bloom, load_bloom, save_bloom, AddressBloom,
+        BLOOM_BLOCK_SPAN,
+    },
+    entities,
+};
 use libsofl_utils::log::{debug, info};
 use sea_orm::{sea_query, EntityTrait};
 
@@ -37,6 +44,9 @@ pub(crate) struct DataStore<'a> {
 
 const METADATA_KEY: &str = "tx_index_progress";
 
+/// A creation or destruct: (contract, tx hash, creator, destruct).
+pub(crate) type Creation = (String, String, Option<String>, bool);
+
 impl<'a> DataStore<'a> {
     pub async fn new(
         db: &'a sea_orm::DatabaseConnection,
@@ -78,6 +88,10 @@ impl<'a> DataStore<'a> {
         self.progress.last_finished_block
     }
 
+    pub(crate) fn get_failed_blocks(&self) -> &[u64] {
+        &self.progress.failed_blocks
+    }
+
     /// Add the invoked contract list to the pending_invocations.
     /// If a previously-pending address is not invoked in the current block (the given address list),
     /// It will be flushed to the underlying database.
@@ -122,15 +136,16 @@ impl<'a> DataStore<'a> {
     pub(crate) async fn add_creations(
         &mut self,
         block: u64,
-        creations: Vec<(String, String, bool)>,
+        creations: Vec<Creation>,
     ) -> Result<(), sea_orm::DbErr> {
-        for (contract, tx, destruct) in creations {
+        for (contract, tx, creator, destruct) in creations {
             let block = block as i64;
             let creation = entities::creation::Model {
                 contract,
                 tx,
                 block,
                 destruct,
+                creator,
             };
             self.creations_to_insert.push(creation.into());
             self.flush_creations().await?;
@@ -238,6 +253,85 @@ impl<'a> DataStore<'a> {
     }
 }
 
+/// BloomStore builds the bloom filters of invoked contracts block by block,
+/// in the order of blocks.
+/// The bloom of a bucket is only kept if none of its blocks is missing,
+/// so that a negative membership test is never wrong.
+pub(crate) struct BloomStore<'a> {
+    db: &'a sea_orm::DatabaseConnection,
+
+    bucket: i64,
+    bloom: AddressBloom,
+    // whether some blocks of the bucket are missing from the bloom
+    incomplete: bool,
+}
+
+impl<'a> BloomStore<'a> {
+    /// `next_block` is the first block to add.
+    pub async fn new(
+        db: &'a sea_orm::DatabaseConnection,
+        next_block: u64,
+    ) -> Result<Self, sea_orm::DbErr> {
+        let bucket = bloom_bucket(next_block);
+        // the earlier blocks of the bucket are saved by previous runs, if any
+        let incomplete = next_block % BLOOM_BLOCK_SPAN != 0
+            && load_bloom(db, bucket).await?.is_none();
+        Ok(Self {
+            db,
+            bucket,
+            bloom: AddressBloom::default(),
+            incomplete,
+        })
+    }
+
+    pub(crate) async fn add_invocations(
+        &mut self,
+        block: u64,
+        addresses: &HashSet<String>,
+    ) -> Result<(), sea_orm::DbErr> {
+        self.switch_bucket(block).await?;
+        for address in addresses {
+            let address: Address = address.parse().expect("invalid address");
+            self.bloom.insert(&address);
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn add_failed_block(
+        &mut self,
+        block: u64,
+    ) -> Result<(), sea_orm::DbErr> {
+        self.switch_bucket(block).await?;
+        self.incomplete = true;
+        Ok(())
+    }
+
+    /// Save the bloom of the current bucket, or remove it if incomplete.
+    pub(crate) async fn flush(&mut self) -> Result<(), sea_orm::DbErr> {
+        if self.incomplete {
+            delete_bloom(self.db, self.bucket).await
+        } else {
+            save_bloom(self.db, self.bucket, &self.bloom).await
+        }
+    }
+
+    async fn switch_bucket(
+        &mut self,
+        block: u64,
+    ) -> Result<(), sea_orm::DbErr> {
+        let bucket = bloom_bucket(block);
+        if bucket == self.bucket {
+            return Ok(());
+        }
+        // move on even if the flush fails, so that the block is still added
+        let r = self.flush().await;
+        self.bucket = bucket;
+        self.bloom = AddressBloom::default();
+        self.incomplete = false;
+        r
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
@@ -317,10 +411,12 @@ mod tests {
         let connection = db.into_connection();
         let mut store = super::DataStore::new(&connection, 2).await.unwrap();
 
-        let creations = vec![("0x1".to_string(), "0x1".to_string(), false)];
+        let creations =
+            vec![("0x1".to_string(), "0x1".to_string(), None, false)];
         store.add_creations(1, creations).await.unwrap(); // should be flushed to cache
         store.update_last_finished_block(1);
-        let creations = vec![("0x2".to_string(), "0x2".to_string(), false)];
+        let creations =
+            vec![("0x2".to_string(), "0x2".to_string(), None, false)];
         store.add_creations(2, creations).await.unwrap(); // should be flushed and save to database
         store.update_last_finished_block(2);
 
@@ -353,7 +449,8 @@ mod tests {
             .append_exec_errors([]);
         let connection = db.into_connection();
         let mut store = super::DataStore::new(&connection, 2).await.unwrap();
-        let creations = vec![("0x1".to_string(), "0x1".to_string(), false)];
+        let creations =
+            vec![("0x1".to_string(), "0x1".to_string(), None, false)];
         store.add_creations(1, creations).await.unwrap(); // should be flushed to cache
         store.add_failed_block(0);
         store.update_last_finished_block(1);
@@ -362,4 +459,36 @@ mod tests {
         let logs = connection.into_transaction_log();
         assert_eq!(logs.len(), 3); // three queries: check metadata, insert creation, update metadata.
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bloom_skips_incomplete_buckets() {
+        use libsofl_core::{conversion::ConvertTo, engine::types::Address};
+        use libsofl_knowledge_index::{
+            bloom::{load_bloom, maybe_invoked},
+            testing::setup_test_db,
+        };
+
+        let db = setup_test_db().await;
+        let address: Address = 0x1234.cvt();
+        let addresses: HashSet<String> = [address.to_string()].into();
+
+        // starting in the middle of bucket 0 which has no bloom
+        let mut store = super::BloomStore::new(&db, 500).await.unwrap();
+        store.add_invocations(500, &addresses).await.unwrap();
+        // bucket 1 is complete, while block 2500 of bucket 2 fails
+        store.add_invocations(1000, &addresses).await.unwrap();
+        store.add_failed_block(2500).await.unwrap();
+        store.add_invocations(3000, &HashSet::new()).await.unwrap();
+        store.flush().await.unwrap();
+
+        assert!(load_bloom(&db, 0).await.unwrap().is_none());
+        assert!(load_bloom(&db, 1).await.unwrap().is_some());
+        assert!(load_bloom(&db, 2).await.unwrap().is_none());
+        assert!(maybe_invoked(&db, address, 1999).await.unwrap());
+        assert!(!maybe_invoked(&db, address, 3000).await.unwrap());
+
+        // resuming in the middle of bucket 3 which has a bloom
+        let store = super::BloomStore::new(&db, 3001).await.unwrap();
+        assert!(!store.incomplete);
+    }
 }
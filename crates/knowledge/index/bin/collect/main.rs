@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
-use clap::Parser;
-use data::DataStore;
+use clap::{Parser, Subcommand};
+use data::{BloomStore, DataStore};
 use futures::stream::StreamExt;
 use indicatif::ProgressStyle;
 use libsofl_core::error::SoflError;
@@ -22,9 +22,10 @@ use tracing_subscriber::{
 
 pub mod analyze;
 pub mod data;
+pub mod verify;
 
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(author, version, about, subcommand_negates_reqs = true)]
 struct Arg {
     #[arg(short, long, default_value = "info")]
     level: String,
@@ -32,11 +33,30 @@ struct Arg {
     #[arg(short, long, default_value = "8")]
     jobs: usize,
 
-    #[arg(help = "until block number (exclusive)")]
-    until_block: u64,
+    #[arg(required = true, help = "until block number (exclusive)")]
+    until_block: Option<u64>,
 
     #[arg(short, long, default_value = "100")]
     db_flush_threshold: u64,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Report the indexed blocks that have no creation or invocation rows
+    /// and are not marked as failed.
+    Verify {
+        #[arg(long, default_value = "1", help = "from block number")]
+        from: u64,
+
+        #[arg(
+            long,
+            help = "until block number (exclusive), defaults to the last finished block"
+        )]
+        until: Option<u64>,
+    },
 }
 
 #[tokio::main(worker_threads = 32)]
@@ -58,10 +78,13 @@ async fn main() {
         .with(indicatif_layer)
         .init();
 
-    info!(
-        until = args.until_block,
-        "start indexing transaction hisotry"
-    );
+    if let Some(Command::Verify { from, until }) = args.command {
+        verify_blocks(from, until).await;
+        return;
+    }
+    let until_block = args.until_block.expect("until block is required");
+
+    info!(until = until_block, "start indexing transaction hisotry");
 
     let cancellation_token = CancellationToken::new();
 
@@ -84,7 +107,7 @@ async fn main() {
 
     let task_tracker = TaskTracker::new();
     collect_blocks(
-        args.until_block,
+        until_block,
         args.jobs,
         cancellation_token.clone(),
         &task_tracker,
@@ -113,10 +136,12 @@ async fn collect_blocks(
     let provider = cfg.bc_provider().unwrap();
     info!(datadir = cfg.datadir, "reth blockchain provider connected");
     let provider = Arc::new(provider);
-    let analyzer = analyze::Analyzer::new(provider);
+    let analyzer = analyze::Analyzer::new(provider)
+        .with_cancellation_token(cancellation_token.clone());
     let mut store = DataStore::new(&db, db_flush_threshold).await.unwrap();
 
     let range = (store.get_last_finished_block() + 1)..until_block;
+    let mut blooms = BloomStore::new(&db, range.start).await.unwrap();
 
     let progress_span = info_span!("tx-index");
     progress_span.pb_set_style(&ProgressStyle::default_bar());
@@ -146,6 +171,14 @@ async fn collect_blocks(
             let task = tasks.remove(0);
             let _ = match task.await.unwrap() {
                 Ok((creations, invocations)) => {
+                    let r = blooms.add_invocations(bn, &invocations).await;
+                    if let Err(e) = r {
+                        error!(
+                            err = format!("{:?}", e),
+                            block = bn,
+                            "failed to add invocations to bloom"
+                        );
+                    }
                     let r =
                         store.add_creations(bn, creations).await.or_else(|e| {
                             if e == DbErr::RecordNotInserted {
@@ -189,14 +222,67 @@ async fn collect_blocks(
                         "failed to analyzed block"
                     );
                     store.add_failed_block(bn);
+                    if let Err(e) = blooms.add_failed_block(bn).await {
+                        error!(
+                            err = format!("{:?}", e),
+                            block = bn,
+                            "failed to add failed block to bloom"
+                        );
+                    }
                 }
             };
             store.update_last_finished_block(bn);
             Span::current().pb_inc(1);
         }
     }
+    blooms.flush().await.unwrap();
     store.save_progress().await.unwrap();
 
     drop(header_span_enter);
     drop(progress_span);
 }
+
+async fn verify_blocks(from: u64, until: Option<u64>) {
+    let cfg = KnowledgeConfig::load_or(Default::default())
+        .expect("failed to load config");
+    let db = cfg.get_database_connection().await.unwrap();
+    info!(url = cfg.database_url, "database connected");
+    let cfg = RethConfig::must_load();
+    let provider = cfg.bc_provider().unwrap();
+    info!(datadir = cfg.datadir, "reth blockchain provider connected");
+    let mut analyzer = analyze::Analyzer::new(Arc::new(provider));
+    // the store is only used to read the progress
+    let store = DataStore::new(&db, 0).await.unwrap();
+
+    let last_finished = store.get_last_finished_block();
+    let until = until.unwrap_or(last_finished + 1).min(last_finished + 1);
+    info!(from = from, until = until, "verifying indexed blocks");
+
+    // re-analyze uncovered blocks in memory to tell whether they are missing
+    let has_activity = |bn: u64| match tokio::task::block_in_place(|| {
+        analyzer.analyze_one_block(bn)
+    }) {
+        Ok((creations, invocations)) => {
+            !creations.is_empty() || !invocations.is_empty()
+        }
+        Err(e) => {
+            warn!(
+                err = format!("{:?}", e),
+                block = bn,
+                "failed to analyze block, treated as missing"
+            );
+            true
+        }
+    };
+    let failed_blocks = store.get_failed_blocks();
+    let gaps = verify::find_gaps(&db, from..until, failed_blocks, has_activity)
+        .await
+        .unwrap();
+
+    if gaps.is_empty() {
+        info!("no missing block found");
+    }
+    for gap in gaps {
+        println!("{}-{}", gap.start(), gap.end());
+    }
+}
@@ -0,0 +1,130 @@
+use std::ops::{Range, RangeInclusive};
+
+use libsofl_knowledge_index::entities;
+use libsofl_utils::log::debug;
+use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter};
+
+/// Number of blocks whose rows are loaded from the database at a time.
+const CHUNK_SIZE: u64 = 10000;
+
+/// Find the blocks in `range` that are not covered by any creation or invocation row
+/// and are not marked as failed.
+/// Blocks without any rows are legitimate if nothing is created or invoked in them,
+/// so `has_activity` is asked for each uncovered block,
+/// and only the blocks with activity are reported.
+/// The database is only read.
+/// Consecutive missing blocks are merged into one range.
+pub(crate) async fn find_gaps<F>(
+    db: &sea_orm::DatabaseConnection,
+    range: Range<u64>,
+    failed_blocks: &[u64],
+    mut has_activity: F,
+) -> Result<Vec<RangeInclusive<u64>>, DbErr>
+where
+    F: FnMut(u64) -> bool,
+{
+    let mut missing = Vec::new();
+    let mut start = range.start;
+    while start < range.end {
+        let end = (start + CHUNK_SIZE).min(range.end);
+        let mut covered = vec![false; (end - start) as usize];
+        let mut cover = |from: i64, to: i64| {
+            let from = (from as u64).max(start);
+            let to = (to as u64).min(end - 1);
+            for bn in from..=to {
+                covered[(bn - start) as usize] = true;
+            }
+        };
+
+        let invocations = entities::invocation::Entity::find()
+            .filter(entities::invocation::Column::FromBlock.lt(end as i64))
+            .filter(entities::invocation::Column::ToBlock.gte(start as i64))
+            .all(db)
+            .await?;
+        for invocation in invocations {
+            cover(invocation.from_block, invocation.to_block);
+        }
+        let creations = entities::creation::Entity::find()
+            .filter(entities::creation::Column::Block.gte(start as i64))
+            .filter(entities::creation::Column::Block.lt(end as i64))
+            .all(db)
+            .await?;
+        for creation in creations {
+            cover(creation.block, creation.block);
+        }
+
+        for (offset, covered) in covered.into_iter().enumerate() {
+            let bn = start + offset as u64;
+            if covered || failed_blocks.contains(&bn) {
+                continue;
+            }
+            if has_activity(bn) {
+                missing.push(bn);
+            }
+        }
+        debug!(from = start, to = end, "blocks verified");
+        start = end;
+    }
+    Ok(merge_ranges(missing))
+}
+
+/// Merge the sorted block numbers into ranges of consecutive blocks.
+fn merge_ranges(blocks: Vec<u64>) -> Vec<RangeInclusive<u64>> {
+    let mut ranges: Vec<RangeInclusive<u64>> = Vec::new();
+    for bn in blocks {
+        match ranges.last_mut() {
+            Some(last) if *last.end() + 1 == bn => {
+                *last = *last.start()..=bn;
+            }
+            _ => ranges.push(bn..=bn),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_knowledge_index::{entities, testing::setup_test_db};
+    use sea_orm::{ActiveModelTrait, IntoActiveModel};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_find_gaps() {
+        let db = setup_test_db().await;
+        let invocation = |contract: &str, from_block, to_block| {
+            entities::invocation::Model {
+                contract: contract.to_string(),
+                from_block,
+                to_block,
+            }
+            .into_active_model()
+        };
+        invocation("0x1", 1, 3).insert(&db).await.unwrap();
+        invocation("0x2", 9, 9).insert(&db).await.unwrap();
+        entities::creation::Model {
+            contract: "0x3".to_string(),
+            tx: "0x4".to_string(),
+            block: 5,
+            destruct: false,
+            creator: None,
+        }
+        .into_active_model()
+        .insert(&db)
+        .await
+        .unwrap();
+
+        // 4 and 8 are missing, 6 is failed, 7 has no activity,
+        // and 10..=12 are missing.
+        let mut asked = Vec::new();
+        let gaps = super::find_gaps(&db, 1..13, &[6], |bn| {
+            asked.push(bn);
+            bn != 7
+        })
+        .await
+        .unwrap();
+        assert_eq!(gaps, vec![4..=4, 8..=8, 10..=12]);
+        assert_eq!(asked, vec![4, 7, 8, 10, 11, 12]);
+
+        let gaps = super::find_gaps(&db, 1..4, &[], |_| true).await.unwrap();
+        assert!(gaps.is_empty());
+    }
+}
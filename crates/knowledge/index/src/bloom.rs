@@ -0,0 +1,176 @@
+use libsofl_core::engine::types::{keccak256, Address};
+use sea_orm::{
+    sea_query::OnConflict, ConnectionTrait, DbErr, EntityTrait, IntoActiveModel,
+};
+
+use crate::entities::invocation_bloom;
+
+/// The number of blocks covered by a bloom filter.
+pub const BLOOM_BLOCK_SPAN: u64 = 1000;
+/// The size of a bloom filter in bytes.
+pub const BLOOM_BYTES: usize = 1 << 15;
+/// The number of bits set per address.
+const BLOOM_HASHES: usize = 3;
+
+/// The bucket of the bloom filter covering the block.
+pub fn bloom_bucket(block: u64) -> i64 {
+    (block / BLOOM_BLOCK_SPAN) as i64
+}
+
+/// AddressBloom is a bloom filter of addresses.
+/// A negative membership test is definite, while a positive one may be false.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressBloom(Vec<u8>);
+
+impl Default for AddressBloom {
+    fn default() -> Self {
+        Self(vec![0; BLOOM_BYTES])
+    }
+}
+
+impl AddressBloom {
+    /// Panics if the length of `bytes` is not `BLOOM_BYTES`.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        assert_eq!(bytes.len(), BLOOM_BYTES, "invalid bloom size");
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn insert(&mut self, address: &Address) {
+        for bit in Self::bits(address) {
+            self.0[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    pub fn maybe_contains(&self, address: &Address) -> bool {
+        Self::bits(address).all(|bit| self.0[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    /// Add all addresses in `other` to this bloom.
+    pub fn merge(&mut self, other: &AddressBloom) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a |= *b;
+        }
+    }
+
+    fn bits(address: &Address) -> impl Iterator<Item = usize> {
+        let hash = keccak256(address);
+        (0..BLOOM_HASHES).map(move |i| {
+            let chunk: [u8; 4] = hash[i * 4..i * 4 + 4].try_into().unwrap();
+            u32::from_be_bytes(chunk) as usize % (BLOOM_BYTES * 8)
+        })
+    }
+}
+
+/// Merge `bloom` into the persisted bloom filter of the bucket.
+pub async fn save_bloom<C: ConnectionTrait>(
+    db: &C,
+    bucket: i64,
+    bloom: &AddressBloom,
+) -> Result<(), DbErr> {
+    let mut merged = bloom.clone();
+    if let Some(existing) = load_bloom(db, bucket).await? {
+        merged.merge(&existing);
+    }
+    let model = invocation_bloom::Model {
+        bucket,
+        bloom: merged.0,
+    };
+    invocation_bloom::Entity::insert(model.into_active_model())
+        .on_conflict(
+            OnConflict::column(invocation_bloom::Column::Bucket)
+                .update_column(invocation_bloom::Column::Bloom)
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+pub async fn load_bloom<C: ConnectionTrait>(
+    db: &C,
+    bucket: i64,
+) -> Result<Option<AddressBloom>, DbErr> {
+    let model = invocation_bloom::Entity::find_by_id(bucket).one(db).await?;
+    Ok(model.map(|m| AddressBloom::from_bytes(m.bloom)))
+}
+
+/// Remove the bloom filter of the bucket, e.g., if some blocks are missing.
+pub async fn delete_bloom<C: ConnectionTrait>(
+    db: &C,
+    bucket: i64,
+) -> Result<(), DbErr> {
+    invocation_bloom::Entity::delete_by_id(bucket)
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Whether the contract may be invoked in the span of blocks containing
+/// `block`. False only if it is definitely not invoked,
+/// so the invocation table only needs to be queried if true.
+/// Spans without a bloom filter (e.g., not indexed yet) are always true.
+pub async fn maybe_invoked<C: ConnectionTrait>(
+    db: &C,
+    address: Address,
+    block: u64,
+) -> Result<bool, DbErr> {
+    let bloom = load_bloom(db, bloom_bucket(block)).await?;
+    Ok(bloom.map_or(true, |b| b.maybe_contains(&address)))
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::engine::types::{keccak256, Address};
+
+    use crate::testing::setup_test_db;
+
+    use super::{bloom_bucket, maybe_invoked, save_bloom, AddressBloom};
+
+    fn seeded_addresses(seed: u64, n: u64) -> Vec<Address> {
+        (0..n)
+            .map(|i| {
+                let preimage = [seed.to_be_bytes(), i.to_be_bytes()].concat();
+                Address::from_word(keccak256(preimage))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_no_false_negative() {
+        let members = seeded_addresses(1, 10000);
+        let mut bloom = AddressBloom::default();
+        members.iter().for_each(|a| bloom.insert(a));
+        assert!(members.iter().all(|a| bloom.maybe_contains(a)));
+
+        // most non-members are ruled out
+        let others = seeded_addresses(2, 10000);
+        let positives = others.iter().filter(|a| bloom.maybe_contains(a));
+        assert!(positives.count() < 50);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_maybe_invoked() {
+        let db = setup_test_db().await;
+        let addresses = seeded_addresses(3, 200);
+        let (first, second) = addresses.split_at(100);
+
+        // the bloom of a bucket is saved in two runs
+        let bucket = bloom_bucket(17000000);
+        for part in [first, second] {
+            let mut bloom = AddressBloom::default();
+            part.iter().for_each(|a| bloom.insert(a));
+            save_bloom(&db, bucket, &bloom).await.unwrap();
+        }
+        for address in addresses.iter() {
+            assert!(maybe_invoked(&db, *address, 17000999).await.unwrap());
+        }
+        let other = seeded_addresses(4, 1)[0];
+        assert!(!maybe_invoked(&db, other, 17000000).await.unwrap());
+        // no bloom for the next bucket
+        assert!(maybe_invoked(&db, other, 17001000).await.unwrap());
+    }
+}
@@ -9,6 +9,9 @@ pub struct Model {
     pub tx: String, // tx hash of the transaction that creates or destroys the contract
     pub block: i64,     // the block number of the transaction
     pub destruct: bool, // whether the contract is created or destroyed in this transaction
+    /// The account that creates the contract (the sender of CREATE/CREATE2),
+    /// None for rows indexed before it is recorded.
+    pub creator: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,17 @@
+use sea_orm::entity::prelude::*;
+
+/// The bloom filter of the contracts invoked in a span of blocks,
+/// see `crate::bloom`.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "invocation_bloom")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub bucket: i64, // the block number divided by BLOOM_BLOCK_SPAN
+    pub bloom: Vec<u8>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {}
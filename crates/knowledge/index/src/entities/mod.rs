@@ -1,2 +1,3 @@
 pub mod creation;
 pub mod invocation;
+pub mod invocation_bloom;
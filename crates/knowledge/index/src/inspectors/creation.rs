@@ -0,0 +1,173 @@
+use libsofl_core::engine::{
+    inspector::EvmInspector,
+    state::BcState,
+    types::{
+        keccak256, Address, CreateInputs, CreateOutcome, EvmContext, Hash,
+        Inspector, TxEnv, U256,
+    },
+};
+
+/// A contract creation or destruct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreationRecord {
+    pub address: Address,
+    /// The account that creates the contract.
+    /// For destructs, None if the contract is not created by earlier transactions in the same execution.
+    pub creator: Option<Address>,
+    /// The hash of the init code, None in the same case as `creator`.
+    pub init_code_hash: Option<Hash>,
+    /// The index of the transaction in the execution, e.g., in the block.
+    pub tx_index: usize,
+    pub destruct: bool,
+}
+
+/// CreationInspector records every successful contract creation and every destruct
+/// in the order they happen, across all transactions of an execution.
+#[derive(Debug, Clone, Default)]
+pub struct CreationInspector {
+    pub records: Vec<CreationRecord>,
+
+    // the number of transactions seen
+    txs: usize,
+}
+
+impl CreationInspector {
+    fn tx_index(&self) -> usize {
+        self.txs.saturating_sub(1)
+    }
+
+    pub fn creations(&self) -> impl Iterator<Item = &CreationRecord> {
+        self.records.iter().filter(|r| !r.destruct)
+    }
+
+    pub fn destructs(&self) -> impl Iterator<Item = &CreationRecord> {
+        self.records.iter().filter(|r| r.destruct)
+    }
+}
+
+impl<BS: BcState> Inspector<BS> for CreationInspector {
+    fn create_end(
+        &mut self,
+        _context: &mut EvmContext<BS>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if let (true, Some(address)) =
+            (outcome.result.result.is_ok(), outcome.address)
+        {
+            self.records.push(CreationRecord {
+                address,
+                creator: Some(inputs.caller),
+                init_code_hash: Some(keccak256(&inputs.init_code)),
+                tx_index: self.tx_index(),
+                destruct: false,
+            });
+        }
+        outcome
+    }
+
+    fn selfdestruct(
+        &mut self,
+        contract: Address,
+        _target: Address,
+        _value: U256,
+    ) {
+        let created = self
+            .records
+            .iter()
+            .rev()
+            .find(|r| r.address == contract && !r.destruct);
+        self.records.push(CreationRecord {
+            address: contract,
+            creator: created.and_then(|r| r.creator),
+            init_code_hash: created.and_then(|r| r.init_code_hash),
+            tx_index: self.tx_index(),
+            destruct: true,
+        });
+    }
+}
+
+impl<BS: BcState> EvmInspector<BS> for CreationInspector {
+    fn transaction(&mut self, _tx: &TxEnv, _state: &BS) -> bool {
+        self.txs += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            memory::MemoryBcState,
+            state::BcState,
+            transition::TransitionSpecBuilder,
+            types::{
+                keccak256, Address, CreateScheme, SpecId, TransactTo, TxEnv,
+            },
+        },
+        solidity::scripting::compile_solidity,
+    };
+
+    use super::CreationInspector;
+
+    #[test]
+    fn test_factory_creation_in_block() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Child {
+                function destroy() public {
+                    selfdestruct(payable(msg.sender));
+                }
+            }
+            contract Factory {
+                function deploy() public returns (Child c) {
+                    c = new Child();
+                    c.destroy();
+                }
+            }
+        "#;
+        let mut contracts = compile_solidity("0.8.12", code).unwrap();
+        let (_, factory_code) = contracts
+            .drain(..)
+            .find(|(name, _)| name == "Factory")
+            .unwrap();
+
+        let sender: Address = 0x1234.cvt();
+        let mut deploy = TxEnv::default();
+        deploy.caller = sender;
+        deploy.transact_to = TransactTo::Create(CreateScheme::Create);
+        deploy.data = factory_code;
+        let factory = sender.create(0);
+        let mut call = TxEnv::default();
+        call.caller = sender;
+        call.transact_to = TransactTo::Call(factory);
+        call.data = keccak256("deploy()")[..4].to_vec().cvt();
+        let spec = TransitionSpecBuilder::new()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .append_tx_env(deploy)
+            .append_tx_env(call)
+            .build()
+            .unwrap();
+
+        let mut inspector = CreationInspector::default();
+        state.transit(spec, &mut inspector).unwrap();
+
+        let creations: Vec<_> = inspector.creations().collect();
+        assert_eq!(creations.len(), 2);
+        assert_eq!(creations[0].address, factory);
+        assert_eq!(creations[0].creator, Some(sender));
+        assert_eq!(creations[0].tx_index, 0);
+        let child = factory.create(1);
+        assert_eq!(creations[1].address, child);
+        assert_eq!(creations[1].creator, Some(factory));
+        assert_eq!(creations[1].tx_index, 1);
+
+        let destructs: Vec<_> = inspector.destructs().collect();
+        assert_eq!(destructs.len(), 1);
+        assert_eq!(destructs[0].address, child);
+        assert_eq!(destructs[0].creator, Some(factory));
+        assert_eq!(destructs[0].init_code_hash, creations[1].init_code_hash);
+    }
+}
@@ -1,2 +1,2 @@
-pub mod extract_creation;
+pub mod creation;
 pub mod extract_invocation;
@@ -1,3 +1,4 @@
+pub mod bloom;
 pub mod config;
 pub mod entities;
 pub mod inspectors;
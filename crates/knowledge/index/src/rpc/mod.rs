@@ -1,47 +1,111 @@
 pub mod service;
 
-use std::sync::Arc;
+use std::{fmt::Display, sync::Arc};
 
 use jsonrpsee::{core::async_trait, proc_macros::rpc};
-use libsofl_core::engine::types::{Address, TxHash};
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use libsofl_core::{
+    engine::types::{Address, TxHash},
+    error::SoflError,
+};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QueryOrder,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Error {
     NotFound(String),
+    InvalidAddress(String),
+    Database(String),
+    Provider(String),
     Internal(String),
 }
 
+impl From<DbErr> for Error {
+    fn from(value: DbErr) -> Self {
+        Error::Database(value.to_string())
+    }
+}
+
+impl From<SoflError> for Error {
+    fn from(value: SoflError) -> Self {
+        Error::Provider(value.to_string())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NotFound(msg) => write!(f, "Not found: {}", msg),
+            Error::InvalidAddress(addr) => {
+                write!(f, "Invalid address: {}", addr)
+            }
+            Error::Database(msg) => write!(f, "Database error: {}", msg),
+            Error::Provider(msg) => write!(f, "Provider error: {}", msg),
+            Error::Internal(msg) => write!(f, "Internal error: {}", msg),
+        }
+    }
+}
+
 impl From<Error> for jsonrpsee::types::ErrorObject<'static> {
     fn from(value: Error) -> Self {
-        match value {
-            Error::Internal(msg) => jsonrpsee::types::ErrorObject::owned(
-                jsonrpsee::types::error::INTERNAL_ERROR_CODE,
-                jsonrpsee::types::error::INTERNAL_ERROR_MSG,
-                Some(msg),
-            ),
-            Error::NotFound(msg) => jsonrpsee::types::ErrorObject::owned(
-                jsonrpsee::types::error::INVALID_PARAMS_CODE,
-                jsonrpsee::types::error::INVALID_PARAMS_MSG,
-                Some(msg),
-            ),
-        }
+        use jsonrpsee::types::error::*;
+        let (code, msg) = match &value {
+            Error::NotFound(_) | Error::InvalidAddress(_) => {
+                (INVALID_PARAMS_CODE, INVALID_PARAMS_MSG)
+            }
+            Error::Provider(_) => {
+                (CALL_EXECUTION_FAILED_CODE, CALL_EXECUTION_FAILED_MSG)
+            }
+            Error::Database(_) | Error::Internal(_) => {
+                (INTERNAL_ERROR_CODE, INTERNAL_ERROR_MSG)
+            }
+        };
+        jsonrpsee::types::ErrorObject::owned(code, msg, Some(value.to_string()))
     }
 }
 
+/// Parse a hex address given as an RPC parameter.
+pub fn parse_address(address: &str) -> Result<Address, Error> {
+    address
+        .parse()
+        .map_err(|_| Error::InvalidAddress(address.to_string()))
+}
+
 #[rpc(client, server, namespace = "kb")]
 pub trait IndexRpc {
     #[method(name = "creation")]
     async fn creation(
         &self,
-        contract: Address,
+        contract: String,
     ) -> Result<Vec<(TxHash, i64, bool)>, Error>;
 
     #[method(name = "invoked_blocks")]
     async fn invoked_blocks(
         &self,
-        address: Address,
+        address: String,
     ) -> Result<Vec<(i64, i64)>, Error>;
+
+    /// Whether the contract may be invoked around `block`,
+    /// false only if it is definitely not invoked (see `crate::bloom`).
+    #[method(name = "maybe_invoked")]
+    async fn maybe_invoked(
+        &self,
+        address: String,
+        block: u64,
+    ) -> Result<bool, Error>;
+
+    /// The contracts created by `creator` in blocks `from_block..to_block`,
+    /// as (contract, tx hash, block number) sorted by block number.
+    #[method(name = "contracts_by_creator")]
+    async fn contracts_by_creator(
+        &self,
+        creator: String,
+        from_block: i64,
+        to_block: i64,
+    ) -> Result<Vec<(Address, TxHash, i64)>, Error>;
 }
 
 pub struct IndexRpcImpl {
@@ -52,19 +116,21 @@ pub struct IndexRpcImpl {
 impl IndexRpcServer for IndexRpcImpl {
     async fn creation(
         &self,
-        contract: Address,
+        contract: String,
     ) -> Result<Vec<(TxHash, i64, bool)>, Error> {
+        let contract = parse_address(&contract)?;
         let models = crate::entities::creation::Entity::find()
             .filter(
                 crate::entities::creation::Column::Contract
                     .eq(contract.to_string()),
             )
             .all(self.db.as_ref())
-            .await
-            .map_err(|err| Error::Internal(err.to_string()))?;
+            .await?;
         let mut rs = vec![];
         for model in models {
-            let tx: TxHash = model.tx.parse().expect("failed to parse tx hash");
+            let tx: TxHash = model.tx.parse().map_err(|_| {
+                Error::Internal(format!("invalid tx hash: {}", model.tx))
+            })?;
             let bn = model.block;
             let is_creation = model.destruct;
             rs.push((tx, bn, is_creation));
@@ -76,16 +142,16 @@ impl IndexRpcServer for IndexRpcImpl {
 
     async fn invoked_blocks(
         &self,
-        contract: Address,
+        contract: String,
     ) -> Result<Vec<(i64, i64)>, Error> {
+        let contract = parse_address(&contract)?;
         let models = crate::entities::invocation::Entity::find()
             .filter(
                 crate::entities::invocation::Column::Contract
                     .eq(contract.to_string()),
             )
             .all(self.db.as_ref())
-            .await
-            .map_err(|err| Error::Internal(err.to_string()))?;
+            .await?;
         let mut rs = vec![];
         for model in models {
             let from_bn = model.from_block;
@@ -96,4 +162,114 @@ impl IndexRpcServer for IndexRpcImpl {
         rs.sort_by(|a, b| a.0.cmp(&b.0));
         Ok(rs)
     }
+
+    async fn maybe_invoked(
+        &self,
+        address: String,
+        block: u64,
+    ) -> Result<bool, Error> {
+        let address = parse_address(&address)?;
+        let db = self.db.as_ref();
+        Ok(crate::bloom::maybe_invoked(db, address, block).await?)
+    }
+
+    async fn contracts_by_creator(
+        &self,
+        creator: String,
+        from_block: i64,
+        to_block: i64,
+    ) -> Result<Vec<(Address, TxHash, i64)>, Error> {
+        use crate::entities::creation::{Column, Entity};
+
+        let creator = parse_address(&creator)?;
+        let models = Entity::find()
+            .filter(Column::Creator.eq(creator.to_string()))
+            .filter(Column::Destruct.eq(false))
+            .filter(Column::Block.gte(from_block))
+            .filter(Column::Block.lt(to_block))
+            .order_by_asc(Column::Block)
+            .all(self.db.as_ref())
+            .await?;
+        let mut rs = vec![];
+        for model in models {
+            let contract = model.contract.parse().map_err(|_| {
+                Error::Internal(format!("invalid address: {}", model.contract))
+            })?;
+            let tx = model.tx.parse().map_err(|_| {
+                Error::Internal(format!("invalid tx hash: {}", model.tx))
+            })?;
+            rs.push((contract, tx, model.block));
+        }
+        Ok(rs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use jsonrpsee::types::{error::INVALID_PARAMS_CODE, ErrorObject};
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::types::{Address, TxHash},
+    };
+    use sea_orm::{ActiveModelTrait, IntoActiveModel};
+
+    use crate::{entities, testing::setup_test_db};
+
+    use super::{parse_address, Error, IndexRpcImpl, IndexRpcServer};
+
+    #[test]
+    fn test_malformed_address() {
+        let err = parse_address("0x1234").unwrap_err();
+        assert_eq!(err, Error::InvalidAddress("0x1234".to_string()));
+        let obj: ErrorObject<'static> = err.into();
+        assert_eq!(obj.code(), INVALID_PARAMS_CODE);
+
+        assert!(
+            parse_address("0x5df9b87991262f6ba471f09758cde1c0fc1de734").is_ok()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_contracts_by_creator() {
+        let db = setup_test_db().await;
+        let deployer: Address = 0x1000.cvt();
+        let factory: Address = 0x2000.cvt();
+        let contract = |n: usize| -> Address { n.cvt() };
+        let rows = [
+            (contract(1), deployer, 10, false),
+            (contract(2), factory, 11, false),
+            (contract(3), deployer, 12, false),
+            // destructs and creations out of range are excluded
+            (contract(1), deployer, 13, true),
+            (contract(4), deployer, 20, false),
+        ];
+        for (i, (addr, creator, block, destruct)) in
+            rows.into_iter().enumerate()
+        {
+            entities::creation::Model {
+                contract: addr.to_string(),
+                tx: TxHash::with_last_byte(i as u8).cvt(),
+                block,
+                destruct,
+                creator: Some(creator.to_string()),
+            }
+            .into_active_model()
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+
+        let rpc = IndexRpcImpl { db: Arc::new(db) };
+        let rs = rpc
+            .contracts_by_creator(deployer.to_string(), 10, 20)
+            .await
+            .unwrap();
+        let expected = vec![
+            (contract(1), TxHash::with_last_byte(0), 10),
+            (contract(3), TxHash::with_last_byte(2), 12),
+        ];
+        assert_eq!(rs, expected);
+    }
 }
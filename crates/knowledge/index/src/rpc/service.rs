@@ -2,7 +2,9 @@ use eyre::Result;
 use std::sync::Arc;
 
 use jsonrpsee::core::async_trait;
-use libsofl_knowledge_base::service::KnowledgeService;
+use libsofl_knowledge_base::{
+    metrics::ServiceMetrics, service::KnowledgeService,
+};
 use sea_orm::DatabaseConnection;
 
 use super::IndexRpcServer;
@@ -17,7 +19,7 @@ impl KnowledgeService for IndexService {
         "index"
     }
 
-    async fn start(&mut self) -> Result<()> {
+    async fn start(&mut self, _metrics: ServiceMetrics) -> Result<()> {
         Ok(())
     }
 
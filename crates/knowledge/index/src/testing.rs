@@ -20,6 +20,11 @@ pub async fn setup_test_db() -> DatabaseConnection {
         .await
         .unwrap();
     let sql = schema.create_table_from_entity(entities::invocation::Entity);
+    db.execute(db.get_database_backend().build(&sql))
+        .await
+        .unwrap();
+    let sql =
+        schema.create_table_from_entity(entities::invocation_bloom::Entity);
     db.execute(db.get_database_backend().build(&sql))
         .await
         .unwrap();
@@ -0,0 +1,51 @@
+use libsofl_knowledge_index::entities::{creation, invocation};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+pub const CREATION_CONTRACT_INDEX: &str = "idx_creation_contract";
+pub const INVOCATION_CONTRACT_INDEX: &str = "idx_invocation_contract";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name(CREATION_CONTRACT_INDEX)
+                    .table(creation::Entity)
+                    .col(creation::Column::Contract)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name(INVOCATION_CONTRACT_INDEX)
+                    .table(invocation::Entity)
+                    .col(invocation::Column::Contract)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(INVOCATION_CONTRACT_INDEX)
+                    .table(invocation::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(CREATION_CONTRACT_INDEX)
+                    .table(creation::Entity)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,57 @@
+use libsofl_knowledge_index::entities::creation;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+pub const CREATION_CREATOR_INDEX: &str = "idx_creation_creator";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // the column already exists if the table is created from the entity
+        // by this round of migrations
+        if !manager.has_column("creation", "creator").await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(creation::Entity)
+                        .add_column(
+                            ColumnDef::new(creation::Column::Creator)
+                                .string()
+                                .null(),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .create_index(
+                Index::create()
+                    .name(CREATION_CREATOR_INDEX)
+                    .table(creation::Entity)
+                    .col(creation::Column::Creator)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(CREATION_CREATOR_INDEX)
+                    .table(creation::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(creation::Entity)
+                    .drop_column(creation::Column::Creator)
+                    .to_owned(),
+            )
+            .await
+    }
+}
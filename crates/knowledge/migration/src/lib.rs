@@ -0,0 +1,110 @@
+mod contract_index;
+mod create_metadata;
+mod creation_creator;
+mod invocation_bloom;
+mod source_code;
+mod tx_index;
+
+pub use sea_orm_migration::prelude::*;
+
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(create_metadata::Migration),
+            Box::new(tx_index::Migration),
+            Box::new(source_code::Migration),
+            Box::new(contract_index::Migration),
+            Box::new(creation_creator::Migration),
+            Box::new(invocation_bloom::Migration),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests_nodep {
+    use libsofl_knowledge_index::entities::creation;
+    use sea_orm::{
+        ActiveValue, ColumnTrait, Database, DatabaseConnection, EntityTrait,
+        QueryFilter,
+    };
+    use sea_orm_migration::prelude::*;
+
+    use crate::{
+        contract_index::{CREATION_CONTRACT_INDEX, INVOCATION_CONTRACT_INDEX},
+        creation_creator::CREATION_CREATOR_INDEX,
+        Migrator,
+    };
+
+    /// Run the full migrator up and down,
+    /// checking that the schema works the same on the backend.
+    pub(crate) async fn check_migrations(db: &DatabaseConnection) {
+        Migrator::up(db, None).await.unwrap();
+        let manager = SchemaManager::new(db);
+        for table in [
+            "metadata",
+            "creation",
+            "invocation",
+            "invocation_bloom",
+            "code",
+        ] {
+            assert!(manager.has_table(table).await.unwrap(), "{}", table);
+        }
+        for (table, index) in [
+            ("creation", CREATION_CONTRACT_INDEX),
+            ("invocation", INVOCATION_CONTRACT_INDEX),
+            ("creation", CREATION_CREATOR_INDEX),
+        ] {
+            let has_index = manager.has_index(table, index).await.unwrap();
+            assert!(has_index, "{}", index);
+        }
+
+        // addresses and hashes are stored as strings,
+        // and block numbers as 64-bit integers
+        let contract = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+        let model = creation::ActiveModel {
+            contract: ActiveValue::Set(contract.to_string()),
+            tx: ActiveValue::Set(format!("0x{}", "ab".repeat(32))),
+            block: ActiveValue::Set(i64::MAX),
+            destruct: ActiveValue::Set(false),
+            creator: ActiveValue::Set(None),
+        };
+        creation::Entity::insert(model).exec(db).await.unwrap();
+        let found = creation::Entity::find()
+            .filter(creation::Column::Contract.eq(contract))
+            .one(db)
+            .await
+            .unwrap()
+            .expect("creation not found");
+        assert_eq!(found.block, i64::MAX);
+        assert_eq!(found.tx.len(), 66);
+
+        Migrator::down(db, None).await.unwrap();
+        assert!(!manager.has_table("creation").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_sqlite() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        check_migrations(&db).await;
+    }
+}
+
+#[cfg(test)]
+mod tests_with_dep {
+    use sea_orm::Database;
+
+    use crate::tests_nodep::check_migrations;
+
+    /// Requires a disposable PostgreSQL database, e.g., a test container,
+    /// whose url is given by `TEST_POSTGRES_URL`.
+    #[tokio::test]
+    async fn test_migrate_postgres() {
+        let url = std::env::var("TEST_POSTGRES_URL")
+            .expect("TEST_POSTGRES_URL is not set");
+        let db = Database::connect(url).await.unwrap();
+        check_migrations(&db).await;
+    }
+}
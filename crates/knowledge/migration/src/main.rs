@@ -1,22 +1,6 @@
-mod create_metadata;
-mod source_code;
-mod tx_index;
-
 use libsofl_utils::config::Config;
-pub use sea_orm_migration::prelude::*;
-
-pub struct Migrator;
-
-#[async_trait::async_trait]
-impl MigratorTrait for Migrator {
-    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![
-            Box::new(create_metadata::Migration),
-            Box::new(tx_index::Migration),
-            Box::new(source_code::Migration),
-        ]
-    }
-}
+use migration::Migrator;
+use sea_orm_migration::cli;
 
 #[tokio::main]
 async fn main() {
@@ -29,6 +29,14 @@ pub struct Cli {
     #[arg(short, long, default_value = "2425")]
     port: usize,
 
+    /// Serve Prometheus metrics at `/metrics` on this port.
+    #[arg(long)]
+    metrics_port: Option<usize>,
+
+    /// The number of bytes of RPC request parameters to log.
+    #[arg(long)]
+    log_params_limit: Option<usize>,
+
     #[arg(long)]
     chain_id: Option<u64>,
 
@@ -68,7 +76,7 @@ async fn main() -> Result<()> {
 
     // services
     let services: Vec<Box<dyn KnowledgeService>> = vec![
-        create_base_service(db.clone()),
+        create_base_service(db.clone(), provider.clone()),
         create_code_service(provider.clone(), db.clone(), &base_cfg).await?,
         create_index_service(db.clone()),
     ];
@@ -76,6 +84,12 @@ async fn main() -> Result<()> {
     // server
     let mut server =
         KnowledgeServer::new(provider.clone(), args.host, args.port);
+    if let Some(port) = args.metrics_port {
+        server = server.with_metrics_port(port);
+    }
+    if let Some(len) = args.log_params_limit {
+        server = server.with_max_logged_params_len(len);
+    }
     services
         .into_iter()
         .for_each(|service| server.register_service(service));
@@ -113,7 +127,9 @@ async fn create_code_service(
 ) -> Result<Box<dyn KnowledgeService>> {
     let mut code_knowledge_cfg =
         libsofl_knowledge_code::config::CodeKnowledgeConfig::must_load_or_default();
-    code_knowledge_cfg.requests_per_second = Some(5.0);
+    // the free plan of Etherscan allows 5 requests per second
+    code_knowledge_cfg.requests_per_second =
+        code_knowledge_cfg.requests_per_second.or(Some(5.0));
     let service =
         CodeService::new(provider, db, base_cfg, &code_knowledge_cfg).await?;
     Ok(Box::new(service))
@@ -121,8 +137,9 @@ async fn create_code_service(
 
 fn create_base_service(
     db: Arc<DatabaseConnection>,
+    provider: Arc<RethProvider>,
 ) -> Box<dyn KnowledgeService> {
-    let service = libsofl_knowledge_base::service::BaseService { db };
+    let service = libsofl_knowledge_base::service::BaseService { db, provider };
     Box::new(service)
 }
 
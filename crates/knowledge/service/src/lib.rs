@@ -1,20 +1,36 @@
+pub mod logging;
+pub mod metrics;
+
 use std::{cell::RefCell, sync::Arc};
 
 use eyre::{eyre, Result};
 use jsonrpsee::{
-    server::{ServerBuilder, ServerHandle},
+    server::{middleware::rpc::RpcServiceBuilder, ServerBuilder, ServerHandle},
     Methods,
 };
-use libsofl_knowledge_base::service::KnowledgeService;
+use libsofl_knowledge_base::{
+    metrics::KnowledgeMetrics, service::KnowledgeService,
+};
 use libsofl_reth::blockchain::provider::RethProvider;
 use libsofl_utils::log::info;
 
+use crate::{
+    logging::{RpcLogger, DEFAULT_MAX_LOGGED_PARAMS_LEN},
+    metrics::{MetricsServer, RpcMetrics},
+};
+
 pub struct KnowledgeServer<'a> {
     pub provider: Arc<RethProvider>,
     pub host: String,
     pub port: usize,
+    /// The port to serve metrics at `/metrics`, disabled if `None`.
+    pub metrics_port: Option<usize>,
+    pub metrics: KnowledgeMetrics,
+    /// The number of bytes of the parameters logged per RPC request.
+    pub max_logged_params_len: usize,
 
     pub(crate) server: RefCell<Option<ServerHandle>>,
+    pub(crate) metrics_server: Option<MetricsServer>,
     pub(crate) services: Vec<Box<dyn KnowledgeService + 'a>>,
 }
 
@@ -24,11 +40,28 @@ impl<'a> KnowledgeServer<'a> {
             provider,
             host,
             port,
+            metrics_port: None,
+            metrics: KnowledgeMetrics::new()
+                .expect("failed to create metrics registry"),
+            max_logged_params_len: DEFAULT_MAX_LOGGED_PARAMS_LEN,
             server: RefCell::new(None),
+            metrics_server: None,
             services: Vec::new(),
         }
     }
 
+    /// Serve metrics in Prometheus text format at `/metrics` on the port.
+    pub fn with_metrics_port(mut self, port: usize) -> Self {
+        self.metrics_port = Some(port);
+        self
+    }
+
+    /// Truncate the parameters of RPC requests to `len` bytes in the logs.
+    pub fn with_max_logged_params_len(mut self, len: usize) -> Self {
+        self.max_logged_params_len = len;
+        self
+    }
+
     pub fn register_service(
         &mut self,
         service: Box<dyn KnowledgeService + 'a>,
@@ -41,17 +74,37 @@ impl<'a> KnowledgeServer<'a> {
             return Err(eyre!("server already started"));
         }
 
+        let metrics = self.metrics.clone();
+        let max_params_len = self.max_logged_params_len;
+        let middleware = RpcServiceBuilder::new()
+            .layer_fn(move |service| RpcLogger {
+                service,
+                max_params_len,
+            })
+            .layer_fn(move |service| RpcMetrics {
+                service,
+                metrics: metrics.clone(),
+            });
         let server = ServerBuilder::default()
+            .set_rpc_middleware(middleware)
             .build(&format!("{}:{}", self.host, self.port))
             .await?;
 
         let mut methods = Methods::new();
         for service in self.services.iter_mut() {
             info!(service = service.name(), "starting service");
-            service.start().await?;
+            let metrics = self.metrics.service(service.name());
+            service.start(metrics).await?;
             methods.merge(service.rpc_methods())?;
         }
 
+        if let Some(port) = self.metrics_port {
+            let metrics_server =
+                MetricsServer::start(self.metrics.clone(), &self.host, port)
+                    .await?;
+            self.metrics_server = Some(metrics_server);
+        }
+
         let server_handle = server.start(methods);
         self.server.replace(Some(server_handle));
         info!(
@@ -72,6 +125,7 @@ impl<'a> KnowledgeServer<'a> {
             server.stop()?;
             server.stopped().await;
         }
+        self.metrics_server.take();
 
         for service in self.services.iter_mut() {
             info!(service = service.name(), "stopping service");
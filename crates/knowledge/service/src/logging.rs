@@ -0,0 +1,158 @@
+use std::{borrow::Cow, time::Instant};
+
+use futures::{future::BoxFuture, FutureExt};
+use jsonrpsee::{
+    server::{middleware::rpc::RpcServiceT, MethodResponse},
+    types::Request,
+};
+use libsofl_utils::log::{debug, info, info_span, Instrument};
+
+/// The default number of bytes of the parameters to log per request.
+pub const DEFAULT_MAX_LOGGED_PARAMS_LEN: usize = 256;
+
+/// RPC middleware that logs the method, parameters, latency, and outcome
+/// of each request within a span named `rpc`.
+/// Parameters are truncated to `max_params_len` bytes,
+/// so that large or sensitive inputs are not logged in full.
+#[derive(Clone)]
+pub struct RpcLogger<S> {
+    pub service: S,
+    pub max_params_len: usize,
+}
+
+impl<'a, S> RpcServiceT<'a> for RpcLogger<S>
+where
+    S: RpcServiceT<'a> + Send + Sync + Clone + 'static,
+{
+    type Future = BoxFuture<'a, MethodResponse>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        let service = self.service.clone();
+        let span = info_span!("rpc", method = req.method_name());
+        let params = req.params();
+        let params =
+            truncate(params.as_str().unwrap_or(""), self.max_params_len);
+        debug!(parent: &span, params = %params, "received rpc request");
+        async move {
+            let start = Instant::now();
+            let rp = service.call(req).await;
+            let elapsed = start.elapsed();
+            if rp.is_success() {
+                debug!(elapsed = ?elapsed, "rpc request succeeded");
+            } else {
+                info!(elapsed = ?elapsed, "rpc request failed");
+            }
+            rp
+        }
+        .instrument(span)
+        .boxed()
+    }
+}
+
+/// Truncate `s` to at most `limit` bytes (on a char boundary),
+/// noting the original length if truncated.
+fn truncate(s: &str, limit: usize) -> Cow<'_, str> {
+    if s.len() <= limit {
+        return Cow::Borrowed(s);
+    }
+    let mut end = limit;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    Cow::Owned(format!("{}...({} bytes)", &s[..end], s.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use jsonrpsee::{
+        core::client::ClientT,
+        http_client::HttpClientBuilder,
+        rpc_params,
+        server::{middleware::rpc::RpcServiceBuilder, ServerBuilder},
+        RpcModule,
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id},
+        Subscriber,
+    };
+    use tracing_subscriber::{
+        layer::{Context, SubscriberExt},
+        Layer,
+    };
+
+    use super::{truncate, RpcLogger};
+
+    /// Records the name and the `method` field of each new span.
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<Mutex<Vec<(String, String)>>>);
+
+    struct MethodVisitor(String);
+
+    impl Visit for MethodVisitor {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "method" {
+                self.0 = value.to_string();
+            }
+        }
+
+        fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+    }
+
+    impl<S: Subscriber> Layer<S> for SpanRecorder {
+        fn on_new_span(
+            &self,
+            attrs: &Attributes<'_>,
+            _: &Id,
+            _: Context<'_, S>,
+        ) {
+            let mut visitor = MethodVisitor(String::new());
+            attrs.record(&mut visitor);
+            let name = attrs.metadata().name().to_string();
+            self.0.lock().unwrap().push((name, visitor.0));
+        }
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("[1,2]", 5), "[1,2]");
+        assert_eq!(truncate("[1,2,3]", 4), "[1,2...(7 bytes)");
+        // not in the middle of a multi-byte char
+        assert_eq!(truncate("[\"é\"]", 3), "[\"...(6 bytes)");
+    }
+
+    #[tokio::test]
+    async fn test_span_per_request() {
+        let recorder = SpanRecorder::default();
+        let subscriber = tracing_subscriber::registry().with(recorder.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut module = RpcModule::new(());
+        module.register_method("test_ping", |_, _| "pong").unwrap();
+        let middleware =
+            RpcServiceBuilder::new().layer_fn(|service| RpcLogger {
+                service,
+                max_params_len: 8,
+            });
+        let server = ServerBuilder::default()
+            .set_rpc_middleware(middleware)
+            .build("127.0.0.1:0")
+            .await
+            .unwrap();
+        let url = format!("http://{}", server.local_addr().unwrap());
+        let handle = server.start(module);
+
+        let client = HttpClientBuilder::default().build(url).unwrap();
+        let rp: String = client
+            .request("test_ping", rpc_params!["a long parameter"])
+            .await
+            .unwrap();
+        assert_eq!(rp, "pong");
+
+        let spans = recorder.0.lock().unwrap().clone();
+        assert!(spans.contains(&("rpc".to_string(), "test_ping".to_string())));
+        handle.stop().unwrap();
+    }
+}
@@ -0,0 +1,174 @@
+use std::{convert::Infallible, net::SocketAddr, time::Instant};
+
+use futures::{future::BoxFuture, FutureExt};
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, StatusCode,
+};
+use jsonrpsee::server::{middleware::rpc::RpcServiceT, MethodResponse};
+use libsofl_knowledge_base::metrics::KnowledgeMetrics;
+use libsofl_utils::log::{error, info};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+/// RPC middleware that records the count and latency of requests per method.
+#[derive(Clone)]
+pub struct RpcMetrics<S> {
+    pub service: S,
+    pub metrics: KnowledgeMetrics,
+}
+
+impl<'a, S> RpcServiceT<'a> for RpcMetrics<S>
+where
+    S: RpcServiceT<'a> + Send + Sync + Clone + 'static,
+{
+    type Future = BoxFuture<'a, MethodResponse>;
+
+    fn call(&self, req: jsonrpsee::types::Request<'a>) -> Self::Future {
+        let service = self.service.clone();
+        let metrics = self.metrics.clone();
+        let method = req.method_name().to_string();
+        async move {
+            let start = Instant::now();
+            let rp = service.call(req).await;
+            metrics.observe_rpc(&method, rp.is_success(), start.elapsed());
+            rp
+        }
+        .boxed()
+    }
+}
+
+/// MetricsServer serves the metrics in Prometheus text format
+/// at `/metrics` over HTTP.
+pub struct MetricsServer {
+    pub socket_addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl MetricsServer {
+    pub async fn start(
+        metrics: KnowledgeMetrics,
+        host: &str,
+        port: usize,
+    ) -> Result<Self, std::io::Error> {
+        let listener = TcpListener::bind(format!("{}:{}", host, port)).await?;
+        let socket_addr = listener.local_addr()?;
+        let make_svc = make_service_fn(move |_| {
+            let metrics = metrics.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let rp = respond(&metrics, &req);
+                    async move { Ok::<_, Infallible>(rp) }
+                }))
+            }
+        });
+        let server = hyper::Server::from_tcp(listener.into_std()?)
+            .map_err(std::io::Error::other)?
+            .serve(make_svc);
+        let handle = tokio::spawn(async move {
+            if let Err(err) = server.await {
+                error!(err = ?err, "metrics server failed");
+            }
+        });
+
+        info!(host = host, port = port, "started metrics server");
+
+        Ok(Self {
+            socket_addr,
+            handle,
+        })
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+        info!("stopped metrics server");
+    }
+}
+
+fn respond(metrics: &KnowledgeMetrics, req: &Request<Body>) -> Response<Body> {
+    if req.method() != Method::GET || req.uri().path() != "/metrics" {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .expect("invalid response");
+    }
+    let rp = match metrics.encode() {
+        Ok(text) => Response::builder()
+            .header(CONTENT_TYPE, prometheus::TEXT_FORMAT)
+            .body(Body::from(text)),
+        Err(err) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::from(err.to_string())),
+    };
+    rp.expect("invalid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpsee::{
+        core::client::ClientT,
+        http_client::HttpClientBuilder,
+        rpc_params,
+        server::{middleware::rpc::RpcServiceBuilder, ServerBuilder},
+        RpcModule,
+    };
+    use libsofl_knowledge_base::metrics::KnowledgeMetrics;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+
+    use super::{MetricsServer, RpcMetrics};
+
+    async fn scrape(server: &MetricsServer, path: &str) -> String {
+        let mut stream = TcpStream::connect(server.socket_addr).await.unwrap();
+        let req = format!(
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            path
+        );
+        stream.write_all(req.as_bytes()).await.unwrap();
+        let mut rp = String::new();
+        stream.read_to_string(&mut rp).await.unwrap();
+        rp
+    }
+
+    #[tokio::test]
+    async fn test_scrape_rpc_metrics() {
+        let metrics = KnowledgeMetrics::new().unwrap();
+        let mut module = RpcModule::new(());
+        module.register_method("test_ping", |_, _| "pong").unwrap();
+        let m = metrics.clone();
+        let middleware =
+            RpcServiceBuilder::new().layer_fn(move |service| RpcMetrics {
+                service,
+                metrics: m.clone(),
+            });
+        let rpc_server = ServerBuilder::default()
+            .set_rpc_middleware(middleware)
+            .build("127.0.0.1:0")
+            .await
+            .unwrap();
+        let url = format!("http://{}", rpc_server.local_addr().unwrap());
+        let rpc_handle = rpc_server.start(module);
+
+        let client = HttpClientBuilder::default().build(url).unwrap();
+        for _ in 0..2 {
+            let rp: String =
+                client.request("test_ping", rpc_params![]).await.unwrap();
+            assert_eq!(rp, "pong");
+        }
+
+        let server =
+            MetricsServer::start(metrics, "127.0.0.1", 0).await.unwrap();
+        let rp = scrape(&server, "/metrics").await;
+        assert!(rp.starts_with("HTTP/1.1 200 OK"));
+        let counter = r#"knowledge_rpc_requests_total{method="test_ping",outcome="success"} 2"#;
+        assert!(rp.lines().any(|l| l == counter));
+        let rp = scrape(&server, "/").await;
+        assert!(rp.starts_with("HTTP/1.1 404 Not Found"));
+
+        rpc_handle.stop().unwrap();
+    }
+}
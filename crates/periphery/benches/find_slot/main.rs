@@ -0,0 +1,75 @@
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::Function;
+use criterion::{criterion_group, criterion_main, Criterion};
+use libsofl_core::{
+    conversion::ConvertTo,
+    engine::{
+        memory::{EmptyMemoryBcState, MemoryBcState},
+        types::{Address, Bytes},
+    },
+    solidity::scripting::{deploy_contracts, SolScriptConfig},
+};
+use libsofl_periphery::cheatcodes::CheatCodes;
+
+// the getter reads eight slots holding the same value as the balance
+const CODE: &str = r#"
+    contract Token {
+        uint256[7] public limits = [uint256(7), 7, 7, 7, 7, 7, 7];
+        mapping(address => uint256) private balances;
+        constructor() {
+            balances[address(0x1234)] = 7;
+        }
+        function balanceOf(address a) public view returns (uint256) {
+            for (uint256 i = 0; i < 7; i++) {
+                require(limits[i] > 0);
+            }
+            return balances[a];
+        }
+    }
+"#;
+
+fn setup() -> (EmptyMemoryBcState, Address, Bytes) {
+    let mut state = MemoryBcState::fresh();
+    let token = deploy_contracts(
+        &mut state,
+        "0.8.12",
+        CODE,
+        vec!["Token"],
+        SolScriptConfig::default(),
+    )
+    .unwrap()
+    .remove(0);
+    let account: Address = 0x1234.cvt();
+    let calldata = Function::parse("balanceOf(address)")
+        .unwrap()
+        .abi_encode_input(&[DynSolValue::Address(account)])
+        .unwrap()
+        .cvt();
+    (state, token, calldata)
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let (mut state, token, calldata) = setup();
+    c.bench_function("find_slot sequential probe", |b| {
+        b.iter(|| {
+            // a fresh cheatcodes has no cached slot
+            let mut cheatcodes =
+                CheatCodes::new(1, 17000000).set_batch_probe(false);
+            cheatcodes
+                .cheat_read(&mut state, token, calldata.clone())
+                .unwrap()
+        })
+    });
+    c.bench_function("find_slot batch probe", |b| {
+        b.iter(|| {
+            let mut cheatcodes =
+                CheatCodes::new(1, 17000000).set_batch_probe(true);
+            cheatcodes
+                .cheat_read(&mut state, token, calldata.clone())
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
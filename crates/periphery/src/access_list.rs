@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use libsofl_core::engine::{
+    inspector::EvmInspector,
+    state::BcState,
+    types::{
+        opcode, AccessList, Address, CallInputs, CallOutcome, CreateInputs,
+        CreateOutcome, EvmContext, Inspector, Interpreter, U256,
+    },
+};
+
+/// AccessListInspector records the addresses and storage slots accessed during execution,
+/// from which an EIP-2930 access list can be generated.
+///
+/// Addresses are recorded when they are called, created, or inspected by
+/// BALANCE, EXTCODESIZE, EXTCODECOPY, and EXTCODEHASH,
+/// and slots are recorded when they are loaded or stored.
+/// Whether the access is cold or warm does not matter.
+#[derive(Debug, Clone, Default)]
+pub struct AccessListInspector {
+    pub accessed: BTreeMap<Address, BTreeSet<U256>>,
+}
+
+impl AccessListInspector {
+    fn touch(&mut self, address: Address) -> &mut BTreeSet<U256> {
+        self.accessed.entry(address).or_default()
+    }
+
+    /// Whether the address is accessed.
+    pub fn contains(&self, address: &Address) -> bool {
+        self.accessed.contains_key(address)
+    }
+
+    /// The access list in the format of `TxEnv::access_list`, sorted by addresses and slots.
+    pub fn into_access_list(self) -> AccessList {
+        self.accessed
+            .into_iter()
+            .map(|(address, slots)| (address, slots.into_iter().collect()))
+            .collect()
+    }
+}
+
+impl<S: BcState> Inspector<S> for AccessListInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<S>) {
+        let top = match interp.stack.data().last() {
+            Some(top) => *top,
+            None => return,
+        };
+        match interp.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                let address = interp.contract().address;
+                self.touch(address).insert(top);
+            }
+            opcode::BALANCE
+            | opcode::EXTCODESIZE
+            | opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH => {
+                let address = Address::from_word(top.to_be_bytes().into());
+                self.touch(address);
+            }
+            _ => {}
+        }
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<S>,
+        inputs: &mut CallInputs,
+        _return_memory_offset: std::ops::Range<usize>,
+    ) -> Option<CallOutcome> {
+        // the code and storage addresses differ for DELEGATECALL and CALLCODE
+        self.touch(inputs.contract);
+        self.touch(inputs.context.address);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut EvmContext<S>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if let Some(address) = outcome.address {
+            self.touch(address);
+        }
+        outcome
+    }
+}
+
+impl<S: BcState> EvmInspector<S> for AccessListInspector {}
+
+#[cfg(test)]
+mod tests_with_dep {
+    use alloy_sol_types::SolCall;
+    use libsofl_core::{
+        blockchain::{provider::BcStateProvider, tx_position::TxPosition},
+        conversion::ConvertTo,
+        engine::{
+            state::BcState,
+            types::{Address, SpecId, U256},
+        },
+    };
+
+    use crate::{
+        addressbook::{UniswapV2Router02ABI, ADDRESS_BOOK},
+        caller::HighLevelCaller,
+        test::get_test_bc_provider,
+        types::Chain,
+    };
+
+    use super::AccessListInspector;
+
+    #[test]
+    fn test_swap_access_list() {
+        let bp = get_test_bc_provider();
+        let fork_at = TxPosition::new(17000001, 0);
+        let mut state = bp.bc_state_at(fork_at).unwrap();
+
+        let weth = ADDRESS_BOOK.weth.must_on_chain(Chain::Mainnet);
+        let usdc = ADDRESS_BOOK.usdc.must_on_chain(Chain::Mainnet);
+        let router: Address =
+            "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".cvt();
+        let pair: Address = "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".cvt();
+        let trader: Address = 0x123456.cvt();
+        let amount_in = U256::from(10).pow(U256::from(18));
+        state.add_ether_balance(trader, amount_in).unwrap();
+
+        let call = UniswapV2Router02ABI::swapExactETHForTokensCall {
+            amountOutMin: U256::ZERO,
+            path: vec![weth, usdc],
+            to: trader,
+            deadline: U256::MAX,
+        };
+        let mut inspector = AccessListInspector::default();
+        HighLevelCaller::new(trader)
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .call(
+                &mut state,
+                router,
+                call.abi_encode().cvt(),
+                Some(amount_in),
+                &mut inspector,
+            )
+            .unwrap();
+
+        let list = inspector.into_access_list();
+        let slots = |address: Address| {
+            list.iter()
+                .find(|(a, _)| *a == address)
+                .map(|(_, slots)| slots.clone())
+        };
+        // reserves, balances, and the reentrancy lock of the pair
+        assert!(!slots(pair).unwrap().is_empty());
+        assert!(!slots(weth).unwrap().is_empty());
+        // USDC is a proxy, whose storage is read in the proxy's own context
+        assert!(!slots(usdc).unwrap().is_empty());
+        assert!(slots(router).is_some());
+    }
+}
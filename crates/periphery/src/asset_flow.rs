@@ -0,0 +1,503 @@
+use std::collections::HashMap;
+
+use alloy_sol_types::SolEvent;
+use libsofl_core::engine::{
+    inspector::EvmInspector,
+    state::BcState,
+    types::{
+        Address, CallInputs, CallOutcome, CreateInputs, CreateOutcome,
+        EvmContext, EvmLog, Inspector, TxEnv, I256, U256,
+    },
+};
+
+use crate::addressbook::{ADDRESS_BOOK, ERC20ABI};
+
+/// The address of a token.
+/// Native ether is represented by `ADDRESS_BOOK.eth`.
+pub type TokenAddress = Address;
+
+/// A transfer of asset from one account to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetTransfer {
+    pub token: TokenAddress,
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+    /// The token id of an ERC721 transfer, whose amount is always 1.
+    /// None for ether and ERC20 transfers.
+    pub token_id: Option<U256>,
+}
+
+/// AssetFlowInspector records the asset transfers in transactions,
+/// including ether transfers (call value, contract creation endowment, and selfdestruct)
+/// and ERC20/ERC721 `Transfer` events.
+/// Transfers happened in reverted calls are discarded.
+#[derive(Debug, Clone, Default)]
+pub struct AssetFlowInspector {
+    /// Asset transfers ordered by their occurrence.
+    /// Transfers below the threshold are also kept here.
+    pub transfers: Vec<AssetTransfer>,
+
+    /// The minimum amount of each token for a transfer to be counted in `net_changes`.
+    pub thresholds: HashMap<TokenAddress, U256>,
+
+    // transfers of each call frame that are not yet committed
+    frames: Vec<Vec<AssetTransfer>>,
+}
+
+impl AssetFlowInspector {
+    /// Ignore transfers of `token` whose amount is less than `min_amount` in `net_changes`.
+    pub fn with_threshold(
+        mut self,
+        token: TokenAddress,
+        min_amount: U256,
+    ) -> Self {
+        self.thresholds.insert(token, min_amount);
+        self
+    }
+
+    /// Whether the transfer is below the threshold of its token.
+    pub fn is_dust(&self, transfer: &AssetTransfer) -> bool {
+        self.thresholds
+            .get(&transfer.token)
+            .is_some_and(|min| transfer.amount < *min)
+    }
+
+    /// Collapse all recorded transfers into the net balance change of each (account, token).
+    /// Negative means the account sent more than it received.
+    /// Transfers below the threshold of their token are excluded,
+    /// and so are transfers whose amount does not fit in I256,
+    /// e.g., those emitted by malicious tokens.
+    pub fn net_changes(self) -> HashMap<(Address, TokenAddress), I256> {
+        let mut changes: HashMap<(Address, TokenAddress), I256> =
+            HashMap::new();
+        for t in self.transfers.iter().filter(|t| !self.is_dust(t)) {
+            let Ok(amount) = I256::try_from(t.amount) else {
+                continue;
+            };
+            let from = changes.entry((t.from, t.token)).or_default();
+            *from = from.saturating_sub(amount);
+            let to = changes.entry((t.to, t.token)).or_default();
+            *to = to.saturating_add(amount);
+        }
+        changes.retain(|_, v| *v != I256::ZERO);
+        changes
+    }
+
+    fn record_ether(&mut self, from: Address, to: Address, amount: U256) {
+        if amount == U256::ZERO || from == to {
+            return;
+        }
+        let transfer = AssetTransfer {
+            token: ADDRESS_BOOK.eth.fixed(),
+            from,
+            to,
+            amount,
+            token_id: None,
+        };
+        if let Some(frame) = self.frames.last_mut() {
+            frame.push(transfer);
+        }
+    }
+
+    fn exit(&mut self, success: bool) {
+        let Some(transfers) = self.frames.pop() else {
+            return;
+        };
+        if !success {
+            return;
+        }
+        match self.frames.last_mut() {
+            Some(parent) => parent.extend(transfers),
+            None => self.transfers.extend(transfers),
+        }
+    }
+}
+
+impl<S: BcState> Inspector<S> for AssetFlowInspector {
+    fn log(&mut self, _context: &mut EvmContext<S>, log: &EvmLog) {
+        // ERC20 and ERC721 share the same `Transfer(address,address,uint256)` topic,
+        // but ERC721 has the token id indexed as well.
+        let topics = log.topics();
+        if topics.is_empty() || topics[0] != ERC20ABI::Transfer::SIGNATURE_HASH
+        {
+            return;
+        }
+        let data = &log.data.data;
+        let transfer = match topics.len() {
+            // ERC20: Transfer(address indexed, address indexed, uint256)
+            3 if data.len() == 32 => AssetTransfer {
+                token: log.address,
+                from: Address::from_word(topics[1]),
+                to: Address::from_word(topics[2]),
+                amount: U256::from_be_slice(data),
+                token_id: None,
+            },
+            // ERC721: Transfer(address indexed, address indexed, uint256 indexed)
+            4 if data.is_empty() => AssetTransfer {
+                token: log.address,
+                from: Address::from_word(topics[1]),
+                to: Address::from_word(topics[2]),
+                amount: U256::from(1),
+                token_id: Some(U256::from_be_bytes(topics[3].0)),
+            },
+            _ => return,
+        };
+        if let Some(frame) = self.frames.last_mut() {
+            frame.push(transfer);
+        }
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<S>,
+        inputs: &mut CallInputs,
+        _return_memory_offset: std::ops::Range<usize>,
+    ) -> Option<CallOutcome> {
+        self.frames.push(Vec::new());
+        self.record_ether(
+            inputs.transfer.source,
+            inputs.transfer.target,
+            inputs.transfer.value,
+        );
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut EvmContext<S>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.exit(outcome.result.result.is_ok());
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<S>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.frames.push(Vec::new());
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut EvmContext<S>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        let success = outcome.result.result.is_ok();
+        if let (true, Some(addr)) = (success, outcome.address) {
+            self.record_ether(inputs.caller, addr, inputs.value);
+        }
+        self.exit(success);
+        outcome
+    }
+
+    fn selfdestruct(
+        &mut self,
+        contract: Address,
+        target: Address,
+        value: U256,
+    ) {
+        if !self.frames.is_empty() {
+            self.record_ether(contract, target, value);
+        }
+    }
+}
+
+impl<S: BcState> EvmInspector<S> for AssetFlowInspector {
+    fn transaction(&mut self, _tx: &TxEnv, _state: &S) -> bool {
+        self.frames.clear();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            memory::MemoryBcState,
+            state::BcState,
+            types::{Address, SpecId, I256, U256},
+        },
+        solidity::{
+            caller::HighLevelCaller,
+            scripting::{deploy_contracts, SolScriptConfig},
+        },
+    };
+
+    use crate::addressbook::ADDRESS_BOOK;
+
+    use super::{AssetFlowInspector, AssetTransfer};
+
+    #[test]
+    fn test_ether_net_changes() {
+        let mut state = MemoryBcState::fresh();
+        let receiver: Address = 0x123456.cvt();
+        let code = format!(
+            r#"
+            contract A {{
+                function forward() public payable {{
+                    payable({}).transfer(msg.value / 2);
+                }}
+            }}
+            "#,
+            receiver
+        );
+        let contract = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["A"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let caller = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST);
+        state
+            .add_ether_balance(caller.address, U256::from(100))
+            .unwrap();
+
+        let mut inspector = AssetFlowInspector::default();
+        caller
+            .invoke(
+                &mut state,
+                contract,
+                "forward()",
+                &[],
+                Some(U256::from(100)),
+                &mut inspector,
+            )
+            .unwrap();
+
+        let eth = ADDRESS_BOOK.eth.fixed();
+        let changes = inspector.net_changes();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(
+            changes[&(caller.address, eth)],
+            -I256::from_raw(U256::from(100))
+        );
+        assert_eq!(changes[&(contract, eth)], I256::from_raw(U256::from(50)));
+        assert_eq!(changes[&(receiver, eth)], I256::from_raw(U256::from(50)));
+    }
+
+    #[test]
+    fn test_dust_excluded_from_net_changes() {
+        let mut state = MemoryBcState::fresh();
+        let receiver: Address = 0x123456.cvt();
+        let dust_receiver: Address = 0x654321.cvt();
+        let code = format!(
+            r#"
+            contract A {{
+                function forward() public payable {{
+                    payable({}).transfer(msg.value - 1);
+                    payable({}).transfer(1);
+                }}
+            }}
+            "#,
+            receiver, dust_receiver
+        );
+        let contract = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["A"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let caller = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST);
+        state
+            .add_ether_balance(caller.address, U256::from(100))
+            .unwrap();
+
+        let eth = ADDRESS_BOOK.eth.fixed();
+        let mut inspector =
+            AssetFlowInspector::default().with_threshold(eth, U256::from(10));
+        caller
+            .invoke(
+                &mut state,
+                contract,
+                "forward()",
+                &[],
+                Some(U256::from(100)),
+                &mut inspector,
+            )
+            .unwrap();
+
+        // raw flows are kept
+        assert_eq!(inspector.transfers.len(), 3);
+        let changes = inspector.net_changes();
+        assert!(!changes.contains_key(&(dust_receiver, eth)));
+        assert_eq!(changes[&(receiver, eth)], I256::from_raw(U256::from(99)));
+        assert_eq!(changes[&(contract, eth)], I256::from_raw(U256::from(1)));
+    }
+
+    /// Emit a `Transfer` event of the standard of the contract.
+    /// The events are declared in the emitting contracts, since emitting
+    /// events of other contracts requires solc 0.8.20.
+    fn emit_transfer(name: &str) -> (Address, Vec<AssetTransfer>) {
+        let mut state = MemoryBcState::fresh();
+        let contract = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            r#"
+            contract Token {
+                event Transfer(address indexed from, address indexed to, uint256 value);
+                function transfer() public {
+                    emit Transfer(address(0x1), address(0x2), 100);
+                }
+            }
+            contract NFT {
+                event Transfer(address indexed from, address indexed to, uint256 indexed tokenId);
+                function transfer() public {
+                    emit Transfer(address(0x1), address(0x2), 7);
+                }
+            }
+            "#,
+            vec![name],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let func = "transfer()";
+        let mut inspector = AssetFlowInspector::default();
+        HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .invoke(&mut state, contract, func, &[], None, &mut inspector)
+            .unwrap();
+        (contract, inspector.transfers)
+    }
+
+    #[test]
+    fn test_erc20_transfer_event() {
+        let (contract, transfers) = emit_transfer("Token");
+        assert_eq!(
+            transfers,
+            vec![AssetTransfer {
+                token: contract,
+                from: 0x1.cvt(),
+                to: 0x2.cvt(),
+                amount: U256::from(100),
+                token_id: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_huge_amount_excluded_from_net_changes() {
+        let token: Address = 0x1234.cvt();
+        let (alice, bob): (Address, Address) = (0x1.cvt(), 0x2.cvt());
+        let transfer = |amount: U256| AssetTransfer {
+            token,
+            from: alice,
+            to: bob,
+            amount,
+            token_id: None,
+        };
+        let inspector = AssetFlowInspector {
+            transfers: vec![
+                transfer(U256::from(10)),
+                transfer(U256::from(1) << 255),
+            ],
+            ..Default::default()
+        };
+        let changes = inspector.net_changes();
+        assert_eq!(changes[&(bob, token)], I256::from_raw(U256::from(10)));
+        assert_eq!(changes[&(alice, token)], -I256::from_raw(U256::from(10)));
+    }
+
+    #[test]
+    fn test_exit_without_enter() {
+        let mut inspector = AssetFlowInspector::default();
+        inspector.record_ether(0x1.cvt(), 0x2.cvt(), U256::from(1));
+        inspector.exit(true);
+        assert!(inspector.transfers.is_empty());
+    }
+
+    #[test]
+    fn test_erc721_transfer_event() {
+        let (contract, transfers) = emit_transfer("NFT");
+        assert_eq!(
+            transfers,
+            vec![AssetTransfer {
+                token: contract,
+                from: 0x1.cvt(),
+                to: 0x2.cvt(),
+                amount: U256::from(1),
+                token_id: Some(U256::from(7)),
+            }]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_with_dep {
+    use alloy_sol_types::SolCall;
+    use libsofl_core::{
+        blockchain::{provider::BcStateProvider, tx_position::TxPosition},
+        conversion::ConvertTo,
+        engine::{
+            state::BcState,
+            types::{Address, SpecId, I256, U256},
+        },
+    };
+
+    use crate::{
+        addressbook::{UniswapV2Router02ABI, ADDRESS_BOOK},
+        caller::HighLevelCaller,
+        test::get_test_bc_provider,
+        types::Chain,
+    };
+
+    use super::AssetFlowInspector;
+
+    #[test]
+    fn test_swap_net_changes() {
+        let bp = get_test_bc_provider();
+        let fork_at = TxPosition::new(17000001, 0);
+        let mut state = bp.bc_state_at(fork_at).unwrap();
+
+        let eth = ADDRESS_BOOK.eth.fixed();
+        let weth = ADDRESS_BOOK.weth.must_on_chain(Chain::Mainnet);
+        let usdc = ADDRESS_BOOK.usdc.must_on_chain(Chain::Mainnet);
+        let router: Address =
+            "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".cvt();
+        let trader: Address = 0x123456.cvt();
+        let amount_in = U256::from(10).pow(U256::from(18));
+        state.add_ether_balance(trader, amount_in).unwrap();
+
+        let call = UniswapV2Router02ABI::swapExactETHForTokensCall {
+            amountOutMin: U256::ZERO,
+            path: vec![weth, usdc],
+            to: trader,
+            deadline: U256::MAX,
+        };
+        let mut inspector = AssetFlowInspector::default();
+        HighLevelCaller::new(trader)
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .call(
+                &mut state,
+                router,
+                call.abi_encode().cvt(),
+                Some(amount_in),
+                &mut inspector,
+            )
+            .unwrap();
+
+        let changes = inspector.net_changes();
+        assert_eq!(changes[&(trader, eth)], -I256::from_raw(amount_in));
+        assert!(changes[&(trader, usdc)] > I256::ZERO);
+        assert!(!changes.contains_key(&(trader, weth)));
+    }
+}
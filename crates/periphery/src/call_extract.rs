@@ -0,0 +1,634 @@
+use std::collections::HashSet;
+
+use libsofl_core::engine::{
+    inspector::EvmInspector,
+    state::BcState,
+    types::{
+        Address, Bytes, CallInputs, CallOutcome, CallScheme, CreateInputs,
+        CreateOutcome, CreateScheme, EvmContext, Inspector, TxEnv, U256,
+    },
+};
+
+/// The kind of a message call frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+    Call,
+    CallCode,
+    DelegateCall,
+    StaticCall,
+    Create,
+    Create2,
+    /// SELFDESTRUCT, recorded as a leaf frame from the destructed contract to the beneficiary.
+    SelfDestruct,
+}
+
+impl CallKind {
+    pub fn is_create(&self) -> bool {
+        matches!(self, CallKind::Create | CallKind::Create2)
+    }
+
+    /// The lowercase opcode name, as used by Etherscan.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallKind::Call => "call",
+            CallKind::CallCode => "callcode",
+            CallKind::DelegateCall => "delegatecall",
+            CallKind::StaticCall => "staticcall",
+            CallKind::Create => "create",
+            CallKind::Create2 => "create2",
+            CallKind::SelfDestruct => "selfdestruct",
+        }
+    }
+}
+
+/// One message call frame extracted from the execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedCall {
+    pub kind: CallKind,
+
+    /// The call depth, 0 for the transaction itself.
+    pub depth: usize,
+
+    /// The indices of the frame among its siblings, from the outermost internal call.
+    /// Empty for the transaction itself.
+    pub trace_address: Vec<usize>,
+
+    pub from: Address,
+
+    /// The code address of the callee, the created contract, or the selfdestruct beneficiary.
+    /// Zero if contract creation fails.
+    pub to: Address,
+
+    pub value: U256,
+    pub input: Bytes,
+    pub gas: u64,
+    pub gas_used: u64,
+    pub success: bool,
+}
+
+/// A SELFDESTRUCT as observed by the EVM.
+///
+/// Since Cancun (EIP-6780), the contract is only destructed if it is created in the same transaction,
+/// otherwise only the balance is transferred. The event is recorded in either case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfDestructEvent {
+    pub contract: Address,
+    pub beneficiary: Address,
+    pub value: U256,
+}
+
+/// An executing frame.
+#[derive(Debug, Clone)]
+struct Frame {
+    /// The index in calls, None if the frame is not recorded.
+    index: Option<usize>,
+    trace_address: Vec<usize>,
+    children: usize,
+}
+
+/// CallExtractInspector records all message call frames (including the transaction itself)
+/// in the order they are entered.
+///
+/// To keep memory bounded for transactions with many internal calls,
+/// the recorded frames can be restricted by `max_depth` and `allowlist`:
+/// a frame is recorded if it is not deeper than `max_depth`,
+/// or it is made from or to a contract in `allowlist`.
+/// Frames filtered out still count in the trace addresses of their siblings.
+#[derive(Debug, Clone, Default)]
+pub struct CallExtractInspector {
+    pub calls: Vec<ExtractedCall>,
+
+    /// All SELFDESTRUCTs in order, regardless of the frame filters.
+    pub selfdestructs: Vec<SelfDestructEvent>,
+
+    /// The maximum depth of the recorded frames, None for unlimited.
+    pub max_depth: Option<usize>,
+
+    /// The contracts whose frames are always recorded.
+    pub allowlist: Option<HashSet<Address>>,
+
+    stack: Vec<Frame>,
+}
+
+impl CallExtractInspector {
+    /// Only record frames not deeper than `max_depth`.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Record frames made from or to the contracts regardless of their depth.
+    /// If `max_depth` is not set, only these frames are recorded.
+    pub fn with_allowlist(
+        mut self,
+        contracts: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        self.allowlist
+            .get_or_insert_with(HashSet::new)
+            .extend(contracts);
+        self
+    }
+
+    fn should_record(&self, depth: usize, from: Address, to: Address) -> bool {
+        let touching = self
+            .allowlist
+            .as_ref()
+            .map(|l| l.contains(&from) || l.contains(&to));
+        match (self.max_depth, touching) {
+            (Some(max_depth), touching) => {
+                depth <= max_depth || touching.unwrap_or(false)
+            }
+            (None, Some(touching)) => touching,
+            (None, None) => true,
+        }
+    }
+
+    /// Internal calls, i.e., calls excluding the transaction itself.
+    pub fn internal_calls(&self) -> impl Iterator<Item = &ExtractedCall> {
+        self.calls.iter().filter(|c| c.depth > 0)
+    }
+
+    fn enter(
+        &mut self,
+        kind: CallKind,
+        from: Address,
+        to: Address,
+        value: U256,
+        input: Bytes,
+        gas: u64,
+    ) {
+        let trace_address = match self.stack.last_mut() {
+            Some(parent) => {
+                let mut addr = parent.trace_address.clone();
+                addr.push(parent.children);
+                parent.children += 1;
+                addr
+            }
+            None => Vec::new(),
+        };
+        let depth = self.stack.len();
+        let index = if self.should_record(depth, from, to) {
+            self.calls.push(ExtractedCall {
+                kind,
+                depth,
+                trace_address: trace_address.clone(),
+                from,
+                to,
+                value,
+                input,
+                gas,
+                gas_used: 0,
+                success: false,
+            });
+            Some(self.calls.len() - 1)
+        } else {
+            None
+        };
+        self.stack.push(Frame {
+            index,
+            trace_address,
+            children: 0,
+        });
+    }
+
+    fn record_selfdestruct(
+        &mut self,
+        contract: Address,
+        target: Address,
+        value: U256,
+    ) {
+        self.selfdestructs.push(SelfDestructEvent {
+            contract,
+            beneficiary: target,
+            value,
+        });
+        self.enter(
+            CallKind::SelfDestruct,
+            contract,
+            target,
+            value,
+            Bytes::new(),
+            0,
+        );
+        self.exit(0, true);
+    }
+
+    fn exit(
+        &mut self,
+        gas_used: u64,
+        success: bool,
+    ) -> Option<&mut ExtractedCall> {
+        // ignore a frame end without a matching start
+        let frame = self.stack.pop()?;
+        let call = &mut self.calls[frame.index?];
+        call.gas_used = gas_used;
+        call.success = success;
+        Some(call)
+    }
+}
+
+impl<S: BcState> Inspector<S> for CallExtractInspector {
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<S>,
+        inputs: &mut CallInputs,
+        _return_memory_offset: std::ops::Range<usize>,
+    ) -> Option<CallOutcome> {
+        let kind = match inputs.context.scheme {
+            CallScheme::Call => CallKind::Call,
+            CallScheme::CallCode => CallKind::CallCode,
+            CallScheme::DelegateCall => CallKind::DelegateCall,
+            CallScheme::StaticCall => CallKind::StaticCall,
+        };
+        self.enter(
+            kind,
+            inputs.transfer.source,
+            inputs.contract,
+            inputs.transfer.value,
+            inputs.input.clone(),
+            inputs.gas_limit,
+        );
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut EvmContext<S>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.exit(outcome.result.gas.spent(), outcome.result.result.is_ok());
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<S>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        let kind = match inputs.scheme {
+            CreateScheme::Create => CallKind::Create,
+            CreateScheme::Create2 { .. } => CallKind::Create2,
+        };
+        self.enter(
+            kind,
+            inputs.caller,
+            Address::ZERO,
+            inputs.value,
+            inputs.init_code.clone(),
+            inputs.gas_limit,
+        );
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut EvmContext<S>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        let success = outcome.result.result.is_ok();
+        if let Some(call) = self.exit(outcome.result.gas.spent(), success) {
+            if let (true, Some(addr)) = (success, outcome.address) {
+                call.to = addr;
+            }
+        }
+        outcome
+    }
+
+    fn selfdestruct(
+        &mut self,
+        contract: Address,
+        target: Address,
+        value: U256,
+    ) {
+        self.record_selfdestruct(contract, target, value);
+    }
+}
+
+impl<S: BcState> EvmInspector<S> for CallExtractInspector {
+    fn transaction(&mut self, _tx: &TxEnv, _state: &S) -> bool {
+        self.stack.clear();
+        true
+    }
+}
+
+/// An internal transaction in the format of Etherscan's `txlistinternal` API.
+/// All fields are strings, as returned by Etherscan.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct EtherscanInternalTx {
+    pub from: String,
+    /// Empty for contract creation.
+    pub to: String,
+    pub value: String,
+    /// The created contract, empty for message calls.
+    pub contract_address: String,
+    pub input: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub gas: String,
+    pub gas_used: String,
+    /// The trace address joined by `_`, e.g., `0_1` is the second call made by the first internal call.
+    pub trace_id: String,
+    pub is_error: String,
+    pub err_code: String,
+}
+
+impl From<&ExtractedCall> for EtherscanInternalTx {
+    fn from(call: &ExtractedCall) -> Self {
+        let addr = |a: Address| a.to_string().to_lowercase();
+        let (to, contract_address) = if call.kind.is_create() {
+            (String::new(), addr(call.to))
+        } else {
+            (addr(call.to), String::new())
+        };
+        let trace_id = call
+            .trace_address
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("_");
+        Self {
+            from: addr(call.from),
+            to,
+            value: call.value.to_string(),
+            contract_address,
+            input: String::new(),
+            kind: call.kind.as_str().to_string(),
+            gas: call.gas.to_string(),
+            gas_used: call.gas_used.to_string(),
+            trace_id,
+            is_error: if call.success { "0" } else { "1" }.to_string(),
+            err_code: String::new(),
+        }
+    }
+}
+
+impl CallExtractInspector {
+    /// Format the internal calls as Etherscan internal transactions.
+    /// If `value_only` is true, only contract creations and calls transferring ether are included,
+    /// which is consistent with what Etherscan lists.
+    pub fn etherscan_internal_txs(
+        &self,
+        value_only: bool,
+    ) -> Vec<EtherscanInternalTx> {
+        self.internal_calls()
+            .filter(|c| {
+                !value_only || c.kind.is_create() || c.value > U256::ZERO
+            })
+            .map(EtherscanInternalTx::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            inspector::no_inspector,
+            memory::MemoryBcState,
+            state::BcState,
+            types::{Address, Database, SpecId, U256},
+        },
+        solidity::{
+            caller::HighLevelCaller,
+            scripting::{deploy_contracts, SolScriptConfig},
+        },
+    };
+
+    use super::{CallExtractInspector, EtherscanInternalTx, SelfDestructEvent};
+
+    #[test]
+    fn test_etherscan_internal_txs() {
+        let mut state = MemoryBcState::fresh();
+        let receiver: Address = 0x123456.cvt();
+        let code = format!(
+            r#"
+            contract B {{
+                function forward() public payable {{
+                    payable({}).transfer(msg.value);
+                }}
+            }}
+            contract A {{
+                function split() public payable {{
+                    B b = new B();
+                    b.forward{{value: msg.value / 2}}();
+                }}
+            }}
+            "#,
+            receiver
+        );
+        let contract = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["A"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let caller = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST);
+        state
+            .add_ether_balance(caller.address, U256::from(100))
+            .unwrap();
+
+        let mut inspector = CallExtractInspector::default();
+        caller
+            .invoke(
+                &mut state,
+                contract,
+                "split()",
+                &[],
+                Some(U256::from(100)),
+                &mut inspector,
+            )
+            .unwrap();
+        let created = inspector.calls[1].to;
+
+        // Etherscan-shaped fixture, gas fields are not compared
+        let a = contract.to_string().to_lowercase();
+        let b = created.to_string().to_lowercase();
+        let r = receiver.to_string().to_lowercase();
+        let fixture = serde_json::json!([
+            {"from": a, "to": "", "value": "0", "contractAddress": b, "input": "", "type": "create", "traceId": "0", "isError": "0", "errCode": ""},
+            {"from": a, "to": b, "value": "50", "contractAddress": "", "input": "", "type": "call", "traceId": "1", "isError": "0", "errCode": ""},
+            {"from": b, "to": r, "value": "50", "contractAddress": "", "input": "", "type": "call", "traceId": "1_0", "isError": "0", "errCode": ""},
+        ]);
+        let txs: Vec<EtherscanInternalTx> = inspector
+            .etherscan_internal_txs(true)
+            .into_iter()
+            .map(|tx| EtherscanInternalTx {
+                gas: String::new(),
+                gas_used: String::new(),
+                ..tx
+            })
+            .collect();
+        let expected: Vec<EtherscanInternalTx> = fixture
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| {
+                let mut v = v.clone();
+                v["gas"] = "".into();
+                v["gasUsed"] = "".into();
+                serde_json::from_value(v).unwrap()
+            })
+            .collect();
+        assert_eq!(txs, expected);
+        assert_eq!(serde_json::to_value(&txs[1]).unwrap()["type"], "call");
+    }
+
+    #[test]
+    fn test_exit_without_enter() {
+        let mut inspector = CallExtractInspector::default();
+        assert!(inspector.exit(0, true).is_none());
+        assert!(inspector.calls.is_empty());
+    }
+
+    #[test]
+    fn test_bounded_depth() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Node {
+                Node public next;
+                function setNext(Node n) public {
+                    next = n;
+                }
+                function ping() public {
+                    if (address(next) != address(0)) {
+                        next.ping();
+                    }
+                }
+            }
+        "#;
+        let nodes: Vec<Address> = (0..4)
+            .map(|_| {
+                deploy_contracts(
+                    &mut state,
+                    "0.8.12",
+                    code,
+                    vec!["Node"],
+                    SolScriptConfig::default(),
+                )
+                .unwrap()
+                .remove(0)
+            })
+            .collect();
+        let caller = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST);
+        for pair in nodes.windows(2) {
+            caller
+                .invoke(
+                    &mut state,
+                    pair[0],
+                    "setNext(address)",
+                    &[pair[1].into()],
+                    None,
+                    no_inspector(),
+                )
+                .unwrap();
+        }
+        let mut ping = |inspector: &mut CallExtractInspector| {
+            caller
+                .invoke(&mut state, nodes[0], "ping()", &[], None, inspector)
+                .unwrap();
+        };
+
+        // the chain of calls is 4 frames deep
+        let mut inspector = CallExtractInspector::default();
+        ping(&mut inspector);
+        assert_eq!(inspector.calls.len(), 4);
+
+        let mut inspector = CallExtractInspector::default().with_max_depth(1);
+        ping(&mut inspector);
+        let depths: Vec<usize> =
+            inspector.calls.iter().map(|c| c.depth).collect();
+        assert_eq!(depths, vec![0, 1]);
+
+        // frames touching the allowlisted contract are recorded at any depth
+        let mut inspector = CallExtractInspector::default()
+            .with_max_depth(0)
+            .with_allowlist([nodes[3]]);
+        ping(&mut inspector);
+        let recorded: Vec<(Address, Vec<usize>)> = inspector
+            .calls
+            .iter()
+            .map(|c| (c.to, c.trace_address.clone()))
+            .collect();
+        assert_eq!(
+            recorded,
+            vec![(nodes[0], vec![]), (nodes[3], vec![0, 0, 0])]
+        );
+    }
+
+    #[test]
+    fn test_selfdestruct_in_creation() {
+        let mut state = MemoryBcState::fresh();
+        let beneficiary: Address = 0xbeef.cvt();
+        let code = format!(
+            r#"
+            contract Bomb {{
+                constructor() payable {{
+                    selfdestruct(payable({}));
+                }}
+            }}
+            contract Factory {{
+                function run() public payable returns (address) {{
+                    return address(new Bomb{{value: msg.value}}());
+                }}
+            }}
+            "#,
+            beneficiary
+        );
+        let factory = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Factory"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let caller = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST);
+        state
+            .add_ether_balance(caller.address, U256::from(100))
+            .unwrap();
+
+        // the frames are filtered out, but the event is still recorded
+        let mut inspector = CallExtractInspector::default().with_max_depth(0);
+        caller
+            .invoke(
+                &mut state,
+                factory,
+                "run()",
+                &[],
+                Some(U256::from(100)),
+                &mut inspector,
+            )
+            .unwrap();
+        assert_eq!(inspector.calls.len(), 1);
+        // contract nonces start at 1
+        let bomb = factory.create(1);
+        assert_eq!(
+            inspector.selfdestructs,
+            vec![SelfDestructEvent {
+                contract: bomb,
+                beneficiary,
+                value: U256::from(100),
+            }]
+        );
+        assert_eq!(
+            state.basic(beneficiary).unwrap().unwrap().balance,
+            U256::from(100)
+        );
+    }
+}
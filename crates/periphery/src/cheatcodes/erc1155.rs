@@ -0,0 +1,213 @@
+use std::fmt::Debug;
+
+use alloy_sol_types::SolCall;
+use libsofl_core::{
+    conversion::ConvertTo,
+    engine::{
+        inspector::no_inspector,
+        state::BcState,
+        types::{Address, U256},
+    },
+    error::SoflError,
+};
+
+use crate::{addressbook::ERC1155ABI, cheatcodes::CheatCodes};
+
+impl CheatCodes {
+    pub fn get_erc1155_balance<S>(
+        &mut self,
+        state: &mut S,
+        token: Address,
+        account: Address,
+        id: U256,
+    ) -> Result<U256, SoflError>
+    where
+        S::Error: Debug,
+        S: BcState,
+    {
+        // signature: balanceOf(address,uint256) -> 0x00fdd58e
+        let call = ERC1155ABI::balanceOfCall { account, id };
+        let calldata = call.abi_encode();
+        let ret = self.cheat_read(state, token, calldata.cvt())?;
+        ERC1155ABI::balanceOfCall::abi_decode_returns(&ret, true)
+            .map(|r| r._0)
+            .map_err(|e| {
+                SoflError::Abi(format!(
+                    "failed to decode balanceOf return: {}",
+                    e
+                ))
+            })
+    }
+
+    /// The balances of the account for each of the ids,
+    /// queried with a single `balanceOfBatch` call.
+    pub fn get_erc1155_balance_batch<S>(
+        &mut self,
+        state: &mut S,
+        token: Address,
+        account: Address,
+        ids: &[U256],
+    ) -> Result<Vec<U256>, SoflError>
+    where
+        S::Error: Debug,
+        S: BcState,
+    {
+        // signature: balanceOfBatch(address[],uint256[]) -> 0x4e1273f4
+        let call = ERC1155ABI::balanceOfBatchCall {
+            accounts: vec![account; ids.len()],
+            ids: ids.to_vec(),
+        };
+        let calldata = call.abi_encode();
+        let ret = self.caller.static_call(
+            state,
+            token,
+            calldata.cvt(),
+            no_inspector(),
+        )?;
+        ERC1155ABI::balanceOfBatchCall::abi_decode_returns(&ret, true)
+            .map(|r| r._0)
+            .map_err(|e| {
+                SoflError::Abi(format!(
+                    "failed to decode balanceOfBatch return: {}",
+                    e
+                ))
+            })
+    }
+
+    // return the old balance if updated
+    pub fn set_erc1155_balance<S>(
+        &mut self,
+        state: &mut S,
+        token: Address,
+        account: Address,
+        id: U256,
+        balance: U256,
+    ) -> Result<Option<U256>, SoflError>
+    where
+        S::Error: Debug,
+        S: BcState,
+    {
+        // the balance is in a mapping keyed by both the account and the id,
+        // whose slot is found by probing balanceOf like ERC20 allowance
+        let call = ERC1155ABI::balanceOfCall { account, id };
+        self.cheat_write(state, token, call.abi_encode().cvt(), balance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            memory::MemoryBcState,
+            types::{Address, U256},
+        },
+        solidity::scripting::{deploy_contracts, SolScriptConfig},
+    };
+
+    use crate::cheatcodes::CheatCodes;
+
+    #[test]
+    fn test_erc1155_balance() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Multi {
+                mapping(uint256 => mapping(address => uint256)) private _balances;
+                constructor() {
+                    _balances[1][address(0x1234)] = 10;
+                }
+                function balanceOf(address account, uint256 id) public view returns (uint256) {
+                    require(account != address(0), "zero address");
+                    return _balances[id][account];
+                }
+                function balanceOfBatch(address[] memory accounts, uint256[] memory ids)
+                    public view returns (uint256[] memory balances)
+                {
+                    require(accounts.length == ids.length, "length mismatch");
+                    balances = new uint256[](accounts.length);
+                    for (uint256 i = 0; i < accounts.length; ++i) {
+                        balances[i] = balanceOf(accounts[i], ids[i]);
+                    }
+                }
+            }
+        "#;
+        let token = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Multi"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let account: Address = 0x1234.cvt();
+        let other: Address = 0x5678.cvt();
+        let (one, two) = (U256::from(1), U256::from(2));
+
+        let mut cheatcodes = CheatCodes::new(1, 17000000);
+        let balance = cheatcodes
+            .get_erc1155_balance(&mut state, token, account, one)
+            .unwrap();
+        assert_eq!(balance, U256::from(10));
+
+        let (x, y) = (U256::from(42), U256::from(5));
+        let old = cheatcodes
+            .set_erc1155_balance(&mut state, token, account, one, x)
+            .unwrap();
+        assert_eq!(old, Some(U256::from(10)));
+        cheatcodes
+            .set_erc1155_balance(&mut state, token, account, two, y)
+            .unwrap();
+        let balances = cheatcodes
+            .get_erc1155_balance_batch(&mut state, token, account, &[one, two])
+            .unwrap();
+        assert_eq!(balances, vec![x, y]);
+
+        // the balances of other accounts are untouched
+        let balances = cheatcodes
+            .get_erc1155_balance_batch(&mut state, token, other, &[one, two])
+            .unwrap();
+        assert_eq!(balances, vec![U256::ZERO, U256::ZERO]);
+    }
+}
+
+#[cfg(test)]
+mod tests_with_dep {
+    use libsofl_core::{
+        blockchain::{provider::BcStateProvider, tx_position::TxPosition},
+        conversion::ConvertTo,
+        engine::types::{Address, U256},
+    };
+
+    use crate::{cheatcodes::CheatCodes, test::get_test_bc_provider};
+
+    #[test]
+    fn test_erc1155_balance_on_mainnet() {
+        let bp = get_test_bc_provider();
+        let fork_at = TxPosition::new(17000001, 0);
+        let mut state = bp.bc_state_at(fork_at).unwrap();
+
+        // adidas Originals: Into the Metaverse
+        let token: Address = "0x28472a58A490c5e09A238847F66A68a47cC76f0f".cvt();
+        let account: Address =
+            "0x1497bF2C336EBE4B8745DF52E190Bd0c8129666a".cvt();
+        let id = U256::from(0);
+
+        let mut cheatcodes = CheatCodes::new(1, 17000001);
+        let before = cheatcodes
+            .get_erc1155_balance(&mut state, token, account, id)
+            .unwrap();
+        let balance = before + U256::from(3);
+        cheatcodes
+            .set_erc1155_balance(&mut state, token, account, id, balance)
+            .unwrap();
+        let after = cheatcodes
+            .get_erc1155_balance(&mut state, token, account, id)
+            .unwrap();
+        assert_eq!(after, balance);
+        let balances = cheatcodes
+            .get_erc1155_balance_batch(&mut state, token, account, &[id])
+            .unwrap();
+        assert_eq!(balances, vec![after]);
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use libsofl_core::engine::types::{Address, Bytes, U256};
+use libsofl_utils::slot::mapping_slot;
+
+use crate::{addressbook::ADDRESS_BOOK, cheatcodes::CheatCodes, types::Chain};
+
+// signature: balanceOf(address) -> 0x70a08231
+const BALANCE_OF: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+// signature: allowance(address,address) -> 0xdd62ed3e
+const ALLOWANCE: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
+
+/// Storage layout of an ERC20 token whose balances and allowances are plain Solidity mappings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenSlotLayout {
+    /// The slot of `mapping(address => uint256)` balances.
+    pub balances: U256,
+    /// The slot of `mapping(address => mapping(address => uint256))` allowances.
+    pub allowances: Option<U256>,
+}
+
+impl TokenSlotLayout {
+    pub fn new(balances: u64, allowances: u64) -> Self {
+        Self {
+            balances: U256::from(balances),
+            allowances: Some(U256::from(allowances)),
+        }
+    }
+
+    /// The slot of the getter call, if it is `balanceOf` or `allowance`.
+    pub fn slot_of(&self, calldata: &Bytes) -> Option<U256> {
+        // the address arguments are already padded to 32 bytes
+        let arg = |i: usize| calldata.get(4 + 32 * i..4 + 32 * (i + 1));
+        match calldata.get(..4)? {
+            s if s == BALANCE_OF && calldata.len() == 36 => {
+                Some(mapping_slot(self.balances, arg(0)?))
+            }
+            s if s == ALLOWANCE && calldata.len() == 68 => {
+                let inner = mapping_slot(self.allowances?, arg(0)?);
+                Some(mapping_slot(inner, arg(1)?))
+            }
+            _ => None,
+        }
+    }
+}
+
+lazy_static! {
+    /// Slot layouts of popular tokens, keyed by (chain id, token).
+    pub static ref KNOWN_TOKEN_SLOTS: HashMap<(u64, Address), TokenSlotLayout> = {
+        let mainnet = Chain::Mainnet;
+        let mut m = HashMap::new();
+        let mut add = |token: Address, layout| {
+            m.insert((mainnet as u64, token), layout);
+        };
+        // FiatTokenV2 (before v2.2, which packs the blacklist flag into balances)
+        add(
+            ADDRESS_BOOK.usdc.must_on_chain(mainnet),
+            TokenSlotLayout::new(9, 10),
+        );
+        add(
+            ADDRESS_BOOK.usdt.must_on_chain(mainnet),
+            TokenSlotLayout::new(2, 5),
+        );
+        add(
+            ADDRESS_BOOK.dai.must_on_chain(mainnet),
+            TokenSlotLayout::new(2, 3),
+        );
+        add(
+            ADDRESS_BOOK.weth.must_on_chain(mainnet),
+            TokenSlotLayout::new(3, 4),
+        );
+        m
+    };
+}
+
+impl CheatCodes {
+    /// Register (or override) the slot layout of a token on the chain of the cheatcodes,
+    /// so that reading and writing its balances and allowances skip the slot probe.
+    pub fn register_token_slots(
+        &mut self,
+        token: Address,
+        layout: TokenSlotLayout,
+    ) {
+        self.known_slots.insert(token, layout);
+    }
+
+    /// Remove the slot layout of a token, so that its slots are probed.
+    pub fn unregister_token_slots(&mut self, token: Address) {
+        self.known_slots.remove(&token);
+    }
+
+    pub(crate) fn known_slot(
+        &self,
+        token: Address,
+        calldata: &Bytes,
+    ) -> Option<U256> {
+        self.known_slots.get(&token)?.slot_of(calldata)
+    }
+}
+
+/// Known slot layouts on the chain.
+pub(crate) fn known_slots_on_chain(
+    chain_id: u64,
+) -> HashMap<Address, TokenSlotLayout> {
+    KNOWN_TOKEN_SLOTS
+        .iter()
+        .filter(|((chain, _), _)| *chain == chain_id)
+        .map(|((_, token), layout)| (*token, *layout))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_sol_types::SolCall;
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            memory::MemoryBcState,
+            state::BcState,
+            types::{AccountInfo, Address, Bytecode, U256},
+        },
+    };
+
+    use crate::{
+        addressbook::{ADDRESS_BOOK, ERC20ABI},
+        cheatcodes::CheatCodes,
+        types::Chain,
+    };
+
+    use super::TokenSlotLayout;
+
+    #[test]
+    fn test_usdc_balance_uses_known_slot() {
+        let usdc = ADDRESS_BOOK.usdc.must_on_chain(Chain::Mainnet);
+        let account: Address = 0x1234.cvt();
+        let calldata = ERC20ABI::balanceOfCall { owner: account }.abi_encode();
+        let slot = TokenSlotLayout::new(9, 10)
+            .slot_of(&calldata.cvt())
+            .unwrap();
+
+        // the code does not implement balanceOf, so the probe would fail
+        let mut state = MemoryBcState::fresh();
+        let code = Bytecode::new_raw(vec![0x00].into());
+        state.insert_account_info(
+            usdc,
+            AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+        );
+        state
+            .insert_account_storage(usdc, slot, U256::from(100))
+            .unwrap();
+
+        let mut cheatcodes = CheatCodes::new(1, 17000000);
+        let balance = cheatcodes
+            .get_erc20_balance(&mut state, usdc, account)
+            .unwrap();
+        assert_eq!(balance, U256::from(100));
+        assert_eq!(cheatcodes.probe_count(), 0);
+
+        // without the known layout, the slot is probed
+        let mut cheatcodes = CheatCodes::new(1, 17000000);
+        cheatcodes.unregister_token_slots(usdc);
+        assert!(cheatcodes
+            .get_erc20_balance(&mut state, usdc, account)
+            .is_err());
+        assert_eq!(cheatcodes.probe_count(), 1);
+
+        // override the layout
+        let mut cheatcodes = CheatCodes::new(1, 17000000);
+        cheatcodes.register_token_slots(usdc, TokenSlotLayout::new(0, 1));
+        let balance = cheatcodes
+            .get_erc20_balance(&mut state, usdc, account)
+            .unwrap();
+        assert_eq!(balance, U256::ZERO);
+    }
+}
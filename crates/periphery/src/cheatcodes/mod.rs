@@ -1,10 +1,6 @@
 // A set of cheatcodes that can directly modify the environments
 
-use std::{
-    any::type_name,
-    collections::{BTreeMap, HashMap},
-    fmt::Debug,
-};
+use std::{any::type_name, collections::HashMap, fmt::Debug};
 
 use crate::{caller::HighLevelCaller, types::SolUint256};
 use alloy_json_abi::Function;
@@ -13,7 +9,7 @@ use libsofl_core::{
     engine::{
         state::BcState,
         transition::get_evm_version,
-        types::{Address, Bytecode, Bytes, B256, U256},
+        types::{Address, Bytecode, Bytes, CfgEnv, SpecId, B256, U256},
     },
     error::SoflError,
 };
@@ -23,8 +19,21 @@ use alloy_sol_types::SolType;
 use inspector::CheatcodeInspector;
 
 mod contract_type;
+mod erc1155;
 mod erc20;
+pub mod known_slots;
 mod price_oracle;
+mod slot_cache;
+
+use known_slots::{known_slots_on_chain, TokenSlotLayout};
+use slot_cache::SlotCache;
+pub use slot_cache::DEFAULT_SLOT_CACHE_CAPACITY;
+
+/// The balance that an impersonated account is topped up to.
+pub const IMPERSONATION_BALANCE: u128 = 1_000 * 10u128.pow(18);
+
+/// The gas limit of the calls from an impersonated account.
+pub const IMPERSONATION_GAS_LIMIT: u64 = 30_000_000;
 
 #[derive(Debug, Clone)]
 enum SlotQueryResult {
@@ -36,11 +45,20 @@ pub struct CheatCodes {
     // runtime env
     inspector: CheatcodeInspector,
 
-    // slot info: (codehash, calldata) -> slot_state
-    slots: BTreeMap<(B256, Bytes), SlotQueryResult>,
+    // slot info: (codehash, calldata) -> slot_state, bounded by LRU eviction
+    slots: SlotCache,
 
     // high-level caller
     caller: HighLevelCaller,
+    evm_version: SpecId,
+    // the impersonated account and its balance before being topped up
+    impersonated: Option<(Address, Option<U256>)>,
+    // whether to probe all candidate slots with a single call
+    batch_probe: bool,
+    // known slot layouts of tokens, which skip the slot probe
+    known_slots: HashMap<Address, TokenSlotLayout>,
+    // number of slot probes, for instrumentation
+    probes: usize,
     // abi parser
     // abi_parser: AbiParser,
     abi_cache: HashMap<String, Function>,
@@ -59,8 +77,13 @@ impl CheatCodes {
             caller: HighLevelCaller::default()
                 .bypass_check()
                 .set_evm_version(evm_version),
+            evm_version,
+            impersonated: None,
             inspector: CheatcodeInspector::default(),
-            slots: BTreeMap::new(),
+            slots: SlotCache::default(),
+            batch_probe: true,
+            known_slots: known_slots_on_chain(chain_id),
+            probes: 0,
             // abi_parser: AbiParser::default(),
             abi_cache: HashMap::new(),
         }
@@ -74,6 +97,31 @@ impl CheatCodes {
         self
     }
 
+    /// Enable or disable probing all candidate slots with a single call when
+    /// a getter reads multiple slots. Enabled by default.
+    pub fn set_batch_probe(mut self, enabled: bool) -> Self {
+        self.batch_probe = enabled;
+        self
+    }
+
+    /// Set the maximum number of cached slot lookups.
+    /// The least recently used lookups are evicted beyond the capacity.
+    /// Defaults to `DEFAULT_SLOT_CACHE_CAPACITY`.
+    pub fn with_slot_cache_capacity(mut self, capacity: usize) -> Self {
+        self.slots.set_capacity(capacity);
+        self
+    }
+
+    /// The number of slot probes run so far.
+    pub fn probe_count(&self) -> usize {
+        self.probes
+    }
+
+    /// The number of cached slot lookups.
+    pub fn cached_slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
     pub fn reset_caller(&mut self) {
         self.caller = HighLevelCaller::default().bypass_check();
     }
@@ -100,6 +148,8 @@ impl CheatCodes {
         S: BcState,
         S::Error: Debug,
     {
+        self.probes += 1;
+
         // staticcall to get the slot, where we force the return type as u256
         self.inspector.reset_access_recording();
         let ret = self.caller.static_call(
@@ -137,43 +187,37 @@ impl CheatCodes {
                     return Some(slot);
                 }
             } else {
-                // there are multiple reads, we need to check if the data is the same
-                let magic = U256::from(0xdeadbeefu64);
+                // there are multiple reads, only the slots holding the returned value
+                // can be the target
+                let mut candidates = Vec::new();
                 for slot in raccesses {
-                    let prev = state.storage(to, slot).ok()?;
-                    if cdata != prev.into() {
+                    if candidates.contains(&slot) {
                         continue;
                     }
+                    if state.storage(to, slot).ok()? == cdata {
+                        candidates.push(slot);
+                    }
+                }
 
-                    // update the target slot
-                    state
-                        .insert_account_storage(to, slot, magic)
-                        .expect("insert should not fail");
-
-                    // we have to do another call to check if the slot is correct,
-                    // because changing the slot might change the program flow
-                    self.inspector.disable_access_recording();
-                    let ret = self
-                        .caller
-                        .static_call(
-                            state,
-                            to,
-                            calldata.clone(),
-                            &mut self.inspector,
-                        )
-                        .ok()?;
-                    let cdata = SolUint256::abi_decode(&ret, false);
-                    if cdata.is_err() {
+                if self.batch_probe {
+                    if let Some(slot) =
+                        self.probe_slots(state, to, &calldata, &candidates)
+                    {
+                        return Some(slot);
+                    }
+                    if candidates.len() == 1 {
                         return None;
                     }
-                    let cdata = cdata.unwrap();
-
-                    state
-                        .insert_account_storage(to, slot, prev)
-                        .expect("insert should not fail");
+                }
 
-                    if magic == cdata {
-                        // we got the slot!
+                // probe the candidates one by one
+                for slot in candidates {
+                    if let Some(slot) = self.probe_slots(
+                        state,
+                        to,
+                        &calldata,
+                        std::slice::from_ref(&slot),
+                    ) {
                         return Some(slot);
                     }
                 }
@@ -182,6 +226,56 @@ impl CheatCodes {
 
         None
     }
+
+    /// Write a distinct magic value to each candidate slot and re-execute the call once.
+    /// The candidate whose magic value is returned is the target slot.
+    /// All candidates are restored to their original values afterwards.
+    fn probe_slots<S>(
+        &mut self,
+        state: &mut S,
+        to: Address,
+        calldata: &Bytes,
+        candidates: &[U256],
+    ) -> Option<U256>
+    where
+        S: BcState,
+        S::Error: Debug,
+    {
+        if candidates.is_empty() {
+            return None;
+        }
+        let magic = U256::from(0xdeadbeefu64);
+        let mut prevs = Vec::with_capacity(candidates.len());
+        for (i, slot) in candidates.iter().enumerate() {
+            prevs.push(state.storage(to, *slot).ok()?);
+            state
+                .insert_account_storage(to, *slot, magic + U256::from(i))
+                .expect("insert should not fail");
+        }
+
+        // we have to do another call to check if the slot is correct,
+        // because changing the slot might change the program flow
+        self.inspector.disable_access_recording();
+        let ret = self.caller.static_call(
+            state,
+            to,
+            calldata.clone(),
+            &mut self.inspector,
+        );
+
+        for (slot, prev) in candidates.iter().zip(prevs) {
+            state
+                .insert_account_storage(to, *slot, prev)
+                .expect("insert should not fail");
+        }
+
+        let cdata = SolUint256::abi_decode(&ret.ok()?, false).ok()?;
+        let i = cdata.checked_sub(magic)?;
+        if i >= U256::from(candidates.len()) {
+            return None;
+        }
+        Some(candidates[i.to::<usize>()])
+    }
 }
 
 // cheatcode: cheat_read
@@ -204,13 +298,23 @@ impl CheatCodes {
         // let rtypes: Vec<ParamType> =
         // func.outputs.iter().map(|p| p.kind.clone()).collect();
         // let rtypes = rtypes.as_slice();
+        if let Some(slot) = self.known_slot(to, &calldata) {
+            let v: U256 = state.storage(to, slot).map_err(|e| {
+                SoflError::BcState(format!(
+                    "failed to read storage value: {:?}",
+                    e
+                ))
+            })?;
+            return Ok(v.cvt());
+        }
+
         if let Ok(Some(account_info)) = state.basic(to) {
             // let calldata = pack_calldata(func.short_signature(), args);
             let code_hash = account_info.code_hash;
             match self.slots.get(&(code_hash, calldata.clone())) {
                 Some(SlotQueryResult::Found(slot)) => {
-                    // return self.decode_from_storage(state, to, *slot, rtypes);
-                    let v: U256 = state.storage(to, *slot).map_err(|e| {
+                    // return self.decode_from_storage(state, to, slot, rtypes);
+                    let v: U256 = state.storage(to, slot).map_err(|e| {
                         SoflError::BcState(format!(
                             "failed to read storage value: {:?}",
                             e
@@ -290,6 +394,10 @@ impl CheatCodes {
         S::Error: Debug,
         S: BcState,
     {
+        if let Some(slot) = self.known_slot(to, &calldata) {
+            return self.write_or_err(state, to, slot, data);
+        }
+
         let account_info = state
             .basic(to)
             .map_err(|e| {
@@ -307,7 +415,7 @@ impl CheatCodes {
         let code_hash = account_info.code_hash;
         match self.slots.get(&(code_hash, calldata.clone())) {
             Some(SlotQueryResult::Found(slot)) => {
-                self.write_or_err(state, to, *slot, data)
+                self.write_or_err(state, to, slot, data)
             }
             Some(SlotQueryResult::NotFound) => Err(SoflError::BcState(
                 format!("{}: cannot find the target slot", type_name::<Self>(),),
@@ -368,6 +476,69 @@ impl CheatCodes {
     }
 }
 
+// cheatcode: impersonate
+impl CheatCodes {
+    /// Impersonate the account, e.g., a whale or a contract without a
+    /// private key, like Foundry's `startPrank`.
+    /// The returned caller sends calls from the account.
+    /// Unlike `HighLevelCaller::bypass_check`, the balance of the account is
+    /// checked, so it is topped up to `IMPERSONATION_BALANCE` if needed.
+    /// The original balance is restored by `stop_impersonate`.
+    /// Impersonating another account stops the current impersonation.
+    pub fn impersonate<S: BcState>(
+        &mut self,
+        state: &mut S,
+        account: Address,
+    ) -> Result<HighLevelCaller, SoflError>
+    where
+        S::Error: Debug,
+    {
+        self.stop_impersonate(state)?;
+        let balance = self.get_balance(state, account)?;
+        let target = U256::from(IMPERSONATION_BALANCE);
+        let original = if balance < target {
+            self.set_balance(state, account, target)?;
+            Some(balance)
+        } else {
+            None
+        };
+        self.impersonated = Some((account, original));
+
+        let mut cfg = CfgEnv::default();
+        // contracts can send transactions
+        cfg.disable_eip3607 = true;
+        cfg.disable_base_fee = true;
+        Ok(HighLevelCaller::new(account)
+            .set_cfg(cfg)
+            .set_evm_version(self.evm_version)
+            .set_gas_limit(IMPERSONATION_GAS_LIMIT))
+    }
+
+    /// Stop impersonating the account, restoring the balance it had before
+    /// being topped up, regardless of what it has spent or received since.
+    /// Returns the account that was impersonated, if any.
+    pub fn stop_impersonate<S: BcState>(
+        &mut self,
+        state: &mut S,
+    ) -> Result<Option<Address>, SoflError>
+    where
+        S::Error: Debug,
+    {
+        let Some((account, original)) = self.impersonated.take() else {
+            return Ok(None);
+        };
+        if let Some(balance) = original {
+            self.set_balance(state, account, balance)?;
+        }
+        Ok(Some(account))
+    }
+
+    /// The account being impersonated.
+    pub fn impersonated(&self) -> Option<Address> {
+        self.impersonated.map(|(account, _)| account)
+    }
+}
+
 // Functions that does not need to access cache
 impl CheatCodes {
     pub fn get_balance<S: BcState>(
@@ -438,6 +609,32 @@ impl CheatCodes {
         }
     }
 
+    /// Whether the account has code, e.g., a contract that is deployed
+    /// and not self-destructed.
+    pub fn is_contract<S: BcState>(
+        &mut self,
+        state: &mut S,
+        account: Address,
+    ) -> Result<bool, SoflError>
+    where
+        S::Error: Debug,
+    {
+        Ok(state.code_size(account)? > 0)
+    }
+
+    /// Whether the account has no code.
+    /// Note that a contract under construction also has no code.
+    pub fn is_eoa<S: BcState>(
+        &mut self,
+        state: &mut S,
+        account: Address,
+    ) -> Result<bool, SoflError>
+    where
+        S::Error: Debug,
+    {
+        Ok(!self.is_contract(state, account)?)
+    }
+
     pub fn set_balance<S: BcState>(
         &mut self,
         state: &mut S,
@@ -467,6 +664,313 @@ impl CheatCodes {
 
         Ok(Some(old_balance))
     }
+
+    /// The nonce of the account, which is zero if the account does not exist.
+    pub fn get_nonce<S: BcState>(
+        &mut self,
+        state: &mut S,
+        account: Address,
+    ) -> Result<u64, SoflError>
+    where
+        S::Error: Debug,
+    {
+        state
+            .basic(account)
+            .map_err(|e| {
+                SoflError::BcState(format!(
+                    "failed to get account basic: {:?}",
+                    e
+                ))
+            })?
+            .map_or(Ok(0), |info| Ok(info.nonce))
+    }
+
+    /// Set the nonce of the account, which determines the address of
+    /// the next contract created by it with CREATE.
+    /// Returns the old nonce if it is changed.
+    pub fn set_nonce<S: BcState>(
+        &mut self,
+        state: &mut S,
+        address: Address,
+        nonce: u64,
+    ) -> Result<Option<u64>, SoflError>
+    where
+        S::Error: Debug,
+    {
+        let mut account_info = state
+            .basic(address)
+            .map_err(|e| {
+                SoflError::BcState(format!(
+                    "failed to get account basic: {:?}",
+                    e
+                ))
+            })?
+            .unwrap_or_default();
+        let old_nonce = account_info.nonce;
+
+        if old_nonce == nonce {
+            return Ok(None);
+        }
+
+        account_info.nonce = nonce;
+        state.insert_account_info(address, account_info);
+
+        Ok(Some(old_nonce))
+    }
+
+    /// Increment the nonce of the account and return the new nonce.
+    pub fn increment_nonce<S: BcState>(
+        &mut self,
+        state: &mut S,
+        address: Address,
+    ) -> Result<u64, SoflError>
+    where
+        S::Error: Debug,
+    {
+        let nonce = self.get_nonce(state, address)?;
+        let nonce = nonce.checked_add(1).ok_or_else(|| {
+            SoflError::BcState(format!("nonce of {} overflows", address))
+        })?;
+        self.set_nonce(state, address, nonce)?;
+        Ok(nonce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+    use alloy_json_abi::Function;
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            inspector::no_inspector,
+            memory::MemoryBcState,
+            state::BcState,
+            types::{Address, Bytes, SpecId, U256},
+        },
+        solidity::{
+            caller::HighLevelCaller,
+            scripting::{deploy_contracts, SolScriptConfig},
+        },
+    };
+    use libsofl_utils::address::compute_create_address;
+
+    use super::CheatCodes;
+
+    fn probe_multi_read_getter(batch_probe: bool) {
+        let mut state = MemoryBcState::fresh();
+        // the getter reads three slots holding the same value
+        let code = r#"
+            contract Token {
+                uint256 public fee = 7;
+                uint256 public cap = 7;
+                mapping(address => uint256) private balances;
+                constructor() {
+                    balances[address(0x1234)] = 7;
+                }
+                function balanceOf(address a) public view returns (uint256) {
+                    require(fee <= cap);
+                    return balances[a];
+                }
+            }
+        "#;
+        let token = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Token"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let account: Address = 0x1234.cvt();
+        let calldata: Bytes = Function::parse("balanceOf(address)")
+            .unwrap()
+            .abi_encode_input(&[DynSolValue::Address(account)])
+            .unwrap()
+            .cvt();
+
+        let mut cheatcodes =
+            CheatCodes::new(1, 17000000).set_batch_probe(batch_probe);
+        cheatcodes
+            .cheat_write(&mut state, token, calldata.clone(), U256::from(100))
+            .unwrap();
+        let ret = cheatcodes.cheat_read(&mut state, token, calldata).unwrap();
+        assert_eq!(ret, U256::from(100).cvt());
+
+        // other slots are restored after probing
+        let fee = Function::parse("fee()").unwrap().selector().to_vec();
+        let ret = cheatcodes.cheat_read(&mut state, token, fee.cvt()).unwrap();
+        assert_eq!(ret, U256::from(7).cvt());
+    }
+
+    #[test]
+    fn test_batch_probe_multi_read_getter() {
+        probe_multi_read_getter(true);
+    }
+
+    #[test]
+    fn test_sequential_probe_multi_read_getter() {
+        probe_multi_read_getter(false);
+    }
+
+    #[test]
+    fn test_is_contract() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Bomb {
+                function kill() public {
+                    selfdestruct(payable(msg.sender));
+                }
+            }
+        "#;
+        let bomb = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Bomb"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let eoa: Address = 0x1234.cvt();
+        state.add_ether_balance(eoa, U256::from(1000)).unwrap();
+
+        let mut cheatcodes = CheatCodes::new(1, 17000000);
+        assert!(cheatcodes.is_contract(&mut state, bomb).unwrap());
+        assert!(!cheatcodes.is_eoa(&mut state, bomb).unwrap());
+        assert!(cheatcodes.is_eoa(&mut state, eoa).unwrap());
+        assert!(!cheatcodes.is_contract(&mut state, eoa).unwrap());
+
+        // the code is removed by selfdestruct before Cancun
+        HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::SHANGHAI)
+            .invoke(&mut state, bomb, "kill()", &[], None, no_inspector())
+            .unwrap();
+        assert!(cheatcodes.is_eoa(&mut state, bomb).unwrap());
+    }
+
+    #[test]
+    fn test_impersonate_zero_balance_account() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Vault {
+                mapping(address => uint256) public deposits;
+                function deposit() external payable {
+                    deposits[msg.sender] += msg.value;
+                }
+            }
+        "#;
+        let vault = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Vault"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let account: Address = 0xdead.cvt();
+        let value = Some(U256::from(10).pow(U256::from(18)));
+
+        let mut cheatcodes = CheatCodes::new(1, 17000000);
+        assert_eq!(
+            cheatcodes.get_balance(&mut state, account).unwrap(),
+            U256::ZERO
+        );
+        let caller = cheatcodes.impersonate(&mut state, account).unwrap();
+        assert_eq!(cheatcodes.impersonated(), Some(account));
+        caller
+            .invoke(&mut state, vault, "deposit()", &[], value, no_inspector())
+            .unwrap();
+        let deposit = caller
+            .view(
+                &mut state,
+                vault,
+                "deposits(address) returns (uint256)",
+                &[DynSolValue::Address(account)],
+                no_inspector(),
+            )
+            .unwrap();
+        assert_eq!(deposit[0].as_uint().unwrap().0, value.unwrap());
+
+        // the original balance is restored
+        let stopped = cheatcodes.stop_impersonate(&mut state).unwrap();
+        assert_eq!(stopped, Some(account));
+        assert_eq!(
+            cheatcodes.get_balance(&mut state, account).unwrap(),
+            U256::ZERO
+        );
+        let err = caller.invoke(
+            &mut state,
+            vault,
+            "deposit()",
+            &[],
+            value,
+            no_inspector(),
+        );
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_stop_impersonate_restores_balance() {
+        let mut state = MemoryBcState::fresh();
+        let account: Address = 0xdead.cvt();
+        let receiver: Address = 0xbeef.cvt();
+        let ether = U256::from(10).pow(U256::from(18));
+
+        let mut cheatcodes = CheatCodes::new(1, 17000000);
+        cheatcodes.set_balance(&mut state, account, ether).unwrap();
+        let caller = cheatcodes.impersonate(&mut state, account).unwrap();
+        // the balance changes while impersonating
+        caller
+            .call(
+                &mut state,
+                receiver,
+                Bytes::new(),
+                Some(ether * U256::from(2)),
+                no_inspector(),
+            )
+            .unwrap();
+        cheatcodes
+            .set_balance(&mut state, account, ether * U256::from(7))
+            .unwrap();
+
+        cheatcodes.stop_impersonate(&mut state).unwrap();
+        assert_eq!(cheatcodes.get_balance(&mut state, account).unwrap(), ether);
+        assert_eq!(
+            cheatcodes.get_balance(&mut state, receiver).unwrap(),
+            ether * U256::from(2)
+        );
+    }
+
+    #[test]
+    fn test_set_nonce_predicts_create_address() {
+        let mut state = MemoryBcState::fresh();
+        let deployer: Address = 0x1234.cvt();
+        let mut cheatcodes = CheatCodes::new(1, 17000000);
+        assert_eq!(cheatcodes.get_nonce(&mut state, deployer).unwrap(), 0);
+        assert_eq!(
+            cheatcodes.set_nonce(&mut state, deployer, 5).unwrap(),
+            Some(0)
+        );
+        let unchanged = cheatcodes.set_nonce(&mut state, deployer, 5).unwrap();
+        assert_eq!(unchanged, None);
+        assert_eq!(
+            cheatcodes.increment_nonce(&mut state, deployer).unwrap(),
+            6
+        );
+
+        let expected = compute_create_address(deployer, 6);
+        // the init code returns empty runtime code
+        let (_, created) = HighLevelCaller::new(deployer)
+            .bypass_check()
+            .create(&mut state, None, &[0x00], None, no_inspector())
+            .unwrap();
+        assert_eq!(created, Some(expected));
+        assert_eq!(cheatcodes.get_nonce(&mut state, deployer).unwrap(), 7);
+    }
 }
 
 #[cfg(test)]
@@ -504,4 +1008,17 @@ mod tests_with_dep {
         assert_eq!(balance1, U256::from(1299267380));
         assert_eq!(balance2, U256::from(1299267380));
     }
+
+    #[test]
+    fn test_is_contract_on_mainnet() {
+        let bp = get_test_bc_provider();
+        let fork_at = TxPosition::new(17000001, 0);
+        let mut state = bp.bc_state_at(fork_at).unwrap();
+        let mut cheatcodes = CheatCodes::new(1, 17000001);
+
+        let usdt: Address = "0xdAC17F958D2ee523a2206206994597C13D831ec7".cvt();
+        let eoa: Address = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".cvt();
+        assert!(cheatcodes.is_contract(&mut state, usdt).unwrap());
+        assert!(cheatcodes.is_eoa(&mut state, eoa).unwrap());
+    }
 }
@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, HashMap};
+
+use libsofl_core::engine::types::{Bytes, B256};
+
+use super::SlotQueryResult;
+
+/// The default number of slot lookups kept by the cache.
+pub const DEFAULT_SLOT_CACHE_CAPACITY: usize = 1 << 16;
+
+type SlotKey = (B256, Bytes);
+
+/// SlotCache caches the slot lookups of getters, keyed by (codehash, calldata).
+/// Once the capacity is reached, the least recently used lookup is evicted.
+/// Since the slot of a getter is deterministic per code hash,
+/// an evicted lookup only needs to be rediscovered.
+#[derive(Debug, Clone)]
+pub(crate) struct SlotCache {
+    capacity: usize,
+    // the logical time of the last access
+    tick: u64,
+    entries: HashMap<SlotKey, (SlotQueryResult, u64)>,
+    // last access time -> key, the oldest entry comes first
+    recency: BTreeMap<u64, SlotKey>,
+}
+
+impl Default for SlotCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOT_CACHE_CAPACITY)
+    }
+}
+
+impl SlotCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    /// Change the capacity, evicting the least recently used lookups if needed.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the lookup is cached, without marking it as used.
+    #[cfg(test)]
+    pub(crate) fn contains(&self, key: &SlotKey) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub(crate) fn get(&mut self, key: &SlotKey) -> Option<SlotQueryResult> {
+        let tick = self.next_tick();
+        let (result, last) = self.entries.get_mut(key)?;
+        let key = self.recency.remove(&*last).expect("recency out of sync");
+        *last = tick;
+        let result = result.clone();
+        self.recency.insert(tick, key);
+        Some(result)
+    }
+
+    pub(crate) fn insert(&mut self, key: SlotKey, result: SlotQueryResult) {
+        if self.capacity == 0 {
+            return;
+        }
+        let tick = self.next_tick();
+        if let Some((_, last)) =
+            self.entries.insert(key.clone(), (result, tick))
+        {
+            self.recency.remove(&last);
+        }
+        self.recency.insert(tick, key);
+        self.evict();
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let (_, key) =
+                self.recency.pop_first().expect("recency out of sync");
+            self.entries.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::engine::types::{Bytes, B256, U256};
+
+    use super::{SlotCache, SlotQueryResult};
+
+    fn key(i: u8) -> (B256, Bytes) {
+        (B256::with_last_byte(i), Bytes::from(vec![i]))
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() {
+        let mut cache = SlotCache::new(3);
+        for i in 0..3 {
+            cache.insert(key(i), SlotQueryResult::Found(U256::from(i)));
+        }
+        // use the oldest entry, so that key(1) becomes the oldest
+        assert!(matches!(
+            cache.get(&key(0)),
+            Some(SlotQueryResult::Found(v)) if v == U256::ZERO
+        ));
+
+        cache.insert(key(3), SlotQueryResult::NotFound);
+        cache.insert(key(4), SlotQueryResult::Found(U256::from(4)));
+        assert_eq!(cache.len(), 3);
+        assert!(!cache.contains(&key(1)));
+        assert!(!cache.contains(&key(2)));
+        assert!(cache.contains(&key(0)));
+        assert!(matches!(
+            cache.get(&key(3)),
+            Some(SlotQueryResult::NotFound)
+        ));
+        assert!(cache.get(&key(4)).is_some());
+
+        // shrinking evicts the oldest entries
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains(&key(4)));
+    }
+}
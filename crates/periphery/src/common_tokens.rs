@@ -0,0 +1,240 @@
+use libsofl_core::{
+    conversion::ConvertTo,
+    engine::{
+        memory::EmptyMemoryBcState,
+        state::BcState,
+        types::{AccountInfo, Address, Bytecode, Bytes, U256},
+    },
+};
+
+use crate::{addressbook::ADDRESS_BOOK, types::Chain};
+
+/// Runtime bytecode of a minimal ERC20 token, hand-assembled.
+///
+/// It implements `balanceOf`, `allowance`, `totalSupply`, `decimals`,
+/// `transfer`, `transferFrom`, and `approve` with the standard events,
+/// where balances and allowances are Solidity mappings,
+/// and infinite allowances are not spent.
+/// WETH tokens additionally implement `deposit` (also the fallback) and `withdraw`,
+/// and their total supply is the ether balance of the token.
+///
+/// The code is followed by a 5-byte trailer read with `CODECOPY`:
+/// the slots of balances, allowances, and total supply, decimals, and whether it is WETH.
+const MINIMAL_ERC20_CODE: &str = concat!(
+    "60013860059003609f396001386004900360bf396001386003900360df396001",
+    "386002900360ff396001386001900361011f39600436106100a45760003560e0",
+    "1c806370a08231146100af578063dd62ed3e146100ca57806318160ddd146100",
+    "f3578063313ce56714610112578063a9059cbb1461017157806323b872dd1461",
+    "0180578063095ea7b31461011e578063d0e30db0146102445780632e1a7d4d14",
+    "61028f575b610244565b60006000fd5b60043560005260805160205260406000",
+    "205460005260206000f35b60243560043560005260a051602052604060002060",
+    "205260005260406000205460005260206000f35b610100516101085760c05154",
+    "60005260206000f35b4760005260206000f35b60e05160005260206000f35b60",
+    "243560043581813360005260a051602052604060002060205260005260406000",
+    "205590600052337f8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e",
+    "5b200ac8c7c3b92560206000a3610239565b610239602435600435336101d656",
+    "5b60043533146101c5573360043560005260a051602052604060002060205260",
+    "0052604060002080548019156101c2576044358082106100a957900390556101",
+    "c5565b50505b6102396044356024356004356101d6565b806000526080516020",
+    "52604060002080548085116100a9578490039055816000526080516020526040",
+    "6000208054840190558260005281817fddf252ad1be2c89b69c2b068fc378daa",
+    "952ba7f163c4a11628f55a4df523b3ef60206000a3505050565b600160005260",
+    "206000f35b61010051156100a957336000526080516020526040600020805434",
+    "01905534600052337fe1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c4607",
+    "51c2402c5c5cc9109c60206000a2005b61010051156100a95760043533600052",
+    "608051602052604060002080548083116100a957829003905560006000600060",
+    "0084335af1156100a957600052337f7fcf532c15f0a6db0bd6d0e038bea71d30",
+    "d808c7d98cb3bf7268a95bf5081b6560206000a200",
+);
+
+/// The runtime bytecode of a minimal ERC20 token with the storage layout.
+/// `total_supply` is ignored for WETH tokens.
+pub fn minimal_erc20_code(
+    balances: u8,
+    allowances: u8,
+    total_supply: u8,
+    decimals: u8,
+    weth: bool,
+) -> Bytecode {
+    let code: Bytes = MINIMAL_ERC20_CODE.cvt();
+    let mut code = code.to_vec();
+    code.extend([balances, allowances, total_supply, decimals, weth as u8]);
+    Bytecode::new_raw(code.into())
+}
+
+/// Well-known tokens that can be preloaded into a state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommonToken {
+    Weth,
+    Usdc,
+    Usdt,
+    Dai,
+}
+
+impl CommonToken {
+    pub const ALL: [CommonToken; 4] = [
+        CommonToken::Weth,
+        CommonToken::Usdc,
+        CommonToken::Usdt,
+        CommonToken::Dai,
+    ];
+
+    /// The canonical address of the token on the chain, if any.
+    pub fn address(&self, chain_id: u64) -> Option<Address> {
+        let chain = Chain::try_from(chain_id).ok()?;
+        let addr = match self {
+            CommonToken::Weth => &ADDRESS_BOOK.weth,
+            CommonToken::Usdc => &ADDRESS_BOOK.usdc,
+            CommonToken::Usdt => &ADDRESS_BOOK.usdt,
+            CommonToken::Dai => &ADDRESS_BOOK.dai,
+        };
+        addr.on_chain(chain)
+    }
+
+    pub fn decimals(&self) -> u8 {
+        match self {
+            CommonToken::Weth | CommonToken::Dai => 18,
+            CommonToken::Usdc | CommonToken::Usdt => 6,
+        }
+    }
+
+    /// The minimal token code, with the same slots of balances, allowances,
+    /// and total supply as the deployed token, so that cheatcodes work on it.
+    pub fn code(&self) -> Bytecode {
+        let decimals = self.decimals();
+        match self {
+            CommonToken::Weth => minimal_erc20_code(3, 4, 0, decimals, true),
+            // FiatTokenV2
+            CommonToken::Usdc => minimal_erc20_code(9, 10, 11, decimals, false),
+            CommonToken::Usdt => minimal_erc20_code(2, 5, 1, decimals, false),
+            CommonToken::Dai => minimal_erc20_code(2, 3, 1, decimals, false),
+        }
+    }
+
+    /// Deploy the minimal token code at the canonical address on the chain.
+    /// Returns None if the token does not exist on the chain.
+    pub fn deploy<S: BcState>(
+        &self,
+        state: &mut S,
+        chain_id: u64,
+    ) -> Option<Address> {
+        let addr = self.address(chain_id)?;
+        let code = self.code();
+        state.insert_account_info(
+            addr,
+            AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+        );
+        Some(addr)
+    }
+}
+
+/// States preloaded with well-known tokens, which avoids forking a chain just to have a token around.
+pub trait CommonTokens: Sized {
+    /// A fresh state with all common tokens on the chain.
+    fn with_common_tokens(chain_id: u64) -> Self {
+        Self::with_tokens(chain_id, &CommonToken::ALL)
+    }
+
+    /// A fresh state with the tokens on the chain.
+    fn with_tokens(chain_id: u64, tokens: &[CommonToken]) -> Self;
+}
+
+impl CommonTokens for EmptyMemoryBcState {
+    fn with_tokens(chain_id: u64, tokens: &[CommonToken]) -> Self {
+        let mut state = EmptyMemoryBcState::fresh();
+        for token in tokens {
+            token.deploy(&mut state, chain_id);
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            inspector::no_inspector,
+            memory::EmptyMemoryBcState,
+            types::{Address, SpecId, U256},
+        },
+    };
+
+    use crate::{
+        addressbook::ADDRESS_BOOK, caller::HighLevelCaller,
+        cheatcodes::CheatCodes, types::Chain,
+    };
+
+    use super::{CommonToken, CommonTokens};
+
+    #[test]
+    fn test_transfer_preloaded_weth() {
+        let mut state = EmptyMemoryBcState::with_common_tokens(1);
+        let weth = ADDRESS_BOOK.weth.must_on_chain(Chain::Mainnet);
+        let alice: Address = 0x1234.cvt();
+        let bob: Address = 0x5678.cvt();
+
+        let mut cheatcodes = CheatCodes::new(1, 17000000);
+        let amount = U256::from(10).pow(U256::from(18));
+        cheatcodes
+            .set_erc20_balance(&mut state, weth, alice, amount)
+            .unwrap();
+        assert_eq!(
+            cheatcodes.get_erc20_total_supply(&mut state, weth).unwrap(),
+            amount
+        );
+
+        let caller = HighLevelCaller::from(alice)
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST);
+        let ret = caller
+            .invoke(
+                &mut state,
+                weth,
+                "transfer(address,uint256) returns (bool)",
+                &[bob.into(), (amount / U256::from(4)).into()],
+                None,
+                no_inspector(),
+            )
+            .unwrap();
+        assert_eq!(ret[0].as_bool(), Some(true));
+
+        let mut balance = |state: &mut EmptyMemoryBcState, account| {
+            cheatcodes.get_erc20_balance(state, weth, account).unwrap()
+        };
+        let rest = amount * U256::from(3) / U256::from(4);
+        assert_eq!(balance(&mut state, alice), rest);
+        assert_eq!(balance(&mut state, bob), amount / U256::from(4));
+    }
+
+    #[test]
+    fn test_deal_probes_preloaded_token() {
+        let mut state =
+            EmptyMemoryBcState::with_tokens(1, &[CommonToken::Usdc]);
+        let usdc = CommonToken::Usdc.address(1).unwrap();
+        let account: Address = 0x1234.cvt();
+
+        // the slots are found by probing the minimal code
+        let mut cheatcodes = CheatCodes::new(1, 17000000);
+        cheatcodes.unregister_token_slots(usdc);
+        cheatcodes
+            .set_erc20_balance(&mut state, usdc, account, U256::from(100))
+            .unwrap();
+        assert_eq!(
+            cheatcodes
+                .get_erc20_balance(&mut state, usdc, account)
+                .unwrap(),
+            U256::from(100)
+        );
+        assert_eq!(
+            cheatcodes.get_erc20_decimals(&mut state, usdc).unwrap(),
+            U256::from(6)
+        );
+
+        // tokens not on the chain are skipped
+        let weth = CommonToken::Weth.address(1).unwrap();
+        assert!(cheatcodes
+            .get_erc20_balance(&mut state, weth, account)
+            .is_err());
+    }
+}
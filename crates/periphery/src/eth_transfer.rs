@@ -0,0 +1,179 @@
+use std::ops::Range;
+
+use libsofl_core::{
+    blockchain::{
+        provider::{BcProvider, BcStateProvider},
+        transaction::Tx,
+    },
+    conversion::ConvertTo,
+    engine::{
+        state::BcState,
+        transition::TransitionSpecBuilder,
+        types::{Address, BcStateRef, BlockNumber, TxHash, U256},
+    },
+    error::SoflError,
+};
+
+use crate::call_extract::{CallExtractInspector, CallKind, ExtractedCall};
+
+/// How the ether is transferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EthTransferKind {
+    /// The value of the transaction itself.
+    Transaction,
+    /// The value of an internal message call or contract creation.
+    Internal,
+    /// The balance sent to the beneficiary of SELFDESTRUCT.
+    SelfDestruct,
+}
+
+/// An ether transfer that takes effect on chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthTransfer {
+    pub block: BlockNumber,
+    pub tx: TxHash,
+    pub kind: EthTransferKind,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+}
+
+/// Enumerate the ether transfers from or to `address` in the block range,
+/// including internal transfers of message calls and selfdestruct.
+/// Transfers in reverted frames are excluded.
+///
+/// Each block is replayed with the call-extract inspector, so this is expensive for large ranges.
+pub fn eth_transfers<T, S, P>(
+    provider: &P,
+    address: Address,
+    range: Range<BlockNumber>,
+) -> Result<Vec<EthTransfer>, SoflError>
+where
+    T: Tx,
+    S: BcStateRef,
+    S::Error: std::fmt::Debug,
+    P: BcProvider<T> + BcStateProvider<S>,
+{
+    let mut transfers = Vec::new();
+    for bn in range {
+        let txs = provider.txs_in_block(bn.cvt())?;
+        let hashes: Vec<TxHash> = txs.iter().map(|tx| tx.hash()).collect();
+        let mut spec_builder =
+            TransitionSpecBuilder::default().at_block(provider, bn);
+        for tx in txs {
+            spec_builder = spec_builder.append_tx(tx);
+        }
+        let spec = spec_builder.build()?;
+
+        let mut insp = CallExtractInspector::default();
+        let mut state = provider.bc_state_at(bn.cvt())?;
+        state.transit(spec, &mut insp)?;
+
+        // each transaction starts with a frame of depth 0
+        let mut tx_index = None;
+        // effective success of the executing frames
+        let mut committed: Vec<bool> = Vec::new();
+        for call in &insp.calls {
+            if call.depth == 0 {
+                tx_index = Some(tx_index.map_or(0, |i| i + 1));
+            }
+            committed.truncate(call.depth);
+            let parent_committed = committed.last().cloned().unwrap_or(true);
+            committed.push(parent_committed && call.success);
+            if !committed[call.depth] {
+                continue;
+            }
+            let tx = hashes[tx_index.expect("impossible: no transaction")];
+            if let Some(transfer) = to_transfer(bn, tx, call) {
+                if transfer.from == address || transfer.to == address {
+                    transfers.push(transfer);
+                }
+            }
+        }
+    }
+    Ok(transfers)
+}
+
+fn to_transfer(
+    block: BlockNumber,
+    tx: TxHash,
+    call: &ExtractedCall,
+) -> Option<EthTransfer> {
+    // the ether of CALLCODE stays in the account of the caller
+    if call.value == U256::ZERO
+        || call.from == call.to
+        || call.kind == CallKind::CallCode
+    {
+        return None;
+    }
+    let kind = match call.kind {
+        CallKind::SelfDestruct => EthTransferKind::SelfDestruct,
+        _ if call.depth == 0 => EthTransferKind::Transaction,
+        _ => EthTransferKind::Internal,
+    };
+    Some(EthTransfer {
+        block,
+        tx,
+        kind,
+        from: call.from,
+        to: call.to,
+        value: call.value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::types::{Bytes, TxHash, U256},
+    };
+
+    use crate::call_extract::{CallKind, ExtractedCall};
+
+    use super::{to_transfer, EthTransferKind};
+
+    #[test]
+    fn test_callcode_is_not_a_transfer() {
+        let call = |kind| ExtractedCall {
+            kind,
+            depth: 1,
+            trace_address: vec![0],
+            from: 0x1.cvt(),
+            to: 0x2.cvt(),
+            value: U256::from(100),
+            input: Bytes::new(),
+            gas: 100000,
+            gas_used: 0,
+            success: true,
+        };
+        let transfer = to_transfer(0, TxHash::ZERO, &call(CallKind::Call));
+        assert_eq!(transfer.unwrap().kind, EthTransferKind::Internal);
+        let transfer = to_transfer(0, TxHash::ZERO, &call(CallKind::CallCode));
+        assert_eq!(transfer, None);
+    }
+}
+
+#[cfg(test)]
+mod tests_with_dep {
+    use crate::{
+        addressbook::ADDRESS_BOOK, test::get_test_bc_provider, types::Chain,
+    };
+
+    use super::{eth_transfers, EthTransferKind};
+
+    #[test]
+    fn test_internal_transfers_to_weth() {
+        let bp = get_test_bc_provider();
+        let weth = ADDRESS_BOOK.weth.must_on_chain(Chain::Mainnet);
+        let transfers = eth_transfers(&bp, weth, 17000000..17000005).unwrap();
+        // routers wrap ether for users with internal calls to WETH.deposit
+        assert!(transfers
+            .iter()
+            .any(|t| t.kind == EthTransferKind::Internal && t.to == weth));
+        // and unwrap ether with WETH.withdraw
+        assert!(transfers
+            .iter()
+            .any(|t| t.kind == EthTransferKind::Internal && t.from == weth));
+        assert!(transfers.iter().all(|t| t.from == weth || t.to == weth));
+    }
+}
@@ -0,0 +1,246 @@
+use std::fmt::Write;
+
+use libsofl_core::{
+    blockchain::transaction::Tx,
+    engine::types::{Address, Bytes, U256},
+};
+
+/// Hex encoding without the `0x` prefix, as used in Solidity `hex"..."` literals.
+fn hex(data: &Bytes) -> String {
+    let s = data.to_string();
+    s.strip_prefix("0x").unwrap_or(&s).to_string()
+}
+
+/// One call (or contract creation) to reproduce in the Foundry test.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayStep {
+    pub from: Address,
+    /// None for contract creation.
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Bytes,
+    /// The observed execution status. No assertion is emitted if None.
+    pub success: Option<bool>,
+    /// The observed return data. No assertion is emitted if None.
+    pub output: Option<Bytes>,
+}
+
+impl ReplayStep {
+    /// Create a replay step from an executed transaction.
+    pub fn from_tx<T: Tx>(tx: &T) -> Self {
+        Self {
+            from: tx.sender(),
+            to: tx.to(),
+            value: tx.value(),
+            input: tx.input(),
+            success: tx.success(),
+            output: tx.output(),
+        }
+    }
+}
+
+/// FoundryTestExporter turns a replayed transaction (sequence) into a
+/// Solidity Foundry test scaffold, so that the reproduction can be handed off to contract developers.
+/// The generated test forks the chain at the given block, applies the state overrides
+/// with cheatcodes (`vm.deal`, `vm.warp`), and replays each step with `vm.prank`,
+/// asserting on the observed outcome.
+#[derive(Debug, Clone)]
+pub struct FoundryTestExporter {
+    pub test_name: String,
+    /// The environment variable of the RPC url used to fork.
+    pub rpc_url_env: String,
+    pub fork_block: u64,
+    pub deals: Vec<(Address, U256)>,
+    pub warp: Option<u64>,
+    pub steps: Vec<ReplayStep>,
+}
+
+impl FoundryTestExporter {
+    pub fn new(test_name: impl ToString, fork_block: u64) -> Self {
+        Self {
+            test_name: test_name.to_string(),
+            rpc_url_env: "ETH_RPC_URL".to_string(),
+            fork_block,
+            deals: Vec::new(),
+            warp: None,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn set_rpc_url_env(mut self, env: impl ToString) -> Self {
+        self.rpc_url_env = env.to_string();
+        self
+    }
+
+    /// Override the ether balance of an account (`vm.deal`).
+    pub fn deal(mut self, account: Address, balance: U256) -> Self {
+        self.deals.push((account, balance));
+        self
+    }
+
+    /// Override the block timestamp (`vm.warp`).
+    pub fn warp(mut self, timestamp: u64) -> Self {
+        self.warp = Some(timestamp);
+        self
+    }
+
+    pub fn append_step(mut self, step: ReplayStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn append_tx<T: Tx>(self, tx: &T) -> Self {
+        self.append_step(ReplayStep::from_tx(tx))
+    }
+
+    /// Generate the Solidity source code of the Foundry test.
+    pub fn export(&self) -> String {
+        let mut s = String::new();
+        let name = self.test_name.as_str();
+        writeln!(s, "// SPDX-License-Identifier: UNLICENSED").unwrap();
+        writeln!(s, "pragma solidity ^0.8.0;").unwrap();
+        writeln!(s).unwrap();
+        writeln!(s, "import \"forge-std/Test.sol\";").unwrap();
+        writeln!(s).unwrap();
+        writeln!(s, "contract {}Test is Test {{", name).unwrap();
+        writeln!(s, "    function setUp() public {{").unwrap();
+        writeln!(
+            s,
+            "        vm.createSelectFork(vm.envString(\"{}\"), {});",
+            self.rpc_url_env, self.fork_block
+        )
+        .unwrap();
+        for (account, balance) in &self.deals {
+            writeln!(s, "        vm.deal({}, {});", account, balance).unwrap();
+        }
+        if let Some(timestamp) = self.warp {
+            writeln!(s, "        vm.warp({});", timestamp).unwrap();
+        }
+        writeln!(s, "    }}").unwrap();
+        writeln!(s).unwrap();
+        writeln!(s, "    function test{}() public {{", name).unwrap();
+        for (i, step) in self.steps.iter().enumerate() {
+            Self::write_step(&mut s, i, step);
+        }
+        writeln!(s, "    }}").unwrap();
+        writeln!(s, "}}").unwrap();
+        s
+    }
+
+    fn write_step(s: &mut String, i: usize, step: &ReplayStep) {
+        writeln!(s, "        // step {}", i).unwrap();
+        writeln!(s, "        vm.prank({});", step.from).unwrap();
+        match step.to {
+            Some(to) => {
+                writeln!(
+                    s,
+                    "        (bool success{}, bytes memory ret{}) = address({}).call{{value: {}}}(hex\"{}\");",
+                    i,
+                    i,
+                    to,
+                    step.value,
+                    hex(&step.input)
+                )
+                .unwrap();
+                match step.success {
+                    Some(true) => {
+                        writeln!(s, "        assertTrue(success{});", i)
+                            .unwrap();
+                        if let Some(output) = &step.output {
+                            writeln!(
+                                s,
+                                "        assertEq(ret{}, hex\"{}\");",
+                                i,
+                                hex(output)
+                            )
+                            .unwrap();
+                        }
+                    }
+                    Some(false) => {
+                        writeln!(s, "        assertFalse(success{});", i)
+                            .unwrap();
+                    }
+                    None => {
+                        writeln!(s, "        success{}; ret{};", i, i).unwrap();
+                    }
+                }
+            }
+            None => {
+                writeln!(
+                    s,
+                    "        bytes memory code{} = hex\"{}\";",
+                    i,
+                    hex(&step.input)
+                )
+                .unwrap();
+                writeln!(s, "        address created{};", i).unwrap();
+                writeln!(
+                    s,
+                    "        assembly {{ created{} := create({}, add(code{}, 0x20), mload(code{})) }}",
+                    i, step.value, i, i
+                )
+                .unwrap();
+                match step.success {
+                    Some(true) => writeln!(
+                        s,
+                        "        assertTrue(created{} != address(0));",
+                        i
+                    )
+                    .unwrap(),
+                    Some(false) => writeln!(
+                        s,
+                        "        assertEq(created{}, address(0));",
+                        i
+                    )
+                    .unwrap(),
+                    None => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        blockchain::transaction::MockTx,
+        conversion::ConvertTo,
+        engine::types::{Address, Bytes, U256},
+    };
+
+    use super::FoundryTestExporter;
+
+    #[test]
+    fn test_export_transfer_replay() {
+        let sender: Address =
+            "0x4354bB7C9dad5b0299199c0084E6ae386afD636C".cvt();
+        let receiver: Address =
+            "0x0123456789abcDEF0123456789abCDef01234567".cvt();
+        let mut tx = MockTx::new();
+        tx.expect_sender().return_const(sender);
+        tx.expect_to().return_const(Some(receiver));
+        tx.expect_value().return_const(U256::from(1000));
+        tx.expect_input().return_const(Bytes::new());
+        tx.expect_success().return_const(Some(true));
+        tx.expect_output().return_const(Some(Bytes::new()));
+
+        let code = FoundryTestExporter::new("Transfer", 17000000)
+            .deal(sender, U256::from(10000))
+            .warp(1681000000)
+            .append_tx(&tx)
+            .export();
+
+        assert!(code.contains("contract TransferTest is Test"));
+        assert!(code.contains(
+            "vm.createSelectFork(vm.envString(\"ETH_RPC_URL\"), 17000000);"
+        ));
+        assert!(code.contains(&format!("vm.deal({}, 10000);", sender)));
+        assert!(code.contains("vm.warp(1681000000);"));
+        assert!(code.contains(&format!("vm.prank({});", sender)));
+        assert!(code.contains(&format!(
+            "address({}).call{{value: 1000}}(hex\"\");",
+            receiver
+        )));
+        assert!(code.contains("assertTrue(success0);"));
+    }
+}
@@ -1,9 +1,16 @@
 #[macro_use]
 extern crate lazy_static;
+pub mod access_list;
 pub mod addressbook;
+pub mod asset_flow;
 pub use libsofl_core::solidity::caller;
+pub mod call_extract;
 pub mod cheatcodes;
+pub mod common_tokens;
 pub mod conversion;
+pub mod eth_transfer;
+pub mod foundry;
 pub mod math;
+pub mod opcode_counter;
 pub mod test;
 pub mod types;
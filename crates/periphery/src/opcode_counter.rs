@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use libsofl_core::engine::{
+    inspector::EvmInspector,
+    state::BcState,
+    types::{EvmContext, Inspector, Interpreter},
+};
+
+/// OpcodeCounter counts the executed opcodes across all call frames,
+/// e.g., to estimate the proving cost of a transaction or to spot long loops.
+#[derive(Debug, Clone)]
+pub struct OpcodeCounter {
+    pub histogram: [u64; 256],
+}
+
+impl Default for OpcodeCounter {
+    fn default() -> Self {
+        Self {
+            histogram: [0; 256],
+        }
+    }
+}
+
+impl OpcodeCounter {
+    /// The number of times the opcode is executed.
+    pub fn count(&self, opcode: u8) -> u64 {
+        self.histogram[opcode as usize]
+    }
+
+    /// The executed opcodes and their counts.
+    pub fn counts(&self) -> HashMap<u8, u64> {
+        self.histogram
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(opcode, count)| (opcode as u8, *count))
+            .collect()
+    }
+
+    /// The total number of executed steps.
+    pub fn total(&self) -> u64 {
+        self.histogram.iter().sum()
+    }
+}
+
+impl<S: BcState> Inspector<S> for OpcodeCounter {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<S>) {
+        self.histogram[interp.current_opcode() as usize] += 1;
+    }
+}
+
+impl<S: BcState> EvmInspector<S> for OpcodeCounter {}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            memory::MemoryBcState,
+            state::BcState,
+            types::{opcode, Address, Bytecode, Bytes, SpecId},
+        },
+    };
+
+    use crate::caller::HighLevelCaller;
+
+    use super::OpcodeCounter;
+
+    #[test]
+    fn test_count_loop() {
+        let looper: Address = 0x1000.cvt();
+        let caller: Address = 0x2000.cvt();
+        // counter = 3; do { counter -= 1 } while (counter != 0)
+        let loop_code = vec![
+            0x60, 0x03, // PUSH1 3
+            0x5b, // JUMPDEST
+            0x60, 0x01, // PUSH1 1
+            0x90, // SWAP1
+            0x03, // SUB
+            0x80, // DUP1
+            0x60, 0x02, // PUSH1 2
+            0x57, // JUMPI
+            0x00, // STOP
+        ];
+        // call the loop contract
+        let mut call_code = vec![
+            0x60, 0x00, // PUSH1 0
+            0x80, 0x80, 0x80, 0x80, // DUP1 * 4
+            0x73, // PUSH20
+        ];
+        call_code.extend_from_slice(looper.as_slice());
+        call_code.extend_from_slice(&[
+            0x5a, // GAS
+            0xf1, // CALL
+            0x50, // POP
+            0x00, // STOP
+        ]);
+        let mut state = MemoryBcState::fresh();
+        state
+            .replace_account_code(looper, Bytecode::new_raw(loop_code.into()))
+            .unwrap();
+        state
+            .replace_account_code(caller, Bytecode::new_raw(call_code.into()))
+            .unwrap();
+
+        let mut counter = OpcodeCounter::default();
+        HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .call(&mut state, caller, Bytes::new(), None, &mut counter)
+            .unwrap();
+
+        assert_eq!(counter.count(opcode::JUMPI), 3);
+        assert_eq!(counter.count(opcode::JUMPDEST), 3);
+        // 1 + 2 * 3 in the loop contract and 1 in the caller
+        assert_eq!(counter.count(opcode::PUSH1), 8);
+        assert_eq!(counter.count(opcode::DUP1), 7);
+        assert_eq!(counter.count(opcode::CALL), 1);
+        assert_eq!(counter.count(opcode::STOP), 2);
+        assert_eq!(counter.total(), 23 + 10);
+        assert_eq!(counter.counts().len(), 11);
+    }
+}
@@ -15,11 +15,11 @@ fn run_block(provider: Arc<RethProvider>, bn: u64) {
     let mut state = provider.bc_state_at(bn.into()).unwrap();
     let txs = provider.txs_in_block(bn.into()).unwrap();
     let mut spec_builder =
-        TransitionSpecBuilder::default().at_block(&provider, bn.into());
+        TransitionSpecBuilder::default().at_block(&provider, bn);
     for tx in txs {
         spec_builder = spec_builder.append_tx(tx);
     }
-    let spec = spec_builder.build();
+    let spec = spec_builder.build().unwrap();
     state.transit(spec, no_inspector()).unwrap();
 }
 
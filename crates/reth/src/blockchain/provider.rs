@@ -1,13 +1,14 @@
 use std::{
     collections::HashMap,
+    ops::Range,
     path::Path,
     sync::{Arc, Mutex},
 };
 
 use libsofl_core::{
     blockchain::{
-        provider::{BcProvider, BcStateProvider},
-        transaction::Tx,
+        provider::{log_matches, BcProvider, BcStateProvider},
+        transaction::{Log, Tx},
         tx_position::TxPosition,
     },
     engine::{
@@ -16,8 +17,8 @@ use libsofl_core::{
         state::BcState,
         transition::TransitionSpecBuilder,
         types::{
-            BlockEnv, BlockHash, BlockHashOrNumber, BlockNumber, CfgEnv, TxEnv,
-            TxHashOrPosition,
+            AccountInfo, Address, BlockEnv, BlockHash, BlockHashOrNumber,
+            BlockNumber, CfgEnv, DatabaseRef, Hash, TxEnv, TxHashOrPosition,
         },
     },
     error::SoflError,
@@ -28,7 +29,7 @@ use reth_blockchain_tree::{
 };
 use reth_db::{open_db_read_only, DatabaseEnv};
 use reth_node_ethereum::EthEvmConfig;
-use reth_primitives::ChainSpecBuilder;
+use reth_primitives::{BloomInput, ChainSpecBuilder};
 pub use reth_provider::{
     providers::BlockchainProvider, BlockHashReader, BlockNumReader,
     BlockchainTreePendingStateProvider, ChainSpecProvider, EvmEnvProvider,
@@ -181,12 +182,29 @@ impl BcStateProvider<RethBcStateRef> for RethProvider {
             for tx in txs.into_iter() {
                 spec_builder = spec_builder.append_tx(tx);
             }
-            let spec = spec_builder.build();
+            let spec = spec_builder.build()?;
             state.transit(spec, no_inspector())?;
         }
 
         Ok(state)
     }
+
+    /// The database is local, so accounts are simply read one by one.
+    fn basic_batch(
+        &self,
+        pos: TxPosition,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, SoflError> {
+        let state = self.bc_state_at(pos)?;
+        addresses
+            .iter()
+            .map(|address| {
+                state.basic_ref(*address).map_err(|e| {
+                    SoflError::Provider(format!("failed to get account: {}", e))
+                })
+            })
+            .collect()
+    }
 }
 
 impl BcProvider<RethTx> for RethProvider {
@@ -225,14 +243,144 @@ impl BcProvider<RethTx> for RethProvider {
                 ))
             })?
             .ok_or(SoflError::NotFound(format!("block {}", block)))?;
-        let txs: Result<Vec<RethTx>, _> =
-            txs.into_iter().map(|t| self.tx(t.hash().into())).collect();
+        // load the receipts once instead of for each transaction
+        let receipts = self
+            .bp
+            .receipts_by_block(block.cvt())
+            .map_err(|e| {
+                SoflError::Provider(format!("failed to get receipts: {}", e))
+            })?
+            .unwrap_or_default();
+        let txs: Result<Vec<RethTx>, _> = txs
+            .into_iter()
+            .map(|t| {
+                RethTx::from_hash_with_block_receipts(
+                    &self.bp,
+                    t.hash(),
+                    &receipts,
+                )
+            })
+            .collect();
         let txs = txs.map_err(|e| {
             SoflError::Provider(format!("failed to get transaction: {}", e))
         })?;
         Ok(txs)
     }
 
+    fn txs_to_address(
+        &self,
+        range: Range<BlockNumber>,
+        contract: Address,
+        internal: bool,
+    ) -> Result<Vec<RethTx>, SoflError> {
+        let mut txs = Vec::new();
+        for bn in range {
+            let block: BlockHashOrNumber = bn.into();
+            let raw_txs = self
+                .bp
+                .transactions_by_block(block.cvt())
+                .map_err(|e| {
+                    SoflError::Provider(format!(
+                        "failed to get transactions by block: {}",
+                        e
+                    ))
+                })?
+                .ok_or(SoflError::NotFound(format!("block {}", block)))?;
+
+            // internal calls are detected by the logs of the contract without
+            // tracing, so those emitting no log are missed.
+            // the logs bloom rules out blocks where the contract emits no log,
+            // so that receipts are only loaded for the remaining blocks
+            let mut emitted = Vec::new();
+            if internal {
+                let header = self
+                    .bp
+                    .header_by_number(bn)
+                    .map_err(|e| {
+                        SoflError::Provider(format!(
+                            "failed to get header: {}",
+                            e
+                        ))
+                    })?
+                    .ok_or(SoflError::NotFound(format!("block {}", block)))?;
+                let input = BloomInput::Raw(contract.as_slice());
+                if header.logs_bloom.contains_input(input) {
+                    emitted = self
+                        .bp
+                        .receipts_by_block(block.cvt())
+                        .map_err(|e| {
+                            SoflError::Provider(format!(
+                                "failed to get receipts: {}",
+                                e
+                            ))
+                        })?
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|r| r.logs.iter().any(|l| l.address == contract))
+                        .collect();
+                }
+            }
+
+            // only matched transactions are loaded
+            for (i, tx) in raw_txs.into_iter().enumerate() {
+                if tx.to() == Some(contract)
+                    || emitted.get(i).cloned().unwrap_or(false)
+                {
+                    txs.push(self.tx(tx.hash().into())?);
+                }
+            }
+        }
+        Ok(txs)
+    }
+
+    fn get_logs(
+        &self,
+        address: Option<Address>,
+        topic0: Option<Hash>,
+        range: Range<BlockNumber>,
+    ) -> Result<Vec<Log>, SoflError> {
+        let mut logs = Vec::new();
+        for bn in range {
+            // the logs bloom rules out blocks without any matching log,
+            // so that receipts are only loaded for the remaining blocks
+            let header = self
+                .bp
+                .header_by_number(bn)
+                .map_err(|e| {
+                    SoflError::Provider(format!("failed to get header: {}", e))
+                })?
+                .ok_or(SoflError::NotFound(format!("block {}", bn)))?;
+            let bloom = &header.logs_bloom;
+            let may_contain =
+                |input: &[u8]| bloom.contains_input(BloomInput::Raw(input));
+            if !address.map_or(true, |a| may_contain(a.as_slice()))
+                || !topic0.map_or(true, |t| may_contain(t.as_slice()))
+            {
+                continue;
+            }
+
+            let block: BlockHashOrNumber = bn.into();
+            let receipts = self
+                .bp
+                .receipts_by_block(block.cvt())
+                .map_err(|e| {
+                    SoflError::Provider(format!(
+                        "failed to get receipts: {}",
+                        e
+                    ))
+                })?
+                .unwrap_or_default();
+            logs.extend(
+                receipts
+                    .into_iter()
+                    .flat_map(|r| r.logs)
+                    .map(ConvertTo::<Log>::cvt)
+                    .filter(|log| log_matches(log, address, topic0)),
+            );
+        }
+        Ok(logs)
+    }
+
     fn fill_cfg_env(
         &self,
         env: &mut CfgEnv,
@@ -360,12 +508,16 @@ mod tests_with_db {
     use libsofl_core::{
         blockchain::{
             provider::{BcProvider, BcStateProvider},
+            transaction::{Tx, TxKind},
             tx_position::TxPosition,
+            verify::verify_block,
         },
         conversion::ConvertTo,
         engine::{
-            inspector::no_inspector, state::BcState,
-            transition::TransitionSpec, types::TxHash,
+            inspector::no_inspector,
+            state::BcState,
+            transition::TransitionSpec,
+            types::{Address, Hash, SpecId, TransactTo, TxEnv, TxHash},
         },
     };
     use libsofl_utils::config::Config;
@@ -426,4 +578,133 @@ mod tests_with_db {
         }
         assert_eq!(receipt.cumulative_gas_used, r.gas_used());
     }
+
+    #[test]
+    fn test_reproduce_blob_tx() {
+        let cfg = RethConfig::must_load();
+        let bp = cfg.bc_provider().unwrap();
+
+        // the first blob-carrying tx after the Dencun upgrade
+        let (bn, txs, index) = (19426587u64..19426687)
+            .find_map(|bn| {
+                let txs = bp.txs_in_block(bn.into()).unwrap();
+                let index = txs.iter().position(|tx| {
+                    let mut env = TxEnv::default();
+                    tx.fill_tx_env(&mut env).unwrap();
+                    !env.blob_hashes.is_empty()
+                })?;
+                Some((bn, txs, index))
+            })
+            .unwrap();
+        let tx_hash = txs[index].hash();
+        let mut state =
+            bp.bc_state_at(TxPosition::new(bn, index as u64)).unwrap();
+        let spec = TransitionSpec::from_tx_hash(&bp, tx_hash).unwrap();
+        assert_eq!(spec.get_evm_version(), SpecId::CANCUN);
+        assert!(spec.block.blob_excess_gas_and_price.is_some());
+
+        // simulate
+        let r = state.transit(spec, no_inspector()).unwrap().pop().unwrap();
+        let receipt = bp.bp.receipt_by_hash(tx_hash).unwrap().unwrap();
+        assert_eq!(receipt.success, r.is_success());
+        // the receipt records the cumulative gas used in the block
+        let gas_before = match index {
+            0 => 0,
+            _ => {
+                let prev = txs[index - 1].hash();
+                let prev = bp.bp.receipt_by_hash(prev).unwrap().unwrap();
+                prev.cumulative_gas_used
+            }
+        };
+        assert_eq!(receipt.cumulative_gas_used - gas_before, r.gas_used());
+    }
+
+    #[test]
+    fn test_verify_block() {
+        let cfg = RethConfig::must_load();
+        let bp = cfg.bc_provider().unwrap();
+        let verification = verify_block(&bp, 17000000).unwrap();
+        assert!(verification.txs > 0);
+        assert_eq!(verification.mismatches, vec![]);
+        assert!(verification.is_ok());
+    }
+
+    #[test]
+    fn test_txs_to_address() {
+        let cfg = RethConfig::must_load();
+        let bp = cfg.bc_provider().unwrap();
+        // the first transaction on mainnet
+        let to: Address = "0x5df9b87991262f6ba471f09758cde1c0fc1de734".cvt();
+        let tx_hash: TxHash =
+            "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060".cvt();
+        let txs = bp.txs_to_address(46140..46150, to, false).unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].hash(), tx_hash);
+
+        // the receiver is an EOA, which never emits logs
+        let txs = bp.txs_to_address(46140..46150, to, true).unwrap();
+        assert_eq!(txs.len(), 1);
+    }
+
+    #[test]
+    fn test_tx_kind() {
+        let cfg = RethConfig::must_load();
+        let bp = cfg.bc_provider().unwrap();
+        // the first transaction on mainnet
+        let tx_hash: TxHash =
+            "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060".cvt();
+        let to: Address = "0x5df9b87991262f6ba471f09758cde1c0fc1de734".cvt();
+        let tx = bp.tx(tx_hash.into()).unwrap();
+        assert_eq!(tx.kind(), TxKind::Call(to));
+        assert!(!tx.is_create());
+
+        // the first contract creation on mainnet
+        let tx = (46147u64..60000)
+            .find_map(|bn| {
+                let txs = bp.txs_in_block(bn.into()).unwrap();
+                txs.into_iter().find(|tx| tx.is_create())
+            })
+            .unwrap();
+        assert_eq!(tx.kind(), TxKind::Create);
+        assert_eq!(tx.to(), None);
+        let mut env = TxEnv::default();
+        tx.fill_tx_env(&mut env).unwrap();
+        assert!(matches!(env.transact_to, TransactTo::Create(_)));
+    }
+
+    #[test]
+    fn test_get_logs() {
+        let cfg = RethConfig::must_load();
+        let bp = cfg.bc_provider().unwrap();
+        let usdt: Address = "0xdAC17F958D2ee523a2206206994597C13D831ec7".cvt();
+        // Transfer(address,address,uint256)
+        let transfer: Hash =
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".cvt();
+        let range = 17000000..17000003;
+
+        let logs = bp
+            .get_logs(Some(usdt), Some(transfer), range.clone())
+            .unwrap();
+        assert!(!logs.is_empty());
+        assert!(logs
+            .iter()
+            .all(|log| log.address == usdt && log.topics[0] == transfer));
+
+        // the same logs are found by scanning every transaction
+        let mut expected = Vec::new();
+        for bn in range.clone() {
+            for tx in bp.txs_in_block(bn.into()).unwrap() {
+                expected.extend(tx.logs().unwrap().into_iter().filter(|log| {
+                    log.address == usdt && log.topics.first() == Some(&transfer)
+                }));
+            }
+        }
+        assert_eq!(logs, expected);
+
+        // None matches any
+        let all = bp.get_logs(None, Some(transfer), range.clone()).unwrap();
+        assert!(all.len() > logs.len());
+        let all = bp.get_logs(Some(usdt), None, range).unwrap();
+        assert!(all.len() >= logs.len());
+    }
 }
@@ -1,5 +1,9 @@
-use libsofl_core::engine::types::{
-    AccountInfo, Address, Bytecode, DatabaseRef, B256, U256,
+use libsofl_core::{
+    engine::{
+        memory::{DumpStorage, MemoryBcState, StorageDump},
+        types::{AccountInfo, Address, Bytecode, DatabaseRef, B256, U256},
+    },
+    error::SoflError,
 };
 use reth_provider::{ProviderError, StateProviderBox};
 use reth_revm::{
@@ -60,3 +64,11 @@ impl DatabaseRef for RethBcStateRef {
         Ok(block_hash.cvt())
     }
 }
+
+/// Only slots cached locally are dumped,
+/// since the storage of the reth database is not enumerated.
+impl DumpStorage for MemoryBcState<RethBcStateRef> {
+    fn dump_storage(&self, address: Address) -> Result<StorageDump, SoflError> {
+        Ok(self.dump_cached_storage(address))
+    }
+}
@@ -1,13 +1,15 @@
 use libsofl_core::{
     blockchain::{
-        transaction::{Log, Tx},
+        transaction::{Log, Tx, TxKind},
         tx_position::TxPosition,
     },
     engine::types::{Address, Bytes, TxEnv, TxHash, U256},
     error::SoflError,
 };
 use reth_primitives::revm::env::fill_tx_env;
-use reth_primitives::{TransactionMeta, TransactionSigned};
+use reth_primitives::{
+    Receipt, TransactionKind, TransactionMeta, TransactionSigned,
+};
 use reth_provider::{ReceiptProvider, TransactionsProvider};
 
 use crate::conversion::ConvertTo;
@@ -23,6 +25,7 @@ pub struct RethTx {
     // only availabe after tx execution
     pub(crate) meta: Option<TransactionMeta>,
     pub(crate) success: Option<bool>,
+    pub(crate) gas_used: Option<u64>,
     pub(crate) output: Option<Bytes>, // TODO: how to get evm output?
     pub(crate) logs: Option<Vec<Log>>,
 }
@@ -40,6 +43,7 @@ impl From<TransactionSigned> for RethTx {
             hash,
             meta: None,
             success: None,
+            gas_used: None,
             output: None,
             logs: None,
         }
@@ -51,6 +55,69 @@ impl RethTx {
         bp: &RethBlockchainProvider,
         hash: TxHash,
     ) -> Result<Self, SoflError> {
+        let (mut tx, meta) = Self::tx_with_meta(bp, hash)?;
+        let index = meta.index;
+        tx.meta = Some(meta);
+
+        // fill receipt if available
+        let id = bp
+            .transaction_id(hash)
+            .map_err(|e| {
+                SoflError::Provider(format!(
+                    "failed to get transaction id: {}",
+                    e
+                ))
+            })?
+            .ok_or(SoflError::NotFound(format!("transaction {}", hash)))?;
+        let receipt = |id| {
+            bp.receipt(id).map_err(|e| {
+                SoflError::Provider(format!("failed to get receipt: {}", e))
+            })
+        };
+        if let Some(r) = receipt(id)? {
+            // receipts record the cumulative gas used in the block,
+            // so only the receipt of the previous transaction is needed
+            let gas_before = match index {
+                0 => 0,
+                _ => {
+                    receipt(id - 1)?
+                        .ok_or(SoflError::NotFound(format!(
+                            "receipt of transaction number {}",
+                            id - 1
+                        )))?
+                        .cumulative_gas_used
+                }
+            };
+            tx.fill_receipt(r, gas_before);
+        }
+        Ok(tx)
+    }
+
+    /// Same as `from_hash`, but the receipt is taken from the receipts of
+    /// the block containing the transaction, which are loaded by the caller,
+    /// e.g., once for all transactions of the block.
+    pub fn from_hash_with_block_receipts(
+        bp: &RethBlockchainProvider,
+        hash: TxHash,
+        receipts: &[Receipt],
+    ) -> Result<Self, SoflError> {
+        let (mut tx, meta) = Self::tx_with_meta(bp, hash)?;
+        let index = meta.index as usize;
+        tx.meta = Some(meta);
+        if let Some(receipt) = receipts.get(index) {
+            let gas_before = match index {
+                0 => 0,
+                _ => receipts[index - 1].cumulative_gas_used,
+            };
+            tx.fill_receipt(receipt.clone(), gas_before);
+        }
+        Ok(tx)
+    }
+
+    fn tx_with_meta(
+        bp: &RethBlockchainProvider,
+        hash: TxHash,
+    ) -> Result<(Self, TransactionMeta), SoflError> {
         let (tx, meta) = bp
             .transaction_by_hash_with_meta(hash)
             .map_err(|e| {
@@ -60,20 +127,16 @@ impl RethTx {
                 ))
             })?
             .ok_or(SoflError::NotFound(format!("transaction {}", hash)))?;
-        let mut tx: RethTx = tx.into();
-        tx.meta = Some(meta);
+        Ok((tx.into(), meta))
+    }
 
-        // fill receipt if available
-        let receipt = bp.receipt_by_hash(hash).map_err(|e| {
-            SoflError::Provider(format!("failed to get receipt by hash: {}", e))
-        })?;
-        if let Some(receipt) = receipt {
-            let success = receipt.success;
-            let logs = receipt.logs.into_iter().map(|log| log.cvt()).collect();
-            tx.success = Some(success);
-            tx.logs = Some(logs);
-        }
-        Ok(tx)
+    /// Fill the execution results recorded in the receipt,
+    /// given the cumulative gas used before the transaction in the block.
+    fn fill_receipt(&mut self, receipt: Receipt, gas_before: u64) {
+        self.success = Some(receipt.success);
+        self.gas_used = Some(receipt.cumulative_gas_used - gas_before);
+        self.logs =
+            Some(receipt.logs.into_iter().map(|log| log.cvt()).collect());
     }
 }
 
@@ -181,6 +244,12 @@ impl Tx for RethTx {
 
     #[doc = " Returns the gas used by the transaction."]
     #[doc = " None if the transaction is not executed."]
+    fn gas_used(&self) -> Option<u64> {
+        self.gas_used
+    }
+
+    #[doc = " Returns the logs emitted by the transaction."]
+    #[doc = " None if the transaction is not executed."]
     fn logs(&self) -> Option<Vec<Log>> {
         self.logs.clone()
     }
@@ -189,4 +258,12 @@ impl Tx for RethTx {
     fn to(&self) -> Option<Address> {
         self.tx.to()
     }
+
+    #[doc = " Returns whether the transaction calls an account or creates a contract."]
+    fn kind(&self) -> TxKind {
+        match self.tx.kind() {
+            TransactionKind::Call(to) => TxKind::Call(*to),
+            TransactionKind::Create => TxKind::Create,
+        }
+    }
 }
@@ -0,0 +1,87 @@
+use libsofl_core::engine::types::{keccak256, Address, Bytes, Hash};
+
+/// The address of the contract created by `sender` with CREATE,
+/// i.e., `keccak256(rlp([sender, nonce]))[12..]`.
+/// The nonce of a contract sender starts from 1 (EIP-161).
+pub fn compute_create_address(sender: Address, nonce: u64) -> Address {
+    sender.create(nonce)
+}
+
+/// The address of the contract created by `deployer` with CREATE2 (EIP-1014),
+/// i.e., `keccak256(0xff ++ deployer ++ salt ++ init_code_hash)[12..]`.
+pub fn compute_create2_address(
+    deployer: Address,
+    salt: Hash,
+    init_code_hash: Hash,
+) -> Address {
+    deployer.create2(salt, init_code_hash)
+}
+
+/// Same as `compute_create2_address`, but hashes the init code.
+pub fn compute_create2_address_from_code(
+    deployer: Address,
+    salt: Hash,
+    init_code: &Bytes,
+) -> Address {
+    compute_create2_address(deployer, salt, keccak256(init_code))
+}
+
+#[cfg(test)]
+mod tests {
+    use libsofl_core::engine::types::{keccak256, Address, Bytes, Hash};
+
+    #[test]
+    fn test_create_address() {
+        let sender: Address = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0"
+            .parse()
+            .unwrap();
+        let expected: Address = "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"
+            .parse()
+            .unwrap();
+        assert_eq!(super::compute_create_address(sender, 0), expected);
+        let expected: Address = "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8"
+            .parse()
+            .unwrap();
+        assert_eq!(super::compute_create_address(sender, 1), expected);
+    }
+
+    #[test]
+    fn test_create2_address() {
+        // example 0 of EIP-1014
+        let init_code = Bytes::from(vec![0x00]);
+        let expected: Address = "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            super::compute_create2_address_from_code(
+                Address::ZERO,
+                Hash::ZERO,
+                &init_code
+            ),
+            expected
+        );
+
+        // USDC-WETH pair created by the Uniswap V2 factory on mainnet
+        let factory: Address = "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f"
+            .parse()
+            .unwrap();
+        let usdc: Address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+            .parse()
+            .unwrap();
+        let weth: Address = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+            .parse()
+            .unwrap();
+        let salt = keccak256([usdc.as_slice(), weth.as_slice()].concat());
+        let init_code_hash: Hash =
+            "0x96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f"
+                .parse()
+                .unwrap();
+        let expected: Address = "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            super::compute_create2_address(factory, salt, init_code_hash),
+            expected
+        );
+    }
+}
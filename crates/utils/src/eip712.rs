@@ -0,0 +1,205 @@
+use alloy_dyn_abi::DynSolValue;
+use libsofl_core::engine::types::{keccak256, Address, Hash, U256};
+
+pub const EIP712_DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// The struct type of ERC20 Permit (EIP-2612).
+pub const PERMIT_TYPE: &str = "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)";
+
+/// The EIP-712 domain with all of name, version, chainId, and verifyingContract,
+/// which is the one used by most ERC20 Permit implementations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip712Domain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: Address,
+}
+
+impl Eip712Domain {
+    pub fn new(
+        name: impl ToString,
+        version: impl ToString,
+        chain_id: u64,
+        verifying_contract: Address,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            chain_id,
+            verifying_contract,
+        }
+    }
+
+    /// The domain separator, i.e., `DOMAIN_SEPARATOR()` of the contract.
+    pub fn separator(&self) -> Hash {
+        hash_struct(
+            type_hash(EIP712_DOMAIN_TYPE),
+            &[
+                DynSolValue::String(self.name.clone()),
+                DynSolValue::String(self.version.clone()),
+                DynSolValue::Uint(U256::from(self.chain_id), 256),
+                DynSolValue::Address(self.verifying_contract),
+            ],
+        )
+    }
+}
+
+/// The type hash of the encoded struct type,
+/// e.g., `Mail(Person from,Person to,string contents)Person(string name,address wallet)`.
+pub fn type_hash(encoded_type: &str) -> Hash {
+    keccak256(encoded_type.as_bytes())
+}
+
+/// `hashStruct` of EIP-712, where `struct_data` are the member values in order.
+/// Strings and bytes are hashed.
+/// Nested structs and arrays must be hashed beforehand and passed as `bytes32`.
+pub fn hash_struct(type_hash: Hash, struct_data: &[DynSolValue]) -> Hash {
+    let mut encoded = Vec::with_capacity(32 * (struct_data.len() + 1));
+    encoded.extend_from_slice(type_hash.as_slice());
+    for value in struct_data {
+        let word = match value {
+            DynSolValue::String(s) => keccak256(s.as_bytes()),
+            DynSolValue::Bytes(b) => keccak256(b),
+            v => v
+                .as_word()
+                .expect("nested structs and arrays must be hashed first"),
+        };
+        encoded.extend_from_slice(word.as_slice());
+    }
+    keccak256(encoded)
+}
+
+/// The digest to be signed,
+/// i.e., `keccak256("\x19\x01" ++ domainSeparator ++ hashStruct(message))`.
+pub fn eip712_digest(
+    domain: &Eip712Domain,
+    type_hash: Hash,
+    struct_data: &[DynSolValue],
+) -> Hash {
+    let mut encoded = Vec::with_capacity(66);
+    encoded.extend_from_slice(b"\x19\x01");
+    encoded.extend_from_slice(domain.separator().as_slice());
+    encoded.extend_from_slice(hash_struct(type_hash, struct_data).as_slice());
+    keccak256(encoded)
+}
+
+/// The digest of an ERC20 Permit (EIP-2612) signed by `owner`.
+pub fn permit_digest(
+    domain: &Eip712Domain,
+    owner: Address,
+    spender: Address,
+    value: U256,
+    nonce: U256,
+    deadline: U256,
+) -> Hash {
+    eip712_digest(
+        domain,
+        type_hash(PERMIT_TYPE),
+        &[
+            DynSolValue::Address(owner),
+            DynSolValue::Address(spender),
+            DynSolValue::Uint(value, 256),
+            DynSolValue::Uint(nonce, 256),
+            DynSolValue::Uint(deadline, 256),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_dyn_abi::DynSolValue;
+    use libsofl_core::engine::types::{Address, Hash, U256};
+
+    use super::{
+        eip712_digest, hash_struct, permit_digest, type_hash, Eip712Domain,
+        PERMIT_TYPE,
+    };
+
+    #[test]
+    fn test_mail_example() {
+        // the example in EIP-712
+        let domain = Eip712Domain::new(
+            "Ether Mail",
+            "1",
+            1,
+            "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+                .parse()
+                .unwrap(),
+        );
+        let expected: Hash =
+            "0xf2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+                .parse()
+                .unwrap();
+        assert_eq!(domain.separator(), expected);
+
+        let person_type = type_hash("Person(string name,address wallet)");
+        let person = |name: &str, wallet: &str| {
+            let wallet: Address = wallet.parse().unwrap();
+            let hash = hash_struct(
+                person_type,
+                &[
+                    DynSolValue::String(name.to_string()),
+                    DynSolValue::Address(wallet),
+                ],
+            );
+            DynSolValue::FixedBytes(hash, 32)
+        };
+        let mail_type = type_hash("Mail(Person from,Person to,string contents)Person(string name,address wallet)");
+        let digest = eip712_digest(
+            &domain,
+            mail_type,
+            &[
+                person("Cow", "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"),
+                person("Bob", "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"),
+                DynSolValue::String("Hello, Bob!".to_string()),
+            ],
+        );
+        let expected: Hash =
+            "0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+                .parse()
+                .unwrap();
+        assert_eq!(digest, expected);
+    }
+
+    #[test]
+    fn test_usdc_permit() {
+        let domain = Eip712Domain::new(
+            "USD Coin",
+            "2",
+            1,
+            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+                .parse()
+                .unwrap(),
+        );
+        // DOMAIN_SEPARATOR() and PERMIT_TYPEHASH() of USDC on mainnet
+        let expected: Hash =
+            "0x06c37168a7db5138defc7866392bb87a741f9b3d104deb5094588ce041cae335"
+                .parse()
+                .unwrap();
+        assert_eq!(domain.separator(), expected);
+        let expected: Hash =
+            "0x6e71edae12b1b97f4d1f60370fef10105fa2faae0126114a169c64845d6126c9"
+                .parse()
+                .unwrap();
+        assert_eq!(type_hash(PERMIT_TYPE), expected);
+
+        let digest = permit_digest(
+            &domain,
+            "0x0000000000000000000000000000000000001234"
+                .parse()
+                .unwrap(),
+            "0x0000000000000000000000000000000000005678"
+                .parse()
+                .unwrap(),
+            U256::from(1000000),
+            U256::ZERO,
+            U256::MAX,
+        );
+        let expected: Hash =
+            "0x0b015e7b68ddc26586a0faa304febc155676b9fbcc44c26fbbf0409492646cb3"
+                .parse()
+                .unwrap();
+        assert_eq!(digest, expected);
+    }
+}
@@ -1,4 +1,8 @@
+pub mod address;
 pub mod config;
+pub mod eip712;
 pub mod log;
 pub mod rate_limit;
+pub mod signature;
+pub mod slot;
 pub mod sync;
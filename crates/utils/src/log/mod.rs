@@ -2,7 +2,7 @@ pub mod config;
 
 pub use tracing::{
     debug, debug_span, error, error_span, event, info, info_span, span, trace,
-    trace_span, warn, warn_span, Level, Subscriber,
+    trace_span, warn, warn_span, Instrument, Level, Subscriber,
 };
 use tracing_subscriber::{
     layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer,
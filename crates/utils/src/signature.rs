@@ -0,0 +1,148 @@
+use k256::ecdsa::{RecoveryId, SigningKey, VerifyingKey};
+use libsofl_core::{
+    engine::types::{keccak256, Address, Hash, U256},
+    error::SoflError,
+};
+
+/// A secp256k1 signature in the form accepted by `ecrecover`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub r: U256,
+    pub s: U256,
+    /// The recovery id plus 27, i.e., 27 or 28.
+    pub v: u8,
+}
+
+impl Signature {
+    /// The 65-byte `r ++ s ++ v` encoding, e.g., as accepted by OpenZeppelin's ECDSA.
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[..32].copy_from_slice(&self.r.to_be_bytes::<32>());
+        bytes[32..64].copy_from_slice(&self.s.to_be_bytes::<32>());
+        bytes[64] = self.v;
+        bytes
+    }
+}
+
+fn signing_key(key: Hash) -> Result<SigningKey, SoflError> {
+    SigningKey::from_slice(key.as_slice())
+        .map_err(|e| SoflError::Custom(format!("invalid private key: {}", e)))
+}
+
+fn address_of_key(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    // skip the 0x04 prefix of the uncompressed point
+    Address::from_slice(&keccak256(&point.as_bytes()[1..])[12..])
+}
+
+/// The address of the private key.
+pub fn address_of(key: Hash) -> Result<Address, SoflError> {
+    Ok(address_of_key(signing_key(key)?.verifying_key()))
+}
+
+/// Sign the 32-byte hash (e.g., an EIP-712 digest) with the private key.
+/// No prefix is added to the hash.
+/// The signature has a low `s` as required by EIP-2.
+pub fn sign_message(key: Hash, hash: Hash) -> Result<Signature, SoflError> {
+    let (signature, recovery_id) = signing_key(key)?
+        .sign_prehash_recoverable(hash.as_slice())
+        .map_err(|e| SoflError::Custom(format!("failed to sign: {}", e)))?;
+    let (r, s) = signature.split_bytes();
+    Ok(Signature {
+        r: U256::from_be_slice(&r),
+        s: U256::from_be_slice(&s),
+        v: recovery_id.to_byte() + 27,
+    })
+}
+
+/// Recover the signer of the hash, in the same way as `ecrecover`.
+pub fn recover(hash: Hash, sig: &Signature) -> Result<Address, SoflError> {
+    let recovery_id = sig
+        .v
+        .checked_sub(27)
+        .and_then(RecoveryId::from_byte)
+        .ok_or_else(|| SoflError::Custom(format!("invalid v: {}", sig.v)))?;
+    let signature = k256::ecdsa::Signature::from_scalars(
+        sig.r.to_be_bytes::<32>(),
+        sig.s.to_be_bytes::<32>(),
+    )
+    .map_err(|e| SoflError::Custom(format!("invalid signature: {}", e)))?;
+    let key = VerifyingKey::recover_from_prehash(
+        hash.as_slice(),
+        &signature,
+        recovery_id,
+    )
+    .map_err(|e| SoflError::Custom(format!("failed to recover: {}", e)))?;
+    Ok(address_of_key(&key))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_dyn_abi::DynSolValue;
+    use libsofl_core::{
+        engine::{
+            inspector::no_inspector,
+            memory::MemoryBcState,
+            types::{keccak256, Address, Hash, SpecId, U256},
+        },
+        solidity::{
+            caller::HighLevelCaller,
+            scripting::{deploy_contracts, SolScriptConfig},
+        },
+    };
+
+    use super::{address_of, recover, sign_message};
+
+    #[test]
+    fn test_sign_and_recover() {
+        let key = Hash::from(U256::from(1).to_be_bytes::<32>());
+        let signer: Address = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+            .parse()
+            .unwrap();
+        assert_eq!(address_of(key).unwrap(), signer);
+
+        let hash = keccak256("hello");
+        let sig = sign_message(key, hash).unwrap();
+        assert_eq!(recover(hash, &sig).unwrap(), signer);
+        assert_ne!(recover(keccak256("world"), &sig).unwrap(), signer);
+
+        // the recovery id must be accepted by ecrecover
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Recover {
+                function recover(bytes32 h, uint8 v, bytes32 r, bytes32 s) public pure returns (address) {
+                    return ecrecover(h, v, r, s);
+                }
+            }
+        "#;
+        let contract = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Recover"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let word = |v: U256| {
+            DynSolValue::FixedBytes(Hash::from(v.to_be_bytes::<32>()), 32)
+        };
+        let ret = HighLevelCaller::default()
+            .bypass_check()
+            .set_evm_version(SpecId::LATEST)
+            .view(
+                &mut state,
+                contract,
+                "recover(bytes32,uint8,bytes32,bytes32) returns (address)",
+                &[
+                    DynSolValue::FixedBytes(hash, 32),
+                    DynSolValue::Uint(U256::from(sig.v), 8),
+                    word(sig.r),
+                    word(sig.s),
+                ],
+                no_inspector(),
+            )
+            .unwrap();
+        assert_eq!(ret[0].as_address().unwrap(), signer);
+    }
+}
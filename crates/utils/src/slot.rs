@@ -0,0 +1,118 @@
+use alloy_dyn_abi::DynSolValue;
+use libsofl_core::engine::types::{keccak256, U256};
+
+/// The slot of `mapping[key]` where the mapping is stored at `base_slot`,
+/// i.e., `keccak256(key ++ base_slot)`.
+/// Value-type keys (e.g., address, uint256) must be padded to 32 bytes
+/// as in ABI encoding, while string and bytes keys are used as is.
+/// See `mapping_slot_of` to encode the key from its value.
+pub fn mapping_slot(base_slot: U256, key: &[u8]) -> U256 {
+    let mut preimage = Vec::with_capacity(key.len() + 32);
+    preimage.extend_from_slice(key);
+    preimage.extend_from_slice(&base_slot.to_be_bytes::<32>());
+    U256::from_be_bytes(keccak256(preimage).0)
+}
+
+/// Same as `mapping_slot`, but encodes the key from its value.
+/// Panics if the key is neither a value type, a string, nor bytes.
+pub fn mapping_slot_of(base_slot: U256, key: &DynSolValue) -> U256 {
+    match key {
+        DynSolValue::String(s) => mapping_slot(base_slot, s.as_bytes()),
+        DynSolValue::Bytes(b) => mapping_slot(base_slot, b),
+        v => {
+            let word = v
+                .as_word()
+                .expect("mapping keys must be value types, strings, or bytes");
+            mapping_slot(base_slot, word.as_slice())
+        }
+    }
+}
+
+/// The slot of `mapping[keys[0]][keys[1]]...` of nested mappings
+/// where the outermost mapping is stored at `base_slot`.
+pub fn nested_mapping_slot(base_slot: U256, keys: &[DynSolValue]) -> U256 {
+    keys.iter().fold(base_slot, mapping_slot_of)
+}
+
+/// The slot of `array[index]` of a dynamic array stored at `base_slot`,
+/// i.e., `keccak256(base_slot) + index`.
+/// For elements spanning multiple slots (e.g., structs),
+/// `index` should be multiplied by the number of slots of an element.
+/// Small elements (at most 16 bytes) are packed, which is not handled here.
+pub fn array_slot(base_slot: U256, index: U256) -> U256 {
+    let start = U256::from_be_bytes(keccak256(base_slot.to_be_bytes::<32>()).0);
+    start.wrapping_add(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_dyn_abi::DynSolValue;
+    use libsofl_core::{
+        conversion::ConvertTo,
+        engine::{
+            memory::MemoryBcState,
+            types::{Address, Database, U256},
+        },
+        solidity::scripting::{deploy_contracts, SolScriptConfig},
+    };
+
+    use super::{
+        array_slot, mapping_slot, mapping_slot_of, nested_mapping_slot,
+    };
+
+    #[test]
+    fn test_solidity_storage_slots() {
+        let mut state = MemoryBcState::fresh();
+        let code = r#"
+            contract Storage {
+                mapping(address => uint256) private balances;
+                mapping(string => uint256) private names;
+                mapping(address => mapping(uint256 => uint256)) private nested;
+                uint256[] private items;
+                constructor() {
+                    balances[address(0x1234)] = 1;
+                    names["alice"] = 2;
+                    nested[address(0x1234)][5] = 3;
+                    items.push(4);
+                    items.push(5);
+                }
+            }
+        "#;
+        let contract = deploy_contracts(
+            &mut state,
+            "0.8.12",
+            code,
+            vec!["Storage"],
+            SolScriptConfig::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let mut read = |slot: U256| state.storage(contract, slot).unwrap();
+
+        let account: Address = 0x1234.cvt();
+        let slot = mapping_slot(U256::ZERO, account.into_word().as_slice());
+        assert_eq!(
+            slot,
+            mapping_slot_of(U256::ZERO, &DynSolValue::Address(account))
+        );
+        assert_eq!(read(slot), U256::from(1));
+
+        let slot = mapping_slot(U256::from(1), b"alice");
+        assert_eq!(read(slot), U256::from(2));
+
+        let slot = nested_mapping_slot(
+            U256::from(2),
+            &[
+                DynSolValue::Address(account),
+                DynSolValue::Uint(U256::from(5), 256),
+            ],
+        );
+        assert_eq!(read(slot), U256::from(3));
+
+        // the length is stored at the base slot
+        assert_eq!(read(U256::from(3)), U256::from(2));
+        assert_eq!(read(array_slot(U256::from(3), U256::ZERO)), U256::from(4));
+        let slot = array_slot(U256::from(3), U256::from(1));
+        assert_eq!(read(slot), U256::from(5));
+    }
+}